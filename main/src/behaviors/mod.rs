@@ -0,0 +1,113 @@
+//! What each kind of NPC does with its turn, in order of priority; the first behavior which has
+//! something to do gets to do it
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum NpcKind {
+    Alien,
+}
+
+/// Distances are in steps, counted the long way round corners (so x and y added together)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Behavior {
+    /// Mill about at random; always has something to do, so it belongs last
+    Wander,
+    /// Walk a loop of the four points this far out from home
+    Patrol { radius: i32 },
+    /// Keep close to the player, once they're this close, but not closer than `keep`
+    Follow { within: i32, keep: i32 },
+    /// Get away from the player, when they're this close
+    Flee { within: i32 },
+    /// Head for the best air around, when out of breath (below this percentage of a full breath)
+    SeekAir { below_perc: usize },
+    /// Stay this close to home, and close in on the player if they come that close to it
+    Guard { radius: i32 },
+    /// Go and look where the player was last seen, if they were this close, for up to this many turns
+    Investigate { within: i32, turns: usize },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+struct NpcBehaviorsAddition {
+    npc: NpcKind,
+    behaviors: Vec<Behavior>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct NpcBehaviors {
+    behaviors: HashMap<NpcKind, Vec<Behavior>>,
+}
+
+/// Anything left out of the config just wanders, like NPCs always used to
+const DEFAULT_BEHAVIORS: [Behavior; 1] = [Behavior::Wander];
+
+impl NpcBehaviors {
+    pub fn from_ron(ron: &str) -> Result<NpcBehaviors, ()> {
+        let additions: Vec<NpcBehaviorsAddition> = ron::de::from_str(ron).map_err(|_e| {})?;
+
+        let mut behaviors = HashMap::new();
+
+        for addition in additions {
+            if behaviors.insert(addition.npc, addition.behaviors).is_some() {
+                return Err(());
+            }
+        }
+
+        Ok(NpcBehaviors { behaviors })
+    }
+
+    pub fn behaviors_for(&self, npc: NpcKind) -> &[Behavior] {
+        self.behaviors.get(&npc).map(|b| b.as_slice()).unwrap_or(&DEFAULT_BEHAVIORS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_test() {
+        let npcs = NpcBehaviors::from_ron("[]").expect("Should deserialize");
+
+        assert_eq!(&[Behavior::Wander], npcs.behaviors_for(NpcKind::Alien));
+    }
+
+    #[test]
+    fn parameters_test() {
+        let ron = r#"[
+            (npc: Alien, behaviors: [SeekAir(below_perc: 50), Patrol(radius: 3), Wander]),
+        ]"#;
+
+        let npcs = NpcBehaviors::from_ron(ron).expect("Should deserialize");
+
+        assert_eq!(
+            &[
+                Behavior::SeekAir { below_perc: 50 },
+                Behavior::Patrol { radius: 3 },
+                Behavior::Wander
+            ],
+            npcs.behaviors_for(NpcKind::Alien)
+        );
+    }
+
+    #[test]
+    fn duplicate_npc_test() {
+        let ron = r#"[
+            (npc: Alien, behaviors: [Wander]),
+            (npc: Alien, behaviors: [Flee(within: 2)]),
+        ]"#;
+
+        assert_eq!(Err(()), NpcBehaviors::from_ron(ron));
+    }
+
+    #[test]
+    fn config_npcs_test() {
+        let ron = include_str!("../../static/config/npcs.ron");
+
+        let npcs = NpcBehaviors::from_ron(ron).expect("The NPC config should deserialize");
+
+        assert!(!npcs.behaviors_for(NpcKind::Alien).is_empty());
+    }
+}
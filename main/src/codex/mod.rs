@@ -0,0 +1,125 @@
+//! Everything the player has found out about the station. Unlike the world, this isn't rolled back
+//! when the tether pulls the player back; it's their progress across loops, so it stays out of the
+//! world save.
+
+use serde::{Deserialize, Serialize};
+
+/// What a codex entry is about; the journal lists topics in this order
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum CodexTopic {
+    Lore,
+    Names,
+    DoorCodes,
+}
+
+pub const ALL_TOPICS: [CodexTopic; 3] = [CodexTopic::Lore, CodexTopic::Names, CodexTopic::DoorCodes];
+
+impl CodexTopic {
+    pub fn label(self) -> &'static str {
+        match self {
+            CodexTopic::Lore => "Lore",
+            CodexTopic::Names => "Names",
+            CodexTopic::DoorCodes => "Door Codes",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CodexEntry {
+    pub topic: CodexTopic,
+    /// Unique name for the entry, which dialogue and hacking check for
+    pub key: String,
+    pub title: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub struct Codex {
+    entries: Vec<CodexEntry>,
+}
+
+impl Codex {
+    /// Records the entry, if it isn't known already; returns whether it was new
+    pub fn learn(&mut self, entry: CodexEntry) -> bool {
+        if self.knows(&entry.key) {
+            return false;
+        }
+
+        self.entries.push(entry);
+        true
+    }
+
+    pub fn knows(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&CodexEntry> {
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+
+    /// Every known entry on the topic, sorted by title
+    pub fn entries_on(&self, topic: CodexTopic) -> Vec<&CodexEntry> {
+        let mut out: Vec<&CodexEntry> = self.entries.iter().filter(|entry| entry.topic == topic).collect();
+        out.sort_by(|a, b| a.title.cmp(&b.title));
+        out
+    }
+
+    /// The topics with anything known about them, in journal order, with how many entries each has
+    pub fn topics(&self) -> Vec<(CodexTopic, usize)> {
+        ALL_TOPICS
+            .iter()
+            .map(|&topic| (topic, self.entries.iter().filter(|entry| entry.topic == topic).count()))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(topic: CodexTopic, key: &str, title: &str) -> CodexEntry {
+        CodexEntry {
+            topic,
+            key: key.to_string(),
+            title: title.to_string(),
+            text: String::new(),
+        }
+    }
+
+    #[test]
+    fn learn_once_test() {
+        let mut codex = Codex::default();
+
+        assert!(!codex.knows("a"));
+        assert!(codex.learn(entry(CodexTopic::Lore, "a", "A")));
+        assert!(codex.knows("a"));
+        assert!(!codex.learn(entry(CodexTopic::Names, "a", "Another A")));
+
+        assert_eq!(Some(CodexTopic::Lore), codex.get("a").map(|e| e.topic));
+    }
+
+    #[test]
+    fn entries_on_sorts_by_title_test() {
+        let mut codex = Codex::default();
+        codex.learn(entry(CodexTopic::Lore, "z", "Zebra"));
+        codex.learn(entry(CodexTopic::Names, "n", "Name"));
+        codex.learn(entry(CodexTopic::Lore, "a", "Aardvark"));
+
+        let titles: Vec<&str> = codex.entries_on(CodexTopic::Lore).iter().map(|e| e.title.as_str()).collect();
+
+        assert_eq!(vec!["Aardvark", "Zebra"], titles);
+    }
+
+    #[test]
+    fn topics_test() {
+        let mut codex = Codex::default();
+        assert_eq!(Vec::<(CodexTopic, usize)>::new(), codex.topics());
+
+        codex.learn(entry(CodexTopic::DoorCodes, "d", "D"));
+        codex.learn(entry(CodexTopic::Lore, "a", "A"));
+        codex.learn(entry(CodexTopic::Lore, "b", "B"));
+
+        assert_eq!(vec![(CodexTopic::Lore, 2), (CodexTopic::DoorCodes, 1)], codex.topics());
+    }
+}
@@ -3,7 +3,8 @@ use super::*;
 use quicksilver::graphics::Color;
 use serde::{Deserialize, Serialize};
 use specs::{
-    saveload::{SimpleMarker, SimpleMarkerAllocator},
+    error::NoError,
+    saveload::{ConvertSaveload, Marker, SimpleMarker, SimpleMarkerAllocator},
     storage::{DenseVecStorage, HashMapStorage, NullStorage, VecStorage},
 };
 
@@ -56,12 +57,29 @@ pub struct Hackable {
     // TODO: why can't this be a &'static str? Something about deriving de/serialize not work.
     pub name: String,
     pub hack_state: HackState,
+    // how hard this is to hack; higher is harder
+    pub security: usize,
+    // how many hacks have gone wrong on this so far
+    pub failed_attempts: usize,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl Hackable {
+    pub fn new(name: &str, security: usize) -> Hackable {
+        Hackable {
+            name: name.to_owned(),
+            hack_state: HackState::Uncompromised,
+            security,
+            failed_attempts: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum HackState {
     Uncompromised,
     Compromised,
+    // too many failed hacks; this won't accept anything from you anymore
+    LockedOut,
 }
 
 #[derive(Component, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
@@ -125,27 +143,146 @@ pub enum NPC {
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
-pub enum AlienAI {
-    Wander,
+pub struct AlienAI {
+    // where it started out; patrolling and guarding are done around here
+    pub home: TilePos,
+    // whatever it was in the middle of last turn, so it can carry on with it
+    pub state: BehaviorState,
+}
+
+impl AlienAI {
+    pub fn new(home: TilePos) -> AlienAI {
+        AlienAI {
+            home,
+            state: BehaviorState::Idle,
+        }
+    }
+}
+
+// The parts of what an NPC is doing which last longer than a single turn
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BehaviorState {
+    Idle,
+    // heading for the nth point of its patrol
+    Patrolling { waypoint: usize },
+    // going to look at where it last saw the player, giving up after a while
+    Investigating { spot: TilePos, turns_left: usize },
 }
 
 #[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[storage(DenseVecStorage)]
 pub struct OxygenContainer {
-    pub capacity: usize, // how much air the entity can hold
-    pub contents: usize, // how much air the entity currently has
+    pub capacity: usize,      // how much of any one gas the entity can hold
+    pub contents: GasMixture, // how much of each gas the entity currently has
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Gas {
+    Oxygen,
+    CarbonDioxide,
+    Nitrogen,
+    Toxin,
+}
+
+impl Gas {
+    pub const ALL: [Gas; 4] = [Gas::Oxygen, Gas::CarbonDioxide, Gas::Nitrogen, Gas::Toxin];
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GasMixture {
+    pub oxygen: usize,
+    pub carbon_dioxide: usize,
+    pub nitrogen: usize,
+    pub toxin: usize,
+}
+
+impl GasMixture {
+    // What a freshly-generated, healthy tile of the station contains
+    pub fn station_air() -> GasMixture {
+        GasMixture {
+            oxygen: constants::oxygen::DEFAULT_FULL_OXYGEN,
+            nitrogen: constants::oxygen::DEFAULT_FULL_NITROGEN,
+            ..Default::default()
+        }
+    }
+
+    pub fn get(&self, gas: Gas) -> usize {
+        match gas {
+            Gas::Oxygen => self.oxygen,
+            Gas::CarbonDioxide => self.carbon_dioxide,
+            Gas::Nitrogen => self.nitrogen,
+            Gas::Toxin => self.toxin,
+        }
+    }
+
+    pub fn get_mut(&mut self, gas: Gas) -> &mut usize {
+        match gas {
+            Gas::Oxygen => &mut self.oxygen,
+            Gas::CarbonDioxide => &mut self.carbon_dioxide,
+            Gas::Nitrogen => &mut self.nitrogen,
+            Gas::Toxin => &mut self.toxin,
+        }
+    }
+
+    // Total pressure is just the sum of the partial pressures
+    pub fn pressure(&self) -> usize {
+        Gas::ALL.iter().map(|&gas| self.get(gas)).sum()
+    }
+}
+
+impl std::ops::AddAssign<GasMixture> for GasMixture {
+    fn add_assign(&mut self, rhs: GasMixture) {
+        for &gas in Gas::ALL.iter() {
+            *self.get_mut(gas) += rhs.get(gas);
+        }
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[storage(VecStorage)]
+pub struct Temperature {
+    pub degrees: f32, // roughly celsius
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[storage(NullStorage)]
+pub struct Insulated; // heat only leaks very slowly into or out of this square (walls and doors)
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct HeatSource {
+    pub setting: HeatSetting,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum HeatSetting {
+    Heat,
+    Cool,
+    Off,
+    // runs hot enough to set itself on fire
+    Overload,
 }
 
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Flammable {
+    pub fuel: usize, // how many more turns this can burn before it's burnt up
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[storage(NullStorage)]
+pub struct Burning;
+
 #[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[storage(HashMapStorage)]
 pub struct Breathes {
     pub capacity: usize,
     pub contents: usize,
-    // +2 per tick if there is at least this much air in their tile
+    // +2 per tick if there is at least this much oxygen in their tile
     pub fast_gain_threshold: usize,
-    // +1 per tick if there is at least this much air in their tile
+    // +1 per tick if there is at least this much oxygen in their tile
     pub slow_gain_threshold: usize,
-    // -1 per tick if there is at least this much air in their tile
+    // -1 per tick if there is at least this much oxygen in their tile
     pub slow_drop_threshold: usize,
     // -2 per tick if the above is not satisfied
 }
@@ -168,10 +305,36 @@ pub enum CanSuffocate {
     // enum determining the behavior of what happens when they _do_ suffocate
     // Player: triggers player death things
     Player,
-    // something else: just dies (entity is deleted)
+    // something else: just dies (and leaves a corpse behind)
     Death,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DeathCause {
+    Air,
+    Cold,
+    Heat,
+    Fire,
+}
+
+// What's left of something that died; it keeps whatever it was carrying, for anyone who searches it
+#[derive(Component, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Corpse {
+    pub name: String,
+    pub cause: DeathCause,
+    // something a careful look turns up about how it got here
+    pub clue: Option<String>,
+}
+
+// Something with a piece of the station's story written on it, which the player can read
+#[derive(Component, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Readable {
+    // the entry in the lore database
+    pub lore_id: String,
+}
+
 #[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
 #[storage(NullStorage)]
 pub struct Vacuum; // oxygen container which destroys all its oxygen each timestep
@@ -181,6 +344,8 @@ pub struct Vacuum; // oxygen container which destroys all its oxygen each timest
 pub struct Door {
     pub door_state: DoorState,
     pub door_behavior: DoorBehavior,
+    // who the door will open for, when it's opening automatically
+    pub access: DoorAccess,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -189,7 +354,6 @@ pub enum DoorState {
     Closed,
 }
 
-#[allow(dead_code)] // Leaving these in as I sort of want to use them later, but not yet
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum DoorBehavior {
     FullAuto,
@@ -199,10 +363,351 @@ pub enum DoorBehavior {
     StayOpen,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DoorAccess {
+    Everyone,
+    PlayerOnly,
+    NoAliens,
+    // only for someone carrying a keycard
+    Keycard,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[storage(NullStorage)]
+pub struct HasKeycard; // doors which need a keycard will open for this
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Window {
+    pub window_state: WindowState,
+    pub integrity: usize, // when this hits zero, the window breaks
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum WindowState {
+    Closed,
+    Open,
+    // a hole in the hull; air (and anything it carries along) goes right through
+    Broken,
+}
+
+impl Window {
+    pub fn take_damage(&mut self, damage: usize) {
+        self.integrity = self.integrity.saturating_sub(damage);
+        if self.integrity == 0 {
+            self.window_state = WindowState::Broken;
+        }
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct LifeSupport {
+    pub kind: LifeSupportKind,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LifeSupportKind {
+    // adds oxygen to the air around it
+    OxygenGenerator,
+    // removes carbon dioxide (and toxins) from the air around it
+    Scrubber,
+    // shares air with every other vent on the same duct network
+    Vent { network: usize },
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct NetworkDevice {
+    // anything on the same network can be controlled from a compromised terminal on it
+    pub network: usize,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Terminal {
+    // the network of devices this can reach, once it's compromised
+    pub network: usize,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct SecurityCamera {
+    pub range: i32,
+    // once the player has tapped into the feed, whatever this sees, they see
+    pub feed_tapped: bool,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Breakable {
+    // wears down as the thing is used; when it hits zero, the thing is Broken
+    pub condition: usize,
+    pub max_condition: usize,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Repairable {
+    // the Repair level it takes to fix this at all
+    pub skill_level: usize,
+    // how many spare parts fixing it uses up
+    pub parts: usize,
+    // how long it takes someone with just enough skill to fix it
+    pub turns: usize,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct SpareParts {
+    pub count: usize,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[storage(NullStorage)]
+pub struct Broken; // broken things don't do anything until they're repaired
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct PowerGenerator {
+    pub output: usize, // how much load this can carry, when it's running
+    pub enabled: bool,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[storage(NullStorage)]
+pub struct Conduit; // carries power to and from anything in this square, and to adjacent conduits
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct PowerConsumer {
+    pub load: usize,
+    // when there isn't enough power to go around, the lowest priority things lose it first
+    pub priority: PowerPriority,
+    // whether this is hooked up to the grid at all
+    pub connected: bool,
+    // whether this actually got power this turn; set by the power system
+    pub powered: bool,
+}
+
+impl PowerConsumer {
+    pub fn new(load: usize, priority: PowerPriority) -> PowerConsumer {
+        PowerConsumer {
+            load,
+            priority,
+            connected: true,
+            powered: true,
+        }
+    }
+}
+
+// Ordered from least to most important
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum PowerPriority {
+    Low,
+    Normal,
+    High,
+    Critical,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct LightSource {
+    pub radius: i32, // light fades out evenly, reaching nothing at all just past this distance
+    pub enabled: bool,
+}
+
 #[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
 #[storage(NullStorage)]
 pub struct OpensDoors;
 
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Pushable {
+    // loose objects get stuck in a breach (and seal it); people just get blown through
+    pub plugs_breaches: bool,
+}
+
+#[derive(Component, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Item {
+    pub name: String,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[storage(NullStorage)]
+pub struct Keycard; // an item which opens keycard doors for whoever is carrying it
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum EquipSlot {
+    Head,
+    Body,
+    Hands,
+    Back,
+}
+
+// What wearing something does for you; these add up across everything worn
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EquipModifiers {
+    // a sealed helmet feeds you air with this much oxygen in it, whatever the air outside is like
+    pub breathed_oxygen: Option<usize>,
+    // added to however far the wearer's own light reaches
+    pub light_radius: i32,
+    // hacks take this much (percent) less time
+    pub hack_speedup_perc: usize,
+    // a sealed suit keeps out the heat and cold, and can be hooked up to a tank
+    pub sealed: bool,
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct Equippable {
+    pub slot: EquipSlot,
+    pub modifiers: EquipModifiers,
+}
+
+// A portable supply of oxygen; a sealed suit breathes from this when the air outside gets thin
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[storage(HashMapStorage)]
+pub struct OxygenTank {
+    pub capacity: usize,
+    pub contents: usize,
+}
+
+// What someone is wearing; worn things are still in their inventory as well
+#[derive(Component, Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[storage(HashMapStorage)]
+pub struct Equipment {
+    pub head: Option<Entity>,
+    pub body: Option<Entity>,
+    pub hands: Option<Entity>,
+    pub back: Option<Entity>,
+}
+
+impl Equipment {
+    pub fn slot_mut(&mut self, slot: EquipSlot) -> &mut Option<Entity> {
+        match slot {
+            EquipSlot::Head => &mut self.head,
+            EquipSlot::Body => &mut self.body,
+            EquipSlot::Hands => &mut self.hands,
+            EquipSlot::Back => &mut self.back,
+        }
+    }
+
+    pub fn worn(&self) -> impl Iterator<Item = Entity> {
+        vec![self.head, self.body, self.hands, self.back].into_iter().flatten()
+    }
+
+    pub fn is_wearing(&self, item: Entity) -> bool {
+        self.worn().any(|worn| worn == item)
+    }
+
+    pub fn take_off(&mut self, item: Entity) {
+        for slot in [&mut self.head, &mut self.body, &mut self.hands, &mut self.back] {
+            if *slot == Some(item) {
+                *slot = None;
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EquipmentData<M> {
+    head: Option<M>,
+    body: Option<M>,
+    hands: Option<M>,
+    back: Option<M>,
+}
+
+impl<M: Marker + Serialize> ConvertSaveload<M> for Equipment
+where
+    for<'de> M: Deserialize<'de>,
+{
+    type Data = EquipmentData<M>;
+    type Error = NoError;
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(EquipmentData {
+            head: self.head.and_then(&mut ids),
+            body: self.body.and_then(&mut ids),
+            hands: self.hands.and_then(&mut ids),
+            back: self.back.and_then(&mut ids),
+        })
+    }
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(Equipment {
+            head: data.head.and_then(&mut ids),
+            body: data.body.and_then(&mut ids),
+            hands: data.hands.and_then(&mut ids),
+            back: data.back.and_then(&mut ids),
+        })
+    }
+}
+
+// Things being carried don't have a position of their own; they get one back when they're dropped
+#[derive(Component, Debug, Clone, Eq, PartialEq)]
+#[storage(HashMapStorage)]
+pub struct Inventory {
+    pub items: Vec<Entity>,
+    pub capacity: usize,
+}
+
+impl Inventory {
+    pub fn new(capacity: usize) -> Inventory {
+        Inventory {
+            items: Vec::new(),
+            capacity,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= self.capacity
+    }
+}
+
+// Inventories point at other entities, so they have to go through the save markers
+#[derive(Serialize, Deserialize)]
+pub struct InventoryData<M> {
+    items: Vec<M>,
+    capacity: usize,
+}
+
+impl<M: Marker + Serialize> ConvertSaveload<M> for Inventory
+where
+    for<'de> M: Deserialize<'de>,
+{
+    type Data = InventoryData<M>;
+    type Error = NoError;
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(InventoryData {
+            items: self.items.iter().filter_map(|&item| ids(item)).collect(),
+            capacity: self.capacity,
+        })
+    }
+
+    fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(Inventory {
+            items: data.items.into_iter().filter_map(ids).collect(),
+            capacity: data.capacity,
+        })
+    }
+}
+
 #[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[storage(HashMapStorage)]
 pub struct Camera {
@@ -1,10 +1,15 @@
 pub mod oxygen {
-    // By default, all oxygen containers have this container
+    // By default, all oxygen containers start with this much of each gas
     pub const DEFAULT_FULL_OXYGEN: usize = 60;
+    pub const DEFAULT_FULL_NITROGEN: usize = 220;
 
-    // Constants describing the speed od the oxygen spread system
+    // The most of any one gas a tile can hold
+    pub const DEFAULT_CONTAINER_CAPACITY: usize = DEFAULT_FULL_OXYGEN + DEFAULT_FULL_NITROGEN;
+
+    // Constants describing the speed od the oxygen spread system; each iteration, neighboring
+    // squares trade 1/DIFFUSION_DIVISOR of the difference between them (this must be at least 4)
     pub const OXYGEN_SYSTEM_ITERATIONS: usize = 10;
-    pub const OXYGEN_SYSTEM_SHARE_PER_ITERATION: usize = 2;
+    pub const DIFFUSION_DIVISOR: usize = 8;
 
     // Constants describing breath; when and how fast
     // the breath level changes
@@ -18,4 +23,298 @@ pub mod oxygen {
     pub const SLOW_DROP_THRESHOLD: usize = 20;
 
     pub const FAST_DROP_SPEED: usize = 2;
+
+    // Each breath (one per tick) turns this much oxygen in the tile into carbon dioxide
+    pub const OXYGEN_PER_BREATH: usize = 1;
+
+    // Too much carbon dioxide stops you from catching your breath, and eventually suffocates you
+    // regardless of how much oxygen is around
+    pub const CARBON_DIOXIDE_SLOW_DROP_THRESHOLD: usize = 30;
+    pub const CARBON_DIOXIDE_FAST_DROP_THRESHOLD: usize = 60;
+
+    // Any real amount of toxin is bad news
+    pub const TOXIN_FAST_DROP_THRESHOLD: usize = 10;
+}
+
+pub mod temperature {
+    // Where the station sits when everything is working
+    pub const DEFAULT_STATION_TEMPERATURE: f32 = 20.0;
+    // Where everything ends up if nothing is working
+    pub const SPACE_TEMPERATURE: f32 = -270.0;
+
+    // How much of the temperature difference between two neighboring squares evens out in one
+    // iteration; open squares share heat quickly, insulated squares (walls) barely at all, and
+    // squares next to open space slowly radiate their heat away
+    pub const OPEN_CONDUCTANCE: f32 = 0.1;
+    pub const INSULATED_CONDUCTANCE: f32 = 0.0002;
+    pub const SPACE_CONDUCTANCE: f32 = 0.0001;
+
+    // Constants describing the speed of the heat spread system
+    pub const HEAT_SYSTEM_ITERATIONS: usize = 5;
+
+    // Heaters and coolers push their square toward these, by at most this much per turn
+    pub const HEATER_TARGET: f32 = 35.0;
+    pub const COOLER_TARGET: f32 = 0.0;
+    pub const HEAT_SOURCE_POWER: f32 = 10.0;
+
+    // Outside of this range, breathers can't catch their breath
+    pub const MILD_COLD_THRESHOLD: f32 = 5.0;
+    pub const MILD_HEAT_THRESHOLD: f32 = 40.0;
+
+    // Outside of this range, breathers quickly succumb
+    pub const SEVERE_COLD_THRESHOLD: f32 = -10.0;
+    pub const SEVERE_HEAT_THRESHOLD: f32 = 55.0;
+}
+
+pub mod fire {
+    // Fire goes out if there is less oxygen than this in its square
+    pub const MIN_OXYGEN: usize = 10;
+    // Each turn, a fire turns this much oxygen in its square into carbon dioxide
+    pub const OXYGEN_PER_TURN: usize = 4;
+
+    // Chance (out of 100) per turn to spread to an adjacent flammable, if its square has
+    // a full load of oxygen; less oxygen means proportionately less chance
+    pub const SPREAD_CHANCE_PERC: usize = 30;
+
+    // Burning squares are at least this hot
+    pub const FIRE_TEMPERATURE: f32 = 300.0;
+
+    // Breathers next to (or in) a fire lose this much breath per turn
+    pub const BURN_DAMAGE: usize = 5;
+
+    // How long generated things can burn
+    pub const RUBBISH_FUEL: usize = 20;
+    pub const HEATER_FUEL: usize = 10;
+}
+
+pub mod decompression {
+    // Anything pushable gets shoved one square (toward the lowest pressure) each turn the difference
+    // in air pressure around it is at least this much
+    pub const PUSH_PRESSURE_DIFFERENCE: usize = 40;
+}
+
+pub mod window {
+    pub const MAX_INTEGRITY: usize = 20;
+
+    // A pane holds back this much difference in air pressure (station on one side, space on the
+    // other) per point of integrity, and takes damage each turn the difference is any more. A new
+    // pane holds a full room of air with some to spare, but once it's been knocked about a bit,
+    // ordinary air pressure is enough to finish it off.
+    pub const PRESSURE_PER_INTEGRITY: usize =
+        (super::oxygen::DEFAULT_FULL_OXYGEN + super::oxygen::DEFAULT_FULL_NITROGEN) * 5 / 4 / MAX_INTEGRITY;
+    pub const PRESSURE_DAMAGE: usize = 1;
+
+    // Damage from anything decompression slams into a window
+    pub const IMPACT_DAMAGE: usize = 5;
+
+    // Windows take damage each turn that they're hotter than this (e.g. from a nearby fire)
+    pub const SAFE_TEMPERATURE: f32 = 150.0;
+    pub const HEAT_DAMAGE: usize = 2;
+}
+
+pub mod life_support {
+    // How much gas a working machine can move into or out of the air around it, per turn
+    pub const GENERATOR_OXYGEN_PER_TURN: usize = 30;
+    pub const SCRUBBER_GAS_PER_TURN: usize = 30;
+
+    // Machines wear down by one, with this chance (out of 100), every turn they're running
+    pub const WEAR_CHANCE_PERC: usize = 10;
+    pub const MAX_CONDITION: usize = 200;
+}
+
+pub mod power {
+    use crate::components::PowerPriority;
+
+    // What a single generator puts out when it's running
+    pub const GENERATOR_OUTPUT: usize = 30;
+
+    // What each kind of machine draws, and how much it matters
+    pub const DOOR_LOAD: usize = 1;
+    pub const DOOR_PRIORITY: PowerPriority = PowerPriority::High;
+
+    pub const HEATER_LOAD: usize = 2;
+    pub const HEATER_PRIORITY: PowerPriority = PowerPriority::Normal;
+
+    pub const LIFE_SUPPORT_LOAD: usize = 4;
+    pub const VENT_LOAD: usize = 1;
+    pub const LIFE_SUPPORT_PRIORITY: PowerPriority = PowerPriority::Critical;
+}
+
+pub mod lighting {
+    use crate::components::PowerPriority;
+
+    // How far each kind of light reaches
+    pub const CEILING_LIGHT_RADIUS: i32 = 8;
+    // the player's own light, and the extra reach a proper flashlight adds on top of it
+    pub const FLASHLIGHT_RADIUS: i32 = 3;
+    pub const FIRE_LIGHT_RADIUS: i32 = 4;
+
+    // Anything lit less than this (out of 1) is too dark to make out
+    pub const MIN_VISIBLE_LIGHT: f32 = 0.1;
+
+    // Even the dimmest visible square is drawn at least this bright (out of 1)
+    pub const MIN_SHADE: f32 = 0.35;
+
+    // Lights are the first thing to go when power runs short
+    pub const LIGHT_LOAD: usize = 1;
+    pub const LIGHT_PRIORITY: PowerPriority = PowerPriority::Low;
+
+    // Lights burn out much more slowly than life support wears down
+    pub const WEAR_CHANCE_PERC: usize = 1;
+    pub const MAX_CONDITION: usize = 200;
+}
+
+pub mod cameras {
+    use crate::components::PowerPriority;
+
+    // How far a security camera can see (as long as there's light to see by)
+    pub const CAMERA_RANGE: i32 = 8;
+
+    pub const CAMERA_LOAD: usize = 1;
+    pub const CAMERA_PRIORITY: PowerPriority = PowerPriority::Normal;
+}
+
+pub mod repair {
+    use crate::components::Repairable;
+
+    // What it takes to fix each kind of thing
+    pub const DOOR: Repairable = Repairable {
+        skill_level: 0,
+        parts: 1,
+        turns: 10,
+    };
+    pub const WINDOW: Repairable = Repairable {
+        skill_level: 1,
+        parts: 3,
+        turns: 30,
+    };
+    pub const LIFE_SUPPORT: Repairable = Repairable {
+        skill_level: 1,
+        parts: 2,
+        turns: 20,
+    };
+    pub const LIGHT: Repairable = Repairable {
+        skill_level: 0,
+        parts: 1,
+        turns: 5,
+    };
+    pub const CAMERA: Repairable = Repairable {
+        skill_level: 1,
+        parts: 1,
+        turns: 10,
+    };
+
+    // The player doesn't start empty-handed
+    pub const STARTING_SPARE_PARTS: usize = 6;
+
+    // Experience for a finished repair, per spare part it took
+    pub const EXP_PER_PART: usize = 10;
+}
+
+pub mod inventory {
+    // How many things someone can carry at once
+    pub const PLAYER_CAPACITY: usize = 8;
+    pub const NPC_CAPACITY: usize = 4;
+}
+
+pub mod corpses {
+    // Whether bodies get in the way, or can just be stepped over
+    pub const BLOCKS_MOVEMENT: bool = false;
+
+    pub const CORPSE_GLYPH: char = '%';
+
+    // What you find on the aliens left dead by a busted airlock
+    pub const AIRLOCK_CLUE: &str = "Its fingers are torn and bloody, as though it clawed at the airlock door until the end.";
+}
+
+pub mod codex {
+    // Knowing this gets you through any door on the station, no hacking skill required
+    pub const DOOR_OVERRIDE_CODE: &str = "code_door_override";
+
+    // Knowing what the aliens call themselves gives you something to say to them
+    pub const ALIEN_NAME: &str = "name_vaeli";
+
+    // How long it takes to punch in a code you already know
+    pub const ENTER_CODE_TURNS: usize = 1;
+}
+
+pub mod equipment {
+    // A helmet's rebreather gives you enough oxygen to slowly catch your breath
+    pub const HELMET_BREATHED_OXYGEN: usize = super::oxygen::SLOW_GAIN_THRESHOLD;
+
+    pub const HACKING_RIG_SPEEDUP_PERC: usize = 25;
+
+    // However much gear you pile on, hacks still take some time
+    pub const MAX_HACK_SPEEDUP_PERC: usize = 75;
+
+    // A full tank lasts this many turns of breathing
+    pub const TANK_CAPACITY: usize = 300;
+    pub const TANK_DRAW_PER_TURN: usize = 1;
+
+    // Breathing from a tank is as good as breathing fresh station air
+    pub const TANK_BREATHED_OXYGEN: usize = super::oxygen::FAST_GAIN_THRESHOLD;
+}
+
+pub mod hacking {
+    // How long hacks take for a novice, on something with a security rating of 1; more skill makes
+    // this faster, and more security makes it slower
+    pub const COMPROMISE_TURNS: usize = 60;
+    pub const RECONFIGURE_TURNS: usize = 5;
+
+    // Chance (out of 100) that a hack works, before skill and security come into it
+    pub const BASE_SUCCESS_PERC: usize = 85;
+    pub const SKILL_BONUS_PERC: usize = 10; // per skill level
+    pub const SECURITY_PENALTY_PERC: usize = 15; // per point of security
+
+    // Once something is compromised, getting it to do things is much easier
+    pub const RECONFIGURE_BONUS_PERC: usize = 20;
+    // No hack is ever a sure thing, or hopeless
+    pub const MIN_SUCCESS_PERC: usize = 5;
+    pub const MAX_SUCCESS_PERC: usize = 95;
+
+    // After this many failed hacks, the target locks you out for good
+    pub const MAX_FAILED_ATTEMPTS: usize = 3;
+
+    // Failing to hack anything this secure sets off an alarm, which locks down the doors around it
+    pub const ALARM_SECURITY: usize = 3;
+    pub const ALARM_RADIUS: i32 = 10;
+
+    // Experience for a successful hack, per point of security of the target
+    pub const EXP_PER_SECURITY: usize = 10;
+
+    // How secure each kind of thing is
+    pub const DOOR_SECURITY: usize = 1;
+    pub const AIRLOCK_SECURITY: usize = 3;
+    pub const HEATER_SECURITY: usize = 1;
+    pub const WINDOW_SECURITY: usize = 2;
+    pub const LIFE_SUPPORT_SECURITY: usize = 3;
+    pub const GENERATOR_SECURITY: usize = 4;
+    pub const LIGHT_SECURITY: usize = 1;
+    pub const TERMINAL_SECURITY: usize = 2;
+    pub const CAMERA_SECURITY: usize = 2;
+}
+
+pub mod ai {
+    // How far off NPCs notice the player, if they can see them
+    pub const PERCEPTION_RANGE: i32 = 8;
+
+    // How far off NPCs look for better air, when they're out of breath
+    pub const SEEK_AIR_RANGE: i32 = 6;
+}
+
+pub mod pathing {
+    // What it costs to take a step onto an ordinary tile
+    pub const STEP_COST: usize = 1;
+
+    // Extra cost of a step for anything that breathes, where there's too little air to catch your
+    // breath, and where there's so little that your breath runs out
+    pub const THIN_AIR_COST: usize = 3;
+    pub const NO_AIR_COST: usize = 12;
+
+    // Extra cost of walking through fire; better a long way round than that
+    pub const FIRE_COST: usize = 20;
+
+    // A planned route gets thrown out and planned again after this many turns, even if nothing
+    // is in the way yet, so it picks up on new dangers and shortcuts
+    pub const MAX_PATH_AGE_TURNS: u64 = 5;
 }
@@ -12,7 +12,10 @@ use specs::Builder;
 
 use loader::Loadable;
 
+use behaviors::NpcBehaviors;
+use lore::LoreDatabase;
 use resources::*;
+use skills::SkillTree;
 use world::{Map, MapGenerationParams, TilePos, WorldState};
 
 pub struct MainState {
@@ -21,7 +24,8 @@ pub struct MainState {
 }
 
 pub struct GameAssets {
-    world_params: Asset<MapGenerationParams>,
+    // the skill tree, lore and NPC behaviors are loaded alongside the map params, and go into the world with the map
+    world_params: Asset<(MapGenerationParams, SkillTree, LoreDatabase, NpcBehaviors)>,
     world_state_ready: resources::InitializationState,
 
     tileset: Asset<HashMap<char, Image>>,
@@ -37,6 +41,8 @@ pub struct ControlsImages {
     pub hack: Asset<Image>,
     pub talk: Asset<Image>,
     pub repair: Asset<Image>,
+    pub pick_up: Asset<Image>,
+    pub read: Asset<Image>,
 }
 
 pub struct DialogueAssets {
@@ -53,17 +59,31 @@ pub const FONT_MONONOKI_PATH: &str = "fonts/mononoki/mononoki-Regular.ttf";
 pub const FONT_SQUARE_PATH: &str = "fonts/square/square.ttf";
 
 // TODO: autogen this list somehow
-pub const ALL_GAME_GLYPHS: &str = "QWERTYUIOPASDFGHJKLZXCVBNMqwertyuiopasdfghjklzxcvbnm,.;:?%* █aAdD@I:`0123456789";
+pub const ALL_GAME_GLYPHS: &str = "QWERTYUIOPASDFGHJKLZXCVBNMqwertyuiopasdfghjklzxcvbnm,.;:?%* █aAdD@I:`0123456789='";
 
 fn render_mononoki(text: String, size: f32, color: Color) -> Asset<Image> {
     Asset::new(Font::load(FONT_MONONOKI_PATH).and_then(move |font| font.render(&text, &FontStyle::new(size, color))))
 }
 
 fn make_assets() -> GameAssets {
-    let world_params: Asset<MapGenerationParams> = Asset::new(load_file("config/map_params.ron").and_then(move |bytes| {
-        let map_gen_params = ron::de::from_bytes(&bytes).expect("Should deserialize");
-        Ok(map_gen_params)
-    }));
+    let world_params: Asset<(MapGenerationParams, SkillTree, LoreDatabase, NpcBehaviors)> = Asset::new(
+        load_file("config/map_params.ron")
+            .join4(
+                load_file("config/skills.ron"),
+                load_file("config/lore.ron"),
+                load_file("config/npcs.ron"),
+            )
+            .and_then(move |(map_bytes, skill_bytes, lore_bytes, npc_bytes)| {
+                let map_gen_params = ron::de::from_bytes(&map_bytes).expect("Should deserialize");
+                let skill_ron = String::from_utf8(skill_bytes).expect("Skills should be valid UTF-8");
+                let skill_tree = SkillTree::from_ron(&skill_ron).expect("Should deserialize");
+                let lore_ron = String::from_utf8(lore_bytes).expect("Lore should be valid UTF-8");
+                let lore = LoreDatabase::from_ron(&lore_ron).expect("Should deserialize");
+                let npc_ron = String::from_utf8(npc_bytes).expect("NPC behaviors should be valid UTF-8");
+                let npc_behaviors = NpcBehaviors::from_ron(&npc_ron).expect("Should deserialize");
+                Ok((map_gen_params, skill_tree, lore, npc_behaviors))
+            }),
+    );
 
     let render_params = GameMapRenderParams::default();
     let tile_size_px = render_params.tile_size_px;
@@ -86,6 +106,8 @@ fn make_assets() -> GameAssets {
         hack: render_mononoki("[H]ack".to_owned(), 18.0, Color::BLACK),
         repair: render_mononoki("[R]epair".to_owned(), 18.0, Color::BLACK),
         talk: render_mononoki("[T]alk".to_owned(), 18.0, Color::BLACK),
+        pick_up: render_mononoki("[G]et".to_owned(), 18.0, Color::BLACK),
+        read: render_mononoki("R[e]ad".to_owned(), 18.0, Color::BLACK),
     };
 
     GameAssets {
@@ -121,6 +143,7 @@ macro_rules! systems {
         // No-op systems for setting up render resources
         $method_name(&mut systems::CharsRendererSetup, $world_name);
         $method_name(&mut systems::ControlsRendererSetup, $world_name);
+        $method_name(&mut systems::InventoryRendererSetup, $world_name);
         $method_name(&mut systems::OxygenOverlaySetup, $world_name);
 
         // important update systems; order matters, be careful
@@ -130,6 +153,11 @@ macro_rules! systems {
         timed!("ToggleControl", $method_name(&mut systems::ToggleControlSystem, $world_name));
         timed!("ToggleHack", $method_name(&mut systems::ToggleHackSystem, $world_name));
         timed!("ToggleTalk", $method_name(&mut systems::ToggleTalkSystem, $world_name));
+        timed!("ToggleRepair", $method_name(&mut systems::ToggleRepairSystem, $world_name));
+        timed!("TogglePickUp", $method_name(&mut systems::TogglePickUpSystem, $world_name));
+        timed!("ToggleRead", $method_name(&mut systems::ToggleReadSystem, $world_name));
+        timed!("ToggleJournal", $method_name(&mut systems::ToggleJournalSystem, $world_name));
+        timed!("InventoryControl", $method_name(&mut systems::InventoryControlSystem, $world_name));
 
         timed!(
             "HackCallbackHandlerSystem",
@@ -141,13 +169,39 @@ macro_rules! systems {
             $method_name(&mut systems::TalkCallbackHandlerSystem, $world_name)
         );
 
+        timed!(
+            "RepairCallbackHandlerSystem",
+            $method_name(&mut systems::RepairCallbackHandlerSystem, $world_name)
+        );
+
+        timed!(
+            "ReadCallbackHandlerSystem",
+            $method_name(&mut systems::ReadCallbackHandlerSystem, $world_name)
+        );
+
+        timed!(
+            "JournalCallbackHandlerSystem",
+            $method_name(&mut systems::JournalCallbackHandlerSystem, $world_name)
+        );
+
+        timed!(
+            "InventoryCallbackHandlerSystem",
+            $method_name(&mut systems::InventoryCallbackHandlerSystem, $world_name)
+        );
+
         // non-players doing stuff
         timed!("NpcMoves", $method_name(&mut systems::NpcMoveSystem, $world_name));
 
         // various updates of inanimates
         timed!("Breathe", $method_name(&mut systems::BreatheSystem, $world_name));
+        timed!("Power", $method_name(&mut systems::PowerSystem, $world_name));
         timed!("DoorOpen", $method_name(&mut systems::DoorOpenSystem, $world_name));
+        timed!("WindowUpdate", $method_name(&mut systems::WindowUpdateSystem, $world_name));
+        timed!("LifeSupport", $method_name(&mut systems::LifeSupportSystem, $world_name));
         timed!("OxygenSpread", $method_name(&mut systems::OxygenSpreadSystem, $world_name));
+        timed!("Decompression", $method_name(&mut systems::DecompressionSystem, $world_name));
+        timed!("HeatSpread", $method_name(&mut systems::HeatSpreadSystem, $world_name));
+        timed!("Fire", $method_name(&mut systems::FireSystem, $world_name));
 
         timed!(
             "DialogueUpdateSystem",
@@ -156,6 +210,7 @@ macro_rules! systems {
 
         // bookkeeping stuff after things stop changing
         timed!("SpaceInserter", $method_name(&mut systems::FakeSpaceInserterSystem, $world_name)); // before vis, after stuff moves
+        timed!("Lighting", $method_name(&mut systems::LightingSystem, $world_name));
         timed!("Visibility", $method_name(&mut systems::VisibilitySystem, $world_name));
 
         // end of turn upkeep
@@ -195,7 +250,10 @@ impl MainState {
                 InitializationState::NotStarted => unimplemented!(), // TODO: make world state load like everything else?
                 InitializationState::Started => {
                     let mut init = InitializationState::Started;
-                    assets.world_params.execute(|params| {
+                    assets.world_params.execute(|(params, skill_tree, lore, npc_behaviors)| {
+                        world.insert::<SkillTree>(skill_tree.clone());
+                        world.insert::<LoreDatabase>(lore.clone());
+                        world.insert::<NpcBehaviors>(npc_behaviors.clone());
                         let map = Map::make_random(&params, world);
                         let world_state = WorldState::new(map);
                         world.insert::<WorldState>(world_state);
@@ -284,6 +342,8 @@ impl Loadable for ControlsImages {
         out &= self.hack.is_loaded()?;
         out &= self.talk.is_loaded()?;
         out &= self.repair.is_loaded()?;
+        out &= self.pick_up.is_loaded()?;
+        out &= self.read.is_loaded()?;
 
         Ok(out)
     }
@@ -333,12 +393,22 @@ impl State for MainState {
                 disabled: false,
             })
             .with(components::OpensDoors)
+            .with(components::Pushable { plugs_breaches: false })
             .with(components::Visible {
                 visibility: world::VisibilityType::CurrentlyVisible,
                 memorable: false,
             })
             .with(components::Breathes::default())
+            .with(components::LightSource {
+                radius: constants::lighting::FLASHLIGHT_RADIUS,
+                enabled: true,
+            })
             .with(components::CanSuffocate::Player)
+            .with(components::SpareParts {
+                count: constants::repair::STARTING_SPARE_PARTS,
+            })
+            .with(components::Inventory::new(constants::inventory::PLAYER_CAPACITY))
+            .with(components::Equipment::default())
             .with(components::Player {})
             .marked::<components::SaveComponent>()
             .build();
@@ -402,6 +472,12 @@ impl State for MainState {
             }
             .run_now(&self.world);
 
+            systems::InventoryRenderer {
+                window,
+                tileset: &mut self.assets.tileset,
+            }
+            .run_now(&self.world);
+
             window.flush()?;
 
             if let Some(dialogue_assets) = self.assets.dialogue_assets.as_mut() {
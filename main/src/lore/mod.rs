@@ -0,0 +1,155 @@
+//! Pieces of the station's story, written on notes and stored on terminals, which the player
+//! reads to work out what happened here
+
+use serde::{Deserialize, Serialize};
+
+use super::codex::{CodexEntry, CodexTopic};
+
+/// Where a piece of lore turns up on the station
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LoreSource {
+    Note,
+    Terminal,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LoreEntry {
+    /// Unique name for the entry, which readable things refer to it by
+    pub id: String,
+    pub title: String,
+    pub source: LoreSource,
+    pub text: String,
+    /// Anything else the player picks up from reading it, like a name or a door code
+    #[serde(default)]
+    pub teaches: Vec<CodexEntry>,
+}
+
+impl LoreEntry {
+    /// What reading this adds to the codex: the entry itself, and whatever it teaches
+    pub fn codex_entries(&self) -> Vec<CodexEntry> {
+        let mut out = vec![CodexEntry {
+            topic: CodexTopic::Lore,
+            key: self.id.clone(),
+            title: self.title.clone(),
+            text: self.text.clone(),
+        }];
+        out.extend(self.teaches.iter().cloned());
+        out
+    }
+}
+
+/// All the lore in the game, in the order it's listed in the config; entries are handed out to
+/// the notes and terminals of each source in that order
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct LoreDatabase {
+    entries: Vec<LoreEntry>,
+}
+
+impl LoreDatabase {
+    pub fn from_ron(ron: &str) -> Result<LoreDatabase, ()> {
+        let entries: Vec<LoreEntry> = ron::de::from_str(ron).map_err(|_e| {})?;
+
+        for (i, entry) in entries.iter().enumerate() {
+            if entries[i + 1..].iter().any(|other| other.id == entry.id) {
+                return Err(());
+            }
+        }
+
+        Ok(LoreDatabase { entries })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&LoreEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// The nth entry which turns up in the given kind of place, if there are that many
+    pub fn nth_from(&self, source: LoreSource, n: usize) -> Option<&LoreEntry> {
+        self.entries.iter().filter(|entry| entry.source == source).nth(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_SOURCES: &str = r#"[
+        (id: "a", title: "A", source: Note, text: "first note"),
+        (id: "b", title: "B", source: Terminal, text: "first terminal"),
+        (id: "c", title: "C", source: Note, text: "second note"),
+    ]"#;
+
+    #[test]
+    fn empty_test() {
+        let actual = LoreDatabase::from_ron("[]").expect("Should deserialize");
+
+        assert_eq!(LoreDatabase::default(), actual);
+    }
+
+    #[test]
+    fn get_test() {
+        let lore = LoreDatabase::from_ron(TWO_SOURCES).expect("Should deserialize");
+
+        assert_eq!(Some("second note"), lore.get("c").map(|entry| entry.text.as_str()));
+        assert_eq!(None, lore.get("d"));
+    }
+
+    #[test]
+    fn nth_from_test() {
+        let lore = LoreDatabase::from_ron(TWO_SOURCES).expect("Should deserialize");
+
+        let id = |source, n| lore.nth_from(source, n).map(|entry| entry.id.as_str());
+
+        assert_eq!(Some("a"), id(LoreSource::Note, 0));
+        assert_eq!(Some("c"), id(LoreSource::Note, 1));
+        assert_eq!(None, id(LoreSource::Note, 2));
+        assert_eq!(Some("b"), id(LoreSource::Terminal, 0));
+        assert_eq!(None, id(LoreSource::Terminal, 1));
+    }
+
+    #[test]
+    fn codex_entries_test() {
+        let ron = r#"[
+            (
+                id: "a",
+                title: "A",
+                source: Note,
+                text: "one",
+                teaches: [(topic: Names, key: "name_b", title: "B", text: "someone")],
+            ),
+        ]"#;
+        let lore = LoreDatabase::from_ron(ron).expect("Should deserialize");
+
+        let learned: Vec<(CodexTopic, String)> = lore
+            .get("a")
+            .expect("Should be there")
+            .codex_entries()
+            .into_iter()
+            .map(|entry| (entry.topic, entry.key))
+            .collect();
+
+        assert_eq!(
+            vec![(CodexTopic::Lore, "a".to_string()), (CodexTopic::Names, "name_b".to_string())],
+            learned
+        );
+    }
+
+    #[test]
+    fn duplicate_id_test() {
+        let ron = r#"[
+            (id: "a", title: "A", source: Note, text: "one"),
+            (id: "a", title: "A", source: Terminal, text: "two"),
+        ]"#;
+
+        assert_eq!(Err(()), LoreDatabase::from_ron(ron));
+    }
+
+    #[test]
+    fn config_lore_test() {
+        let ron = include_str!("../../static/config/lore.ron");
+
+        let lore = LoreDatabase::from_ron(ron).expect("The lore config should deserialize");
+
+        assert!(lore.nth_from(LoreSource::Note, 0).is_some());
+        assert!(lore.nth_from(LoreSource::Terminal, 0).is_some());
+    }
+}
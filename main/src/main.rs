@@ -10,6 +10,8 @@ use specs::{prelude::*, saveload::MarkedBuilder};
 
 use serde::{Deserialize, Serialize};
 
+use worldgen::rng;
+
 use quicksilver::{
     geom::*,
     graphics::{Background::*, Color},
@@ -23,11 +25,13 @@ mod timer;
 #[macro_use]
 mod components;
 
+mod behaviors;
+mod codex;
 mod constants;
 mod game_state;
+mod lore;
 mod numerics;
 mod resources;
-mod rng;
 mod skills;
 mod systems;
 mod world;
@@ -1,6 +1,9 @@
 use super::*;
 
-use std::collections::VecDeque;
+use std::collections::HashMap;
+
+use codex::CodexTopic;
+use world::TilePos;
 
 #[derive(Clone)]
 pub struct RenderStale(pub bool);
@@ -11,6 +14,19 @@ impl Default for RenderStale {
     }
 }
 
+// How brightly lit each square is, from 0 (pitch black) up to 1; recomputed as things change,
+// so this is never saved
+#[derive(Clone, Debug, Default)]
+pub struct LightMap {
+    pub levels: HashMap<TilePos, f32>,
+}
+
+impl LightMap {
+    pub fn level(&self, pos: TilePos) -> f32 {
+        self.levels.get(&pos).copied().unwrap_or(0.0)
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct SavedStates {
     pub saves: Vec<SaveGameData>,
@@ -33,6 +49,12 @@ impl Default for GameClock {
     }
 }
 
+impl GameClock {
+    pub fn total_seconds(&self) -> u64 {
+        ((self.hours * 60 + self.minutes) * 60 + self.seconds) as u64
+    }
+}
+
 #[derive(Clone)]
 pub struct SaveGameData {
     // components, saved
@@ -132,6 +154,14 @@ pub enum Callback {
     Hack(HackCallback),
     // rollup for talk callbacks
     Talk(TalkCallback),
+    // rollup for repair callbacks
+    Repair(RepairCallback),
+    // rollup for picking things up and putting them down
+    Inventory(InventoryCallback),
+    // rollup for reading notes and terminals
+    Read(ReadCallback),
+    // rollup for looking through the journal
+    Journal(JournalCallback),
     // request to save the game; handled by SaveSystem
     SaveGame,
     // request to load the game; handled by LoadSystem
@@ -145,27 +175,88 @@ pub enum HackCallback {
     // when you select a hack target and you need to see a more specific menu about
     // how you want to hack it
     ChooseHackTarget { entity: Entity },
+    // when you pick a device off a compromised terminal's network, and need to see what you can do with it
+    ChooseRemoteTarget { terminal: Entity, entity: Entity },
     // when you actually select the hack type and start hacking
     InitiateHack { target: HackTarget, turn_duration: usize },
 }
 
+#[derive(Clone, Debug)]
+pub enum RepairCallback {
+    // when you select a broken thing, and need to see what it would take to fix it
+    ChooseRepairTarget { entity: Entity },
+    // when you decide to go ahead, and start repairing it
+    InitiateRepair { target: Entity, turn_duration: usize },
+}
+
 #[derive(Clone, Debug)]
 pub enum TalkCallback {
     ChooseTalkTarget { entity: Entity },
 }
 
+#[derive(Clone, Debug)]
+pub enum ReadCallback {
+    ChooseReadTarget { entity: Entity },
+}
+
+#[derive(Clone, Debug)]
+pub enum JournalCallback {
+    // the list of topics the player knows anything about
+    Contents,
+    // everything known about one topic
+    OpenTopic { topic: CodexTopic },
+    // a single entry, in full
+    OpenEntry { key: String },
+}
+
+#[derive(Clone, Debug)]
+pub enum InventoryCallback {
+    // the player picks up something lying next to them
+    PickUp { item: Entity },
+    // the player puts down something they're carrying, where they're standing
+    Drop { item: Entity },
+    // the player puts on something they're carrying (or takes it off, if they're already wearing it)
+    ToggleWorn { item: Entity },
+    // the player takes a closer look at a body, and what it's carrying
+    Search { corpse: Entity },
+    // the player takes something a body was carrying
+    TakeFrom { corpse: Entity, item: Entity },
+}
+
 #[derive(Clone, Debug)]
 pub struct HackTarget {
     pub entity: Entity,
     pub hack_type: HackType,
+    // the compromised terminal this is being done through, if it isn't being done directly
+    pub via: Option<Entity>,
 }
 
 #[derive(Clone, Debug)]
 pub enum HackType {
     // things must be compromised before then can be messed with
     Compromise,
+    // compromise something with a code the player already knows, which always works
+    EnterCode,
     // set the behavior of a door
     Door { new_door_behavior: components::DoorBehavior },
+    // change who a door will open for
+    DoorAccess { new_access: components::DoorAccess },
+    // start or stop watching what a security camera sees
+    CameraFeed { tapped: bool },
+    // change what a heater (or cooler) is doing
+    HeatSource { new_setting: components::HeatSetting },
+    // open or close a window
+    Window { new_window_state: components::WindowState },
+    // turn a life support machine on or off
+    LifeSupport { enabled: bool },
+    // turn a light on or off
+    Light { enabled: bool },
+    // turn a power generator on or off
+    Generator { enabled: bool },
+    // hook a machine up to the power grid, or cut it off
+    PowerConnection { connected: bool },
+    // change who gets power first when there isn't enough to go around
+    PowerPriority { new_priority: components::PowerPriority },
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -202,6 +293,7 @@ impl Default for NpcMoves {
 pub enum KeyboardFocus {
     GameMap,
     Dialogue,
+    Inventory,
 }
 
 impl Default for KeyboardFocus {
@@ -210,6 +302,28 @@ impl Default for KeyboardFocus {
     }
 }
 
+// Which line of the inventory screen is highlighted, while it's open
+#[derive(Debug, Copy, Clone, Default)]
+pub struct InventoryScreen {
+    pub selected_index: usize,
+}
+
+// Routes already planned for things on the move, so they aren't planned again every turn;
+// these are thrown out on reload, since the entities they're for don't survive it
+#[derive(Debug, Clone, Default)]
+pub struct PathCache {
+    pub paths: HashMap<Entity, CachedPath>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedPath {
+    pub goal: TilePos,
+    // every tile along the way, from where the route was planned through to the goal
+    pub route: Vec<TilePos>,
+    // the turn (see turn_state_helpers::current_turn) it was planned on
+    pub planned_turn: u64,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct GameIsQuit(pub bool);
 
@@ -222,18 +336,43 @@ impl Default for GameIsQuit {
 #[derive(Copy, Clone, Debug)]
 pub struct GameMapDisplayOptions {
     pub display_controls_pane: bool,
-    pub show_oxygen_overlay: bool,
+    // None means the overlay is off
+    pub overlay: Option<MapOverlay>,
 }
 
 impl Default for GameMapDisplayOptions {
     fn default() -> Self {
         GameMapDisplayOptions {
             display_controls_pane: true,
-            show_oxygen_overlay: false,
+            overlay: None,
         }
     }
 }
 
+impl GameMapDisplayOptions {
+    // Steps through the overlays: off, then each gas, then total pressure, then temperature, then off again
+    pub fn cycle_overlay(&mut self) {
+        use components::Gas;
+
+        self.overlay = match self.overlay {
+            None => Some(MapOverlay::Gas(Gas::ALL[0])),
+            Some(MapOverlay::Gas(gas)) => match Gas::ALL.iter().position(|&g| g == gas) {
+                Some(ind) if ind + 1 < Gas::ALL.len() => Some(MapOverlay::Gas(Gas::ALL[ind + 1])),
+                _ => Some(MapOverlay::Pressure),
+            },
+            Some(MapOverlay::Pressure) => Some(MapOverlay::Temperature),
+            Some(MapOverlay::Temperature) => None,
+        };
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MapOverlay {
+    Gas(components::Gas),
+    Pressure,
+    Temperature,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct GameMapRenderParams {
     pub font_width: f32,
@@ -241,6 +380,7 @@ pub struct GameMapRenderParams {
     pub tile_size_px: Vector,
     pub controls_image_offset_px: Vector,
     pub oxygen_meter_offset_px: Vector,
+    pub action_progress_offset_px: Vector,
 }
 
 impl Default for GameMapRenderParams {
@@ -251,17 +391,73 @@ impl Default for GameMapRenderParams {
             tile_size_px: Vector::new(20.0, 20.0),
             controls_image_offset_px: Vector::new(30.0, 30.0),
             oxygen_meter_offset_px: Vector::new(30.0, 60.0),
+            action_progress_offset_px: Vector::new(30.0, 90.0),
         }
     }
 }
 
+// Something the player is busy doing over several turns; they can't do anything else until it's
+// finished, cancelled, or interrupted
 #[derive(Clone, Debug, Default)]
-pub struct QueuedPlayerActions {
-    pub action_queue: VecDeque<QueuedPlayerAction>,
+pub struct OngoingPlayerAction {
+    pub current: Option<OngoingAction>,
+}
+
+#[derive(Clone, Debug)]
+pub struct OngoingAction {
+    pub kind: OngoingActionKind,
+    pub total_turns: usize,
+    pub elapsed_turns: usize,
+    // what things looked like last turn, so we can tell if something has gone wrong;
+    // None until the first turn of the action is taken
+    pub last_seen: Option<ActionSurroundings>,
+}
+
+impl OngoingAction {
+    pub fn new(kind: OngoingActionKind, total_turns: usize) -> OngoingAction {
+        OngoingAction {
+            kind,
+            total_turns,
+            elapsed_turns: 0,
+            last_seen: None,
+        }
+    }
+
+    pub fn target(&self) -> Entity {
+        match &self.kind {
+            OngoingActionKind::Hack { target } => target.entity,
+            OngoingActionKind::Repair { target } => *target,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match &self.kind {
+            OngoingActionKind::Hack { .. } => "Hacking",
+            OngoingActionKind::Repair { .. } => "Repairing",
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
-pub enum QueuedPlayerAction {
-    Wait,
+pub enum OngoingActionKind {
     Hack { target: HackTarget },
+    Repair { target: Entity },
+}
+
+// The things which, if they change in the middle of an action, mean the player should stop and look up
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionSurroundings {
+    // None if the player doesn't breathe
+    pub breath: Option<usize>,
+    pub player_position: TilePos,
+    pub adjacent_npcs: Vec<Entity>,
+    // None if the target is gone entirely
+    pub target: Option<TargetSnapshot>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetSnapshot {
+    pub position: TilePos,
+    pub hack_state: Option<components::HackState>,
+    pub broken: bool,
 }
@@ -1,11 +1,15 @@
-//! TODO: plug in skills at some point
+//! The player's skills; experience gained in a skill also flows up to its parents
 #![allow(dead_code)]
 
 use std::collections::HashMap;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Eq, PartialEq)]
+/// Each level takes this much more experience than the last; so level 1 takes this much,
+/// level 2 takes twice this much more, and so on
+pub const EXP_PER_LEVEL: usize = 50;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 struct Skill {
     /// List of parents of this skill
     parents: Vec<SkillParentRelation>,
@@ -13,7 +17,7 @@ struct Skill {
     exp_progress: usize,
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
 struct SkillParentRelation {
     /// Index of the parent in the tree
     index: usize,
@@ -22,7 +26,7 @@ struct SkillParentRelation {
     strength: usize,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
 pub struct SkillTree {
     skills: Vec<Skill>,
     lookup: HashMap<String, usize>,
@@ -113,6 +117,18 @@ impl SkillTree {
         }
     }
 
+    /// Level 0 to start with; reaching level n takes EXP_PER_LEVEL * (1 + 2 + ... + n) experience
+    pub fn check_level(&self, skill_name: &str) -> Result<usize, ()> {
+        let exp = self.check_experience(skill_name)?;
+
+        let mut level = 0;
+        while exp >= EXP_PER_LEVEL * (level + 1) * (level + 2) / 2 {
+            level += 1;
+        }
+
+        Ok(level)
+    }
+
     pub fn from_ron(ron: &str) -> Result<SkillTree, ()> {
         let additions: Vec<SkillAddition> = ron::de::from_str(ron).map_err(|_e| {})?;
 
@@ -343,4 +359,26 @@ mod tests {
         assert_eq!(actual.check_experience("r_c12"), Ok(13));
         assert_eq!(actual.check_experience("bonus_name"), Err(()));
     }
+
+    #[test]
+    fn check_level_test() {
+        let ron = r#"[(name: "root", parents: [])]"#;
+
+        let mut tree = SkillTree::from_ron(ron).expect("Should deserialize");
+
+        let mut gain = |exp| {
+            tree.gain_experience(ExpGain { exp, skill_name: "root" })
+                .expect("name is valid so this should work");
+            tree.check_level("root").expect("name is valid so this should work")
+        };
+
+        assert_eq!(gain(0), 0);
+        assert_eq!(gain(EXP_PER_LEVEL - 1), 0);
+        assert_eq!(gain(1), 1);
+        assert_eq!(gain(2 * EXP_PER_LEVEL - 1), 1);
+        assert_eq!(gain(1), 2);
+        assert_eq!(gain(10 * EXP_PER_LEVEL), 4);
+
+        assert_eq!(tree.check_level("bogus"), Err(()));
+    }
 }
@@ -0,0 +1,135 @@
+//! Multi-turn actions, and the things which can interrupt them partway through
+
+use super::*;
+
+use resources::ActionSurroundings;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Interruption {
+    LosingBreath,
+    NpcAdjacent,
+    TargetChanged,
+}
+
+pub fn interruption_text(interruption: Interruption) -> &'static str {
+    match interruption {
+        Interruption::LosingBreath => "You're struggling to breathe, and stop what you're doing.",
+        Interruption::NpcAdjacent => "Someone is right next to you, and you stop what you're doing.",
+        Interruption::TargetChanged => "Things have changed, and you stop what you're doing.",
+    }
+}
+
+/// Whether anything between last turn and this one is worth stopping for; things which were
+/// already true when the action started (like someone standing nearby) don't count
+pub fn check_interruption(before: &ActionSurroundings, now: &ActionSurroundings) -> Option<Interruption> {
+    if let (Some(before_breath), Some(now_breath)) = (before.breath, now.breath) {
+        if now_breath < before_breath {
+            return Some(Interruption::LosingBreath);
+        }
+    }
+
+    if now.adjacent_npcs.iter().any(|npc| !before.adjacent_npcs.contains(npc)) {
+        return Some(Interruption::NpcAdjacent);
+    }
+
+    if now.player_position != before.player_position || now.target != before.target {
+        return Some(Interruption::TargetChanged);
+    }
+
+    None
+}
+
+/// Something like "████......", filled in proportion to how much of the action is done
+pub fn progress_bar(elapsed_turns: usize, total_turns: usize, width: usize) -> String {
+    let filled = (width * elapsed_turns)
+        .checked_div(total_turns)
+        .map_or(width, |filled| std::cmp::min(width, filled));
+
+    let mut out = "█".repeat(filled);
+    out.push_str(&".".repeat(width - filled));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{Builder, World, WorldExt};
+
+    use resources::TargetSnapshot;
+
+    fn surroundings() -> ActionSurroundings {
+        ActionSurroundings {
+            breath: Some(100),
+            player_position: TilePos { x: 3, y: 4 },
+            adjacent_npcs: Vec::new(),
+            target: Some(TargetSnapshot {
+                position: TilePos { x: 3, y: 5 },
+                hack_state: None,
+                broken: true,
+            }),
+        }
+    }
+
+    #[test]
+    fn nothing_changed_test() {
+        assert_eq!(check_interruption(&surroundings(), &surroundings()), None);
+
+        // catching your breath is fine
+        let mut before = surroundings();
+        before.breath = Some(90);
+        assert_eq!(check_interruption(&before, &surroundings()), None);
+    }
+
+    #[test]
+    fn losing_breath_test() {
+        let mut now = surroundings();
+        now.breath = Some(99);
+        assert_eq!(check_interruption(&surroundings(), &now), Some(Interruption::LosingBreath));
+    }
+
+    #[test]
+    fn npc_adjacent_test() {
+        let mut world = World::new();
+        let npc = world.create_entity().build();
+        let other_npc = world.create_entity().build();
+
+        let mut before = surroundings();
+        before.adjacent_npcs.push(npc);
+
+        // someone who was already there when you started isn't news
+        assert_eq!(check_interruption(&before, &before.clone()), None);
+
+        // someone leaving isn't either
+        assert_eq!(check_interruption(&before, &surroundings()), None);
+
+        let mut now = before.clone();
+        now.adjacent_npcs.push(other_npc);
+        assert_eq!(check_interruption(&before, &now), Some(Interruption::NpcAdjacent));
+    }
+
+    #[test]
+    fn target_changed_test() {
+        let mut now = surroundings();
+        now.target = None;
+        assert_eq!(check_interruption(&surroundings(), &now), Some(Interruption::TargetChanged));
+
+        let mut now = surroundings();
+        now.target.as_mut().unwrap().broken = false;
+        assert_eq!(check_interruption(&surroundings(), &now), Some(Interruption::TargetChanged));
+
+        // getting shoved away from the work counts too
+        let mut now = surroundings();
+        now.player_position = TilePos { x: 2, y: 4 };
+        assert_eq!(check_interruption(&surroundings(), &now), Some(Interruption::TargetChanged));
+    }
+
+    #[test]
+    fn progress_bar_test() {
+        assert_eq!(progress_bar(0, 60, 4), "....");
+        assert_eq!(progress_bar(30, 60, 4), "██..");
+        assert_eq!(progress_bar(59, 60, 4), "███.");
+        assert_eq!(progress_bar(60, 60, 4), "████");
+        assert_eq!(progress_bar(0, 0, 4), "████");
+    }
+}
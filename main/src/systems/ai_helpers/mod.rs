@@ -0,0 +1,334 @@
+//! Deciding what an NPC does with its turn, from what it knows; working out how to actually take
+//! the step is left to whoever asked
+
+use super::*;
+
+use behaviors::Behavior;
+use components::BehaviorState;
+
+use path_helpers::PathGrid;
+
+/// What an NPC knows when it's deciding what to do
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NpcView {
+    pub pos: TilePos,
+    pub home: TilePos,
+    // how full its lungs are, as a percentage; None if it doesn't breathe
+    pub breath_perc: Option<usize>,
+    // where the player is, if the NPC has noticed them
+    pub player: Option<TilePos>,
+    // the tile with the most oxygen nearby, if there's anywhere worth going
+    pub best_air: Option<TilePos>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Decision {
+    Stay,
+    // a random step, or none at all
+    Wander,
+    // head for a tile which can be stood on
+    MoveTo(TilePos),
+    // head for something standing on a tile, stopping next to it
+    Approach(TilePos),
+    StepAwayFrom(TilePos),
+}
+
+pub fn distance(a: TilePos, b: TilePos) -> i32 {
+    (a.x - b.x).abs() + (a.y - b.y).abs()
+}
+
+/// Goes through the behaviors in order, and takes the first one which has something to do; the
+/// state only changes if that behavior changes it
+pub fn decide(behaviors: &[Behavior], view: &NpcView, state: BehaviorState, grid: &PathGrid) -> (Decision, BehaviorState) {
+    behaviors
+        .iter()
+        .find_map(|&behavior| try_behavior(behavior, view, state, grid))
+        .unwrap_or((Decision::Stay, state))
+}
+
+fn try_behavior(behavior: Behavior, view: &NpcView, state: BehaviorState, grid: &PathGrid) -> Option<(Decision, BehaviorState)> {
+    let player_within = |range: i32| view.player.filter(|&player| distance(view.pos, player) <= range);
+
+    match behavior {
+        Behavior::Wander => Some((Decision::Wander, state)),
+        Behavior::Patrol { radius } => {
+            let home = view.home;
+            let waypoints: Vec<TilePos> = [(radius, 0), (0, radius), (-radius, 0), (0, -radius)]
+                .iter()
+                .map(|&(x, y)| TilePos {
+                    x: home.x + x,
+                    y: home.y + y,
+                })
+                .filter(|&pos| grid.is_passable(pos))
+                .collect();
+
+            if waypoints.is_empty() {
+                return None;
+            }
+
+            let mut waypoint = match state {
+                BehaviorState::Patrolling { waypoint } => waypoint % waypoints.len(),
+                _ => 0,
+            };
+            if view.pos == waypoints[waypoint] {
+                waypoint = (waypoint + 1) % waypoints.len();
+            }
+
+            Some((Decision::MoveTo(waypoints[waypoint]), BehaviorState::Patrolling { waypoint }))
+        }
+        Behavior::Follow { within, keep } => player_within(within).map(|player| {
+            if distance(view.pos, player) > keep {
+                (Decision::Approach(player), state)
+            } else {
+                (Decision::Stay, state)
+            }
+        }),
+        Behavior::Flee { within } => player_within(within).map(|player| (Decision::StepAwayFrom(player), state)),
+        Behavior::SeekAir { below_perc } => {
+            let breath_perc = view.breath_perc?;
+            let best_air = view.best_air?;
+
+            if breath_perc >= below_perc {
+                None
+            } else if best_air == view.pos {
+                Some((Decision::Stay, state))
+            } else {
+                Some((Decision::MoveTo(best_air), state))
+            }
+        }
+        Behavior::Guard { radius } => {
+            let intruder = view.player.filter(|&player| distance(view.home, player) <= radius);
+
+            if let Some(player) = intruder {
+                Some((Decision::Approach(player), state))
+            } else if distance(view.pos, view.home) > radius {
+                Some((Decision::MoveTo(view.home), state))
+            } else {
+                None
+            }
+        }
+        Behavior::Investigate { within, turns } => {
+            if let Some(player) = player_within(within) {
+                return Some((
+                    Decision::Approach(player),
+                    BehaviorState::Investigating {
+                        spot: player,
+                        turns_left: turns,
+                    },
+                ));
+            }
+
+            match state {
+                BehaviorState::Investigating { spot, turns_left } if turns_left > 0 => {
+                    let next_state = BehaviorState::Investigating {
+                        spot,
+                        turns_left: turns_left - 1,
+                    };
+                    // once it's there, it has a look around until it gives up
+                    if distance(view.pos, spot) <= 1 {
+                        Some((Decision::Stay, next_state))
+                    } else {
+                        Some((Decision::Approach(spot), next_state))
+                    }
+                }
+                BehaviorState::Investigating { .. } => Some((Decision::Stay, BehaviorState::Idle)),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(x: i32, y: i32) -> TilePos {
+        TilePos { x, y }
+    }
+
+    fn open_grid(size: i32) -> PathGrid {
+        let mut grid = PathGrid::default();
+        for x in 0..size {
+            for y in 0..size {
+                grid.set_cost(pos(x, y), constants::pathing::STEP_COST);
+            }
+        }
+        grid
+    }
+
+    fn view_at(at: TilePos) -> NpcView {
+        NpcView {
+            pos: at,
+            home: at,
+            breath_perc: Some(100),
+            player: None,
+            best_air: None,
+        }
+    }
+
+    #[test]
+    fn nothing_to_do_test() {
+        let grid = open_grid(5);
+
+        assert_eq!(
+            (Decision::Stay, BehaviorState::Idle),
+            decide(&[], &view_at(pos(2, 2)), BehaviorState::Idle, &grid)
+        );
+    }
+
+    #[test]
+    fn priority_order_test() {
+        let grid = open_grid(10);
+        let view = NpcView {
+            player: Some(pos(3, 2)),
+            ..view_at(pos(2, 2))
+        };
+
+        let behaviors = [Behavior::Flee { within: 2 }, Behavior::Wander];
+        assert_eq!(
+            Decision::StepAwayFrom(pos(3, 2)),
+            decide(&behaviors, &view, BehaviorState::Idle, &grid).0
+        );
+
+        // out of range, so on to the next one
+        let far = NpcView {
+            player: Some(pos(8, 8)),
+            ..view
+        };
+        assert_eq!(Decision::Wander, decide(&behaviors, &far, BehaviorState::Idle, &grid).0);
+    }
+
+    #[test]
+    fn patrol_test() {
+        let grid = open_grid(10);
+        let behaviors = [Behavior::Patrol { radius: 2 }];
+        let home = pos(5, 5);
+
+        let view = view_at(home);
+        let (decision, state) = decide(&behaviors, &view, BehaviorState::Idle, &grid);
+        assert_eq!(Decision::MoveTo(pos(7, 5)), decision);
+        assert_eq!(BehaviorState::Patrolling { waypoint: 0 }, state);
+
+        // on reaching a waypoint, it heads for the next one
+        let there = NpcView { pos: pos(7, 5), ..view };
+        let (decision, state) = decide(&behaviors, &there, state, &grid);
+        assert_eq!(Decision::MoveTo(pos(5, 7)), decision);
+        assert_eq!(BehaviorState::Patrolling { waypoint: 1 }, state);
+    }
+
+    #[test]
+    fn patrol_skips_walls_test() {
+        let mut grid = open_grid(10);
+        grid.block(pos(7, 5));
+
+        let (decision, _) = decide(&[Behavior::Patrol { radius: 2 }], &view_at(pos(5, 5)), BehaviorState::Idle, &grid);
+
+        assert_eq!(Decision::MoveTo(pos(5, 7)), decision);
+    }
+
+    #[test]
+    fn follow_test() {
+        let grid = open_grid(10);
+        let behaviors = [Behavior::Follow { within: 5, keep: 1 }];
+
+        let near = NpcView {
+            player: Some(pos(4, 2)),
+            ..view_at(pos(2, 2))
+        };
+        assert_eq!(
+            Decision::Approach(pos(4, 2)),
+            decide(&behaviors, &near, BehaviorState::Idle, &grid).0
+        );
+
+        let close_enough = NpcView {
+            player: Some(pos(3, 2)),
+            ..near
+        };
+        assert_eq!(Decision::Stay, decide(&behaviors, &close_enough, BehaviorState::Idle, &grid).0);
+    }
+
+    #[test]
+    fn seek_air_test() {
+        let grid = open_grid(10);
+        let behaviors = [Behavior::SeekAir { below_perc: 50 }, Behavior::Wander];
+
+        let gasping = NpcView {
+            breath_perc: Some(30),
+            best_air: Some(pos(6, 2)),
+            ..view_at(pos(2, 2))
+        };
+        assert_eq!(
+            Decision::MoveTo(pos(6, 2)),
+            decide(&behaviors, &gasping, BehaviorState::Idle, &grid).0
+        );
+
+        let fine = NpcView {
+            breath_perc: Some(80),
+            ..gasping
+        };
+        assert_eq!(Decision::Wander, decide(&behaviors, &fine, BehaviorState::Idle, &grid).0);
+
+        let already_there = NpcView {
+            best_air: Some(pos(2, 2)),
+            ..gasping
+        };
+        assert_eq!(Decision::Stay, decide(&behaviors, &already_there, BehaviorState::Idle, &grid).0);
+    }
+
+    #[test]
+    fn guard_test() {
+        let grid = open_grid(20);
+        let behaviors = [Behavior::Guard { radius: 3 }, Behavior::Wander];
+        let home = pos(5, 5);
+
+        let strayed = NpcView {
+            pos: pos(10, 5),
+            ..view_at(home)
+        };
+        assert_eq!(Decision::MoveTo(home), decide(&behaviors, &strayed, BehaviorState::Idle, &grid).0);
+
+        let intruder = NpcView {
+            player: Some(pos(6, 6)),
+            ..strayed
+        };
+        assert_eq!(
+            Decision::Approach(pos(6, 6)),
+            decide(&behaviors, &intruder, BehaviorState::Idle, &grid).0
+        );
+
+        assert_eq!(Decision::Wander, decide(&behaviors, &view_at(home), BehaviorState::Idle, &grid).0);
+    }
+
+    #[test]
+    fn investigate_test() {
+        let grid = open_grid(20);
+        let behaviors = [Behavior::Investigate { within: 6, turns: 2 }, Behavior::Wander];
+
+        let spotted = NpcView {
+            player: Some(pos(8, 2)),
+            ..view_at(pos(2, 2))
+        };
+        let (decision, state) = decide(&behaviors, &spotted, BehaviorState::Idle, &grid);
+        assert_eq!(Decision::Approach(pos(8, 2)), decision);
+
+        // the player slips out of sight, but it still goes to look
+        let gone = NpcView { player: None, ..spotted };
+        let (decision, state) = decide(&behaviors, &gone, state, &grid);
+        assert_eq!(Decision::Approach(pos(8, 2)), decision);
+
+        let (_, state) = decide(&behaviors, &gone, state, &grid);
+        assert_eq!(
+            BehaviorState::Investigating {
+                spot: pos(8, 2),
+                turns_left: 0
+            },
+            state
+        );
+
+        // and eventually gives up
+        let (decision, state) = decide(&behaviors, &gone, state, &grid);
+        assert_eq!(Decision::Stay, decision);
+        assert_eq!(BehaviorState::Idle, state);
+        assert_eq!(Decision::Wander, decide(&behaviors, &gone, state, &grid).0);
+    }
+}
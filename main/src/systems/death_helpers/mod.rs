@@ -0,0 +1,80 @@
+//! Shared handling for when something dies, whatever killed it
+
+use super::*;
+
+use components::*;
+use resources::{Callback, Callbacks};
+
+pub use components::DeathCause;
+
+fn death_text(cause: DeathCause) -> &'static str {
+    match cause {
+        DeathCause::Air => "You have been without air for too long.",
+        DeathCause::Cold => "The cold has crept in too deep to shake.",
+        DeathCause::Heat => "The heat has become too much to bear.",
+        DeathCause::Fire => "The flames catch you, and do not let go.",
+    }
+}
+
+/// How a body looks, given what killed it
+pub fn corpse_text(cause: DeathCause) -> &'static str {
+    match cause {
+        DeathCause::Air => "Its face is frozen in a desperate gasp.",
+        DeathCause::Cold => "It is rimed with frost, and stiff as a board.",
+        DeathCause::Heat => "Its skin is blistered and cracked from the heat.",
+        DeathCause::Fire => "It is badly burned, and still smoking.",
+    }
+}
+
+pub fn process_death(entity: Entity, cs: &CanSuffocate, cause: DeathCause, callbacks: &mut Callbacks, lazy_update: &LazyUpdate) {
+    match cs {
+        CanSuffocate::Player => {
+            let builder = dialogue_helpers::DialogueBuilder::new(&format!(
+                "{}\n\nThis life is over, but the tether pulls you back.",
+                death_text(cause)
+            ))
+            .with_option("[Continue]", vec![Callback::EndDialogue, Callback::LoadGame]);
+
+            dialogue_helpers::launch_dialogue(builder, callbacks);
+        }
+        CanSuffocate::Death => {
+            lazy_update.exec_mut(move |world| leave_corpse(world, entity, cause));
+        }
+    }
+}
+
+// The body stays where it fell, looking like it did, and holding onto whatever it had; it just
+// doesn't do anything anymore
+fn leave_corpse(world: &mut World, entity: Entity, cause: DeathCause) {
+    if !world.entities().is_alive(entity) {
+        return;
+    }
+
+    let name = world
+        .read_storage::<Talkable>()
+        .get(entity)
+        .map(|talkable| talkable.name.clone())
+        .unwrap_or_else(|| "Creature".to_string());
+
+    world.write_storage::<NPC>().remove(entity);
+    world.write_storage::<Breathes>().remove(entity);
+    world.write_storage::<CanSuffocate>().remove(entity);
+    world.write_storage::<OpensDoors>().remove(entity);
+    world.write_storage::<Talkable>().remove(entity);
+
+    if !constants::corpses::BLOCKS_MOVEMENT {
+        world.write_storage::<BlocksMovement>().remove(entity);
+    }
+
+    if let Some(char_render) = world.write_storage::<CharRender>().get_mut(entity) {
+        char_render.glyph = constants::corpses::CORPSE_GLYPH;
+    }
+    if let Some(visible) = world.write_storage::<Visible>().get_mut(entity) {
+        visible.memorable = true;
+    }
+
+    world
+        .write_storage::<Corpse>()
+        .insert(entity, Corpse { name, cause, clue: None })
+        .expect("Entity should be live, since we just checked");
+}
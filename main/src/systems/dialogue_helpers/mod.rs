@@ -1,5 +1,6 @@
 use super::*;
 
+use codex::Codex;
 use resources::*;
 
 pub struct DialogueBuilder {
@@ -32,6 +33,15 @@ impl DialogueBuilder {
         self
     }
 
+    /// Adds the option only if the player has learned the codex entry with the given key
+    pub fn with_option_if_known(self, codex: &Codex, key: &str, text: &str, callbacks: Vec<Callback>) -> Self {
+        if codex.knows(key) {
+            self.with_option(text, callbacks)
+        } else {
+            self
+        }
+    }
+
     pub fn build(self) -> DialogueState {
         if self.selected_index >= self.options.len() {
             panic!(
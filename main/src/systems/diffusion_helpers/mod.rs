@@ -0,0 +1,278 @@
+//! Gas diffusion over a dense grid of squares.
+//!
+//! Each step, every pair of neighboring squares trades a fixed fraction of the difference between
+//! them. The trade is computed once per pair, from the amounts at the start of the step, and what
+//! one side gives the other side gets; so the total amount is conserved exactly, except for what
+//! flows into vacuum, and the result doesn't depend on what order anything is looked at in.
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Cell {
+    // nothing goes in or out (walls, closed doors, etc.)
+    Blocked,
+    // anything which flows in here is gone for good
+    Vacuum,
+    Air(usize),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    // row-by-row, so cells[x,y] is cells[y * width + x]
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    /// Everything outside the grid counts as vacuum
+    pub fn new(width: usize, height: usize) -> Grid {
+        Grid {
+            width,
+            height,
+            cells: vec![Cell::Vacuum; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        self.cells[y * self.width + x] = cell;
+    }
+
+    #[cfg(test)]
+    fn total(&self) -> usize {
+        self.cells
+            .iter()
+            .map(|cell| match cell {
+                Cell::Air(amount) => *amount,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Runs one step of diffusion, where each pair of neighbors trades 1/divisor of the difference
+    /// between them (rounded toward zero). Returns how much was lost into vacuum.
+    ///
+    /// Since a square has four neighbors, the divisor must be at least 4, or squares could
+    /// give away more than they have.
+    pub fn diffuse(&mut self, divisor: usize) -> usize {
+        assert!(divisor >= 4, "Diffusion divisor must be at least 4, got {}", divisor);
+
+        let divisor = divisor as isize;
+        let mut changes = vec![0isize; self.cells.len()];
+        let mut lost = 0;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let ind = y * self.width + x;
+                let here = self.cells[ind];
+
+                // Each pair is handled exactly once: from the left square, or the top square
+                let pairs = [(x + 1 < self.width, ind + 1), (y + 1 < self.height, ind + self.width)];
+
+                for &(in_grid, other) in pairs.iter() {
+                    if !in_grid {
+                        continue;
+                    }
+
+                    match (here, self.cells[other]) {
+                        (Cell::Air(a), Cell::Air(b)) => {
+                            let flow = (a as isize - b as isize) / divisor;
+                            changes[ind] -= flow;
+                            changes[other] += flow;
+                        }
+                        (Cell::Air(a), Cell::Vacuum) => {
+                            let flow = a as isize / divisor;
+                            changes[ind] -= flow;
+                            lost += flow;
+                        }
+                        (Cell::Vacuum, Cell::Air(b)) => {
+                            let flow = b as isize / divisor;
+                            changes[other] -= flow;
+                            lost += flow;
+                        }
+                        _ => {}
+                    }
+                }
+
+                // The edges of the grid leak into the vacuum beyond
+                if let Cell::Air(a) = here {
+                    let edges = [x == 0, y == 0, x + 1 == self.width, y + 1 == self.height];
+                    for _ in edges.iter().filter(|&&is_edge| is_edge) {
+                        let flow = a as isize / divisor;
+                        changes[ind] -= flow;
+                        lost += flow;
+                    }
+                }
+            }
+        }
+
+        for (cell, change) in self.cells.iter_mut().zip(changes) {
+            if let Cell::Air(amount) = cell {
+                *amount = (*amount as isize + change) as usize;
+            }
+        }
+
+        lost as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng};
+
+    use crate::rng::PalladRng;
+
+    const DIVISOR: usize = 8;
+
+    fn random_grid(rng: &mut PalladRng, with_vacuum: bool) -> Grid {
+        let width = rng.gen_range(1, 20);
+        let height = rng.gen_range(1, 20);
+        let mut grid = Grid::new(width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let roll = rng.gen_range(0, 100);
+                let cell = if roll < 20 {
+                    Cell::Blocked
+                } else if with_vacuum && roll < 25 {
+                    Cell::Vacuum
+                } else {
+                    Cell::Air(rng.gen_range(0, 500))
+                };
+                grid.set(x, y, cell);
+            }
+        }
+
+        grid
+    }
+
+    // Surrounds the grid with a wall, so nothing can leak out of the edges
+    fn walled(grid: &Grid) -> Grid {
+        let mut out = Grid::new(grid.width + 2, grid.height + 2);
+        for y in 0..out.height {
+            for x in 0..out.width {
+                out.set(x, y, Cell::Blocked);
+            }
+        }
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                out.set(x + 1, y + 1, grid.get(x, y));
+            }
+        }
+        out
+    }
+
+    fn mirrored(grid: &Grid) -> Grid {
+        let mut out = Grid::new(grid.width, grid.height);
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                out.set(grid.width - 1 - x, y, grid.get(x, y));
+            }
+        }
+        out
+    }
+
+    fn transposed(grid: &Grid) -> Grid {
+        let mut out = Grid::new(grid.height, grid.width);
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                out.set(y, x, grid.get(x, y));
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn sealed_grid_conserves_gas_test() {
+        let mut rng = PalladRng::seed_from_u64(1);
+
+        for _ in 0..200 {
+            let mut grid = walled(&random_grid(&mut rng, false));
+            let before = grid.total();
+
+            for _ in 0..10 {
+                assert_eq!(grid.diffuse(DIVISOR), 0);
+                assert_eq!(grid.total(), before);
+            }
+        }
+    }
+
+    #[test]
+    fn only_vacuum_loses_gas_test() {
+        let mut rng = PalladRng::seed_from_u64(2);
+
+        for _ in 0..200 {
+            let mut grid = random_grid(&mut rng, true);
+            let mut expected = grid.total();
+
+            for _ in 0..10 {
+                let lost = grid.diffuse(DIVISOR);
+                expected -= lost;
+                assert_eq!(grid.total(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn diffusion_is_symmetric_test() {
+        let mut rng = PalladRng::seed_from_u64(3);
+
+        for _ in 0..200 {
+            let grid = random_grid(&mut rng, true);
+
+            let mut forward = grid.clone();
+            let mut flipped = mirrored(&grid);
+            let mut swapped = transposed(&grid);
+
+            for _ in 0..10 {
+                let lost = forward.diffuse(DIVISOR);
+                assert_eq!(flipped.diffuse(DIVISOR), lost);
+                assert_eq!(swapped.diffuse(DIVISOR), lost);
+            }
+
+            assert_eq!(mirrored(&forward), flipped);
+            assert_eq!(transposed(&forward), swapped);
+        }
+    }
+
+    #[test]
+    fn diffusion_stays_in_bounds_test() {
+        let mut rng = PalladRng::seed_from_u64(4);
+
+        for _ in 0..200 {
+            let mut grid = random_grid(&mut rng, true);
+            let max_before = grid
+                .cells
+                .iter()
+                .filter_map(|c| if let Cell::Air(a) = c { Some(*a) } else { None })
+                .max();
+
+            for _ in 0..10 {
+                grid.diffuse(DIVISOR);
+            }
+
+            for cell in grid.cells.iter() {
+                if let Cell::Air(amount) = cell {
+                    assert!(Some(*amount) <= max_before);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn even_air_stays_even_test() {
+        let mut grid = walled(&Grid {
+            width: 3,
+            height: 3,
+            cells: vec![Cell::Air(100); 9],
+        });
+
+        grid.diffuse(DIVISOR);
+
+        assert!(grid.cells.iter().all(|&cell| cell == Cell::Blocked || cell == Cell::Air(100)));
+    }
+}
@@ -0,0 +1,90 @@
+//! Who doors will open for; shared by the doors themselves, and by anything planning a route
+//! through them
+
+use super::*;
+
+use components::*;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DoorOpener {
+    pub is_player: bool,
+    pub is_alien: bool,
+    pub has_keycard: bool,
+}
+
+pub fn carries_keycard(inventory: Option<&Inventory>, keycard: &ReadStorage<Keycard>) -> bool {
+    inventory
+        .map(|inventory| inventory.items.iter().any(|&item| keycard.contains(item)))
+        .unwrap_or(false)
+}
+
+pub fn allows(access: DoorAccess, opener: DoorOpener) -> bool {
+    match access {
+        DoorAccess::Everyone => true,
+        DoorAccess::PlayerOnly => opener.is_player,
+        DoorAccess::NoAliens => !opener.is_alien,
+        DoorAccess::Keycard => opener.has_keycard,
+    }
+}
+
+/// Whether the door would open if the opener walked up to it, as things stand
+pub fn opens_for(door: &Door, is_powered: bool, is_broken: bool, opener: DoorOpener) -> bool {
+    if !is_powered || is_broken {
+        return door.door_state == DoorState::Open;
+    }
+
+    match door.door_behavior {
+        DoorBehavior::StayOpen => true,
+        DoorBehavior::StayClosed => false,
+        DoorBehavior::FullAuto | DoorBehavior::AutoOpen => allows(door.access, opener),
+        // it won't open on its own, but it won't close on you either
+        DoorBehavior::AutoClose => door.door_state == DoorState::Open,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALIEN: DoorOpener = DoorOpener {
+        is_player: false,
+        is_alien: true,
+        has_keycard: false,
+    };
+
+    const PLAYER_WITH_KEYCARD: DoorOpener = DoorOpener {
+        is_player: true,
+        is_alien: false,
+        has_keycard: true,
+    };
+
+    fn door(door_state: DoorState, door_behavior: DoorBehavior, access: DoorAccess) -> Door {
+        Door {
+            door_state,
+            door_behavior,
+            access,
+        }
+    }
+
+    #[test]
+    fn access_test() {
+        let no_aliens = door(DoorState::Closed, DoorBehavior::FullAuto, DoorAccess::NoAliens);
+        assert!(!opens_for(&no_aliens, true, false, ALIEN));
+        assert!(opens_for(&no_aliens, true, false, PLAYER_WITH_KEYCARD));
+
+        let keycard = door(DoorState::Closed, DoorBehavior::AutoOpen, DoorAccess::Keycard);
+        assert!(!opens_for(&keycard, true, false, ALIEN));
+        assert!(opens_for(&keycard, true, false, PLAYER_WITH_KEYCARD));
+    }
+
+    #[test]
+    fn stuck_doors_stay_as_they_are_test() {
+        let closed = door(DoorState::Closed, DoorBehavior::FullAuto, DoorAccess::Everyone);
+        assert!(!opens_for(&closed, false, false, PLAYER_WITH_KEYCARD));
+        assert!(!opens_for(&closed, true, true, PLAYER_WITH_KEYCARD));
+
+        let open = door(DoorState::Open, DoorBehavior::StayClosed, DoorAccess::Everyone);
+        assert!(opens_for(&open, false, false, ALIEN));
+        assert!(!opens_for(&open, true, false, ALIEN));
+    }
+}
@@ -0,0 +1,140 @@
+//! What the things someone is wearing do for them
+
+use super::*;
+
+use components::{Breathes, EquipModifiers, Equipment, Equippable, GasMixture};
+
+/// Everything someone is wearing, added up
+pub fn worn_modifiers(equipment: Option<&Equipment>, equippable: &ReadStorage<Equippable>) -> EquipModifiers {
+    let worn = equipment
+        .into_iter()
+        .flat_map(|equipment| equipment.worn())
+        .filter_map(|item| equippable.get(item))
+        .map(|equippable| equippable.modifiers);
+
+    combine(worn)
+}
+
+pub fn combine(all: impl Iterator<Item = EquipModifiers>) -> EquipModifiers {
+    all.fold(EquipModifiers::default(), |total, next| EquipModifiers {
+        // the best air supply wins; they don't stack
+        breathed_oxygen: match (total.breathed_oxygen, next.breathed_oxygen) {
+            (Some(a), Some(b)) => Some(std::cmp::max(a, b)),
+            (a, b) => a.or(b),
+        },
+        light_radius: total.light_radius + next.light_radius,
+        hack_speedup_perc: total.hack_speedup_perc + next.hack_speedup_perc,
+        sealed: total.sealed || next.sealed,
+    })
+}
+
+/// The air someone actually breathes; a sealed helmet keeps the outside air out entirely
+pub fn breathed_air(outside: &GasMixture, modifiers: &EquipModifiers) -> GasMixture {
+    match modifiers.breathed_oxygen {
+        Some(oxygen) => GasMixture {
+            oxygen,
+            ..GasMixture::default()
+        },
+        None => *outside,
+    }
+}
+
+/// Whether a sealed suit should switch over to its tank; only once the air is too thin to catch your breath
+pub fn needs_tank(air: &GasMixture, breathe: &Breathes, modifiers: &EquipModifiers) -> bool {
+    modifiers.sealed && air.oxygen < breathe.slow_gain_threshold
+}
+
+/// What someone breathing from a tank gets
+pub fn tank_air() -> GasMixture {
+    GasMixture {
+        oxygen: constants::equipment::TANK_BREATHED_OXYGEN,
+        ..GasMixture::default()
+    }
+}
+
+/// How long something takes once the wearer's speedup is taken off
+pub fn hack_turns(turns: usize, modifiers: &EquipModifiers) -> usize {
+    let speedup = std::cmp::min(modifiers.hack_speedup_perc, constants::equipment::MAX_HACK_SPEEDUP_PERC);
+    std::cmp::max(1, turns * (100 - speedup) / 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helmet(oxygen: usize) -> EquipModifiers {
+        EquipModifiers {
+            breathed_oxygen: Some(oxygen),
+            ..EquipModifiers::default()
+        }
+    }
+
+    fn rig(perc: usize) -> EquipModifiers {
+        EquipModifiers {
+            hack_speedup_perc: perc,
+            ..EquipModifiers::default()
+        }
+    }
+
+    #[test]
+    fn combine_test() {
+        assert_eq!(combine(vec![].into_iter()), EquipModifiers::default());
+
+        let light = EquipModifiers {
+            light_radius: 3,
+            ..EquipModifiers::default()
+        };
+        let total = combine(vec![light, light, rig(10), helmet(30), helmet(20)].into_iter());
+
+        assert_eq!(total.light_radius, 6);
+        assert_eq!(total.hack_speedup_perc, 10);
+        assert_eq!(total.breathed_oxygen, Some(30));
+    }
+
+    #[test]
+    fn breathed_air_test() {
+        let smoky = GasMixture {
+            oxygen: 5,
+            carbon_dioxide: 100,
+            nitrogen: 50,
+            toxin: 100,
+        };
+
+        assert_eq!(breathed_air(&smoky, &EquipModifiers::default()), smoky);
+
+        let inside_helmet = breathed_air(&smoky, &helmet(40));
+        assert_eq!(inside_helmet.oxygen, 40);
+        assert_eq!(inside_helmet.toxin, 0);
+        assert_eq!(inside_helmet.carbon_dioxide, 0);
+    }
+
+    #[test]
+    fn needs_tank_test() {
+        let breathe = Breathes::default();
+        let suit = EquipModifiers {
+            sealed: true,
+            ..EquipModifiers::default()
+        };
+        let air = |oxygen| GasMixture {
+            oxygen,
+            ..GasMixture::default()
+        };
+
+        // plenty of air, no need to dip into the tank
+        assert!(!needs_tank(&air(breathe.slow_gain_threshold), &breathe, &suit));
+        assert!(needs_tank(&air(breathe.slow_gain_threshold - 1), &breathe, &suit));
+        assert!(needs_tank(&air(0), &breathe, &suit));
+
+        // no suit, nothing to hook a tank up to
+        assert!(!needs_tank(&air(0), &breathe, &EquipModifiers::default()));
+    }
+
+    #[test]
+    fn hack_turns_test() {
+        assert_eq!(hack_turns(40, &EquipModifiers::default()), 40);
+        assert_eq!(hack_turns(40, &rig(25)), 30);
+        // there's a limit to how much gear can help
+        assert_eq!(hack_turns(40, &rig(500)), 10);
+        assert_eq!(hack_turns(1, &rig(50)), 1);
+    }
+}
@@ -0,0 +1,288 @@
+//! Shadowcasting field of view, for anything that needs to know what can be seen from where: the
+//! player, cameras, NPCs, and lights (which "see" whatever they light up)
+//!
+//! Shamelessly taken from http://journal.stuffwithstuff.com/2015/09/07/what-the-hero-sees/
+//! which is a completely wonderful article
+
+use super::*;
+
+use std::cmp::max;
+use std::collections::HashSet;
+
+use numerics::Float;
+
+/// The positions of everything which can't be seen through
+pub type Occlusions = HashSet<TilePos>;
+
+/// Calls `visit` on every position in view of the observer (out to, but not including, vis_range),
+/// including the occluding squares at the edge of view. The observer's own position is not visited.
+/// Positions on the boundary between octants may be visited more than once.
+pub fn field_of_view<O, F>(observer_pos: TilePos, vis_range: i32, occludes: O, mut visit: F)
+where
+    O: Fn(TilePos) -> bool,
+    F: FnMut(TilePos),
+{
+    for octant in 0..8 {
+        octant_view(observer_pos, octant, vis_range, &occludes, &mut visit);
+    }
+}
+
+/// Every position in view of the observer (out to, but not including, vis_range), including the
+/// observer's own position and the occluding squares at the edge of view
+pub fn compute_fov<O>(observer_pos: TilePos, vis_range: i32, occludes: O) -> HashSet<TilePos>
+where
+    O: Fn(TilePos) -> bool,
+{
+    let mut seen = HashSet::new();
+    seen.insert(observer_pos);
+
+    field_of_view(observer_pos, vis_range, occludes, |pos| {
+        seen.insert(pos);
+    });
+
+    seen
+}
+
+/// Whether the observer can see the target, if it's within vis_range (exclusive, and counted the
+/// same way as for compute_fov, so the further of the x and y distances). Only looks as far as the
+/// target, so it's much cheaper than working out the whole field of view.
+pub fn has_line_of_sight<O>(observer_pos: TilePos, target: TilePos, vis_range: i32, occludes: O) -> bool
+where
+    O: Fn(TilePos) -> bool,
+{
+    let reach = max((target.x - observer_pos.x).abs(), (target.y - observer_pos.y).abs());
+
+    if reach == 0 {
+        return true;
+    } else if reach >= vis_range {
+        return false;
+    }
+
+    let mut seen = false;
+    field_of_view(observer_pos, reach + 1, occludes, |pos| {
+        seen |= pos == target;
+    });
+
+    seen
+}
+
+// Nothing outside the octant is touched, so callers keeping track of what can't be seen have to
+// mark everything unseen before they start; that's what lets this stop early once it's all in shadow
+fn octant_view<O, F>(observer_pos: TilePos, octant: usize, vis_range: i32, occludes: &O, visit: &mut F)
+where
+    O: Fn(TilePos) -> bool,
+    F: FnMut(TilePos),
+{
+    let mut line = ShadowLine::default();
+
+    for row in 1..vis_range {
+        let mut all_occluded = true;
+
+        for col in 0..=row {
+            let pos = observer_pos + transform_octant(row, col, octant);
+
+            let projection = project_tile(row, col);
+
+            if line.is_in_shadow(projection) {
+                continue;
+            }
+
+            all_occluded = false;
+            visit(pos);
+
+            if occludes(pos) {
+                line.add_shadow(projection);
+            }
+        }
+
+        if all_occluded {
+            break;
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Shadow {
+    start: Float,
+    end: Float,
+}
+
+impl Shadow {
+    fn contains(self, other: Shadow) -> bool {
+        self.start <= other.start && self.end >= other.end
+    }
+}
+
+// only refers to a single octant (!)
+#[derive(Default, Debug)]
+struct ShadowLine {
+    shadows: Vec<Shadow>,
+}
+
+impl ShadowLine {
+    fn is_in_shadow(&self, projection: Shadow) -> bool {
+        self.shadows.iter().any(|shadow| shadow.contains(projection))
+    }
+
+    // precondition: shadow is a nontrivial addition
+    fn add_shadow(&mut self, to_insert: Shadow) {
+        let shadow_len = self.shadows.len();
+
+        let mut index = 0;
+
+        // linear search for the insertion point; list tends to be very short
+        // so that index is the first where to_insert.start <= shadows[index].start
+        while index < shadow_len && self.shadows[index].start < to_insert.start {
+            index += 1;
+        }
+
+        // NB: because of the iteration order (doing individual squares, and doing closer
+        // rows before later ones), we actually never need to unify more than two shadows
+        // because this square will always fit in the union of two adjacent shadows
+        let overlaps_previous = index > 0 && self.shadows[index - 1].end >= to_insert.start;
+        let overlaps_next = index < shadow_len && self.shadows[index].start <= to_insert.end;
+
+        if overlaps_next {
+            if overlaps_previous {
+                // Then we need to unify prev and next into one shadow
+                self.shadows[index - 1].end = self.shadows[index].end;
+                self.shadows.remove(index);
+            } else {
+                self.shadows[index].start = to_insert.start;
+            }
+        } else {
+            if overlaps_previous {
+                self.shadows[index - 1].end = to_insert.end;
+            } else {
+                self.shadows.insert(index, to_insert);
+            }
+        }
+    }
+}
+
+/// Computes a shadow of a given occlusive block at (row, col)
+/// (which is a relative position to the observer). Assumes the
+/// thing has already been transformed by octant, so is "in the
+/// zeroth octant"
+///
+/// Params:
+/// * rel_pos: position relative to the observer's position
+///
+/// Precondition: row >= col (N/NE octant), row >= 1
+fn project_tile(row: i32, col: i32) -> Shadow {
+    // NB: here, row means "up" (so 3 means 3 tiles north)
+    // whereas typically that direction would be negative
+    // don't worry about that
+    let (row, col) = (row as f32, col as f32);
+
+    // slope to the top-left corner of the blocking square
+    let top_left = col / (row + 2.0);
+    // slope to the bottom-right corner of the blocking square
+    let bottom_right = (col + 1.0) / (row + 1.0);
+
+    Shadow {
+        start: top_left.into(),
+        end: bottom_right.into(),
+    }
+}
+
+/// Transforms a (row, col) pair from the given octant into an actual position.
+/// Octants are labeled clockwise, sequentially
+///
+/// Note that (row, col) coordinates are such that the "row" axis extends positively
+/// from the observer, and the "col" axis is seen as horizontal and varies from 0
+/// to row (inclusive)
+fn transform_octant(row: i32, col: i32, octant: usize) -> TilePos {
+    let (x, y) = match octant {
+        // 0 is the N/NE octant, and is a y-reflection of 3 (S/SE)
+        0 => (col, -row),
+        // 1 is the E/NE octant, and is an x/y reflection of 0 (N/NE)
+        1 => (row, -col),
+        // 2 is the E/SE octant, and is an x/y reflection of 3 (S/SE)
+        2 => (row, col),
+        // 3 is the S/SE octant, where the major axis is y (positive is South, as desired) and minor
+        // is x, so line up with row,col perfectly
+        3 => (col, row),
+        // 4 is the S/SW octant, and is an x-reflection of 3 (S/SE)
+        4 => (-col, row),
+        // 5 is the W/SW octant, and is an x/y-reflection of 4 (S/SW)
+        5 => (-row, col),
+        // 6 is the W/NW octant, and is an x/y-reflection of 7 (N/NW)
+        6 => (-row, -col),
+        // 7 is the N/NW octant, and is an x-reflection of 0 (N/NE)
+        7 => (-col, -row),
+        // There are only octants 0..=7. The rusty thing would be to use enums,
+        // but they're annoying for enumeration through, and the octant is only
+        // used in this module, and only privately, so it should be alright
+        _ => panic!("Unsupported octant: {}", octant),
+    };
+
+    TilePos { x, y }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(x: i32, y: i32) -> TilePos {
+        TilePos { x, y }
+    }
+
+    #[test]
+    fn open_space_test() {
+        let seen = compute_fov(pos(0, 0), 3, |_| false);
+
+        // everything out to (but not including) the range, counting the further of x and y
+        assert_eq!(25, seen.len());
+        assert!(seen.contains(&pos(0, 0)));
+        assert!(seen.contains(&pos(2, -2)));
+        assert!(!seen.contains(&pos(3, 0)));
+    }
+
+    #[test]
+    fn wall_test() {
+        let walls: Occlusions = (-3..=3).map(|y| pos(2, y)).collect();
+
+        let seen = compute_fov(pos(0, 0), 6, |p| walls.contains(&p));
+
+        // the wall itself can be seen, but nothing behind it
+        assert!(seen.contains(&pos(2, 0)));
+        assert!(!seen.contains(&pos(3, 0)));
+        assert!(!seen.contains(&pos(4, 1)));
+        assert!(seen.contains(&pos(-4, 1)));
+    }
+
+    #[test]
+    fn line_of_sight_test() {
+        let walls: Occlusions = vec![pos(2, 0)].into_iter().collect();
+        let occludes = |p| walls.contains(&p);
+
+        assert!(has_line_of_sight(pos(0, 0), pos(0, 0), 1, occludes));
+        assert!(has_line_of_sight(pos(0, 0), pos(2, 0), 5, occludes));
+        assert!(!has_line_of_sight(pos(0, 0), pos(4, 0), 5, occludes));
+        assert!(has_line_of_sight(pos(0, 0), pos(0, 4), 5, occludes));
+
+        // too far off to see, wall or no wall
+        assert!(!has_line_of_sight(pos(0, 0), pos(0, 4), 4, occludes));
+    }
+
+    #[test]
+    fn agrees_with_fov_test() {
+        let walls: Occlusions = vec![pos(1, 1), pos(-2, 0), pos(0, -3), pos(3, 2)].into_iter().collect();
+        let occludes = |p| walls.contains(&p);
+
+        let seen = compute_fov(pos(0, 0), 6, occludes);
+
+        for x in -6..=6 {
+            for y in -6..=6 {
+                let target = pos(x, y);
+                assert_eq!(
+                    seen.contains(&target),
+                    has_line_of_sight(pos(0, 0), target, 6, occludes),
+                    "{:?}",
+                    target
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! How long hacks take and how likely they are to work, given the hacker's skill and the target's security
+
+use super::*;
+
+use skills::SkillTree;
+
+pub const HACKING_SKILL: &str = "Hacking";
+
+/// The player's hacking level; zero if the skill tree doesn't have hacking in it at all
+pub fn hacking_level(skills: &SkillTree) -> usize {
+    skills.check_level(HACKING_SKILL).unwrap_or(0)
+}
+
+/// How many turns a hack takes, given how long it would take a novice on the least secure target
+pub fn hack_duration(base_turns: usize, skill_level: usize, security: usize) -> usize {
+    std::cmp::max(1, base_turns * (security + 1) / (skill_level + 2))
+}
+
+/// Chance (out of 100) that a hack works; bonus is for hacks which are easier than usual
+pub fn success_chance_perc(skill_level: usize, security: usize, bonus: usize) -> usize {
+    use constants::hacking::*;
+
+    let chance = (BASE_SUCCESS_PERC + SKILL_BONUS_PERC * skill_level + bonus).saturating_sub(SECURITY_PENALTY_PERC * security);
+
+    chance.clamp(MIN_SUCCESS_PERC, MAX_SUCCESS_PERC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use constants::hacking::*;
+
+    #[test]
+    fn hack_duration_test() {
+        // a novice on the easiest target takes exactly the base time
+        assert_eq!(hack_duration(60, 0, 1), 60);
+        // more skill is faster
+        assert_eq!(hack_duration(60, 1, 1), 40);
+        assert_eq!(hack_duration(60, 4, 1), 20);
+        // more security is slower
+        assert_eq!(hack_duration(60, 0, 3), 120);
+        // but nothing is instant
+        assert_eq!(hack_duration(5, 100, 0), 1);
+    }
+
+    #[test]
+    fn success_chance_test() {
+        assert_eq!(success_chance_perc(0, 1, 0), BASE_SUCCESS_PERC - SECURITY_PENALTY_PERC);
+        assert!(success_chance_perc(1, 1, 0) > success_chance_perc(0, 1, 0));
+        assert!(success_chance_perc(0, 2, 0) < success_chance_perc(0, 1, 0));
+        assert!(success_chance_perc(0, 2, RECONFIGURE_BONUS_PERC) > success_chance_perc(0, 2, 0));
+
+        assert_eq!(success_chance_perc(100, 0, 0), MAX_SUCCESS_PERC);
+        assert_eq!(success_chance_perc(0, 100, 0), MIN_SUCCESS_PERC);
+    }
+}
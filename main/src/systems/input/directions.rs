@@ -0,0 +1,40 @@
+//! Which way things are from the player, for the toggles which list whatever is next to them
+
+use super::*;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    Here,
+}
+
+pub fn get_direction(my_pos: TilePos, other_pos: TilePos) -> Direction {
+    if my_pos.y < other_pos.y {
+        Direction::South
+    } else if my_pos.y > other_pos.y {
+        Direction::North
+    } else if my_pos.x < other_pos.x {
+        Direction::East
+    } else if my_pos.x > other_pos.x {
+        Direction::West
+    } else {
+        Direction::Here
+    }
+}
+
+pub fn is_neighbor(pos: TilePos, neighbor_positions: &[TilePos]) -> bool {
+    neighbor_positions.contains(&pos)
+}
+
+pub fn to_string(dir: Direction) -> &'static str {
+    match dir {
+        Direction::South => "South",
+        Direction::East => "East",
+        Direction::North => "North",
+        Direction::West => "West",
+        Direction::Here => "Here",
+    }
+}
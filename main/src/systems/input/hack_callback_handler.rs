@@ -2,15 +2,34 @@ use super::*;
 
 use super::dialogue_helpers::{launch_dialogue, DialogueBuilder};
 
+use codex::Codex;
 use components::*;
 use resources::*;
+use skills::SkillTree;
 
 pub struct HackCallbackHandlerSystem;
 
 #[derive(SystemData)]
 pub struct HackCallbackHandlerSystemData<'a> {
-    queued_actions: Write<'a, QueuedPlayerActions>,
+    ongoing_action: Write<'a, OngoingPlayerAction>,
     hackable: WriteStorage<'a, Hackable>,
+    door: ReadStorage<'a, Door>,
+    heat_source: ReadStorage<'a, HeatSource>,
+    window: ReadStorage<'a, Window>,
+    life_support: ReadStorage<'a, LifeSupport>,
+    light_source: ReadStorage<'a, LightSource>,
+    generator: ReadStorage<'a, PowerGenerator>,
+    power_consumer: ReadStorage<'a, PowerConsumer>,
+    terminal: ReadStorage<'a, Terminal>,
+    security_camera: ReadStorage<'a, SecurityCamera>,
+    network_device: ReadStorage<'a, NetworkDevice>,
+    has_position: ReadStorage<'a, HasPosition>,
+    player: ReadStorage<'a, Player>,
+    equipment: ReadStorage<'a, Equipment>,
+    equippable: ReadStorage<'a, Equippable>,
+    entities: Entities<'a>,
+    skills: Read<'a, SkillTree>,
+    codex: Read<'a, Codex>,
     callbacks: Write<'a, Callbacks>,
 }
 
@@ -24,34 +43,39 @@ impl<'a> System<'a> for HackCallbackHandlerSystem {
         });
 
         for hcb in hack_callbacks {
-            handle_hack_callback(hcb, &mut data.queued_actions, &mut data.hackable, &mut data.callbacks);
+            handle_hack_callback(hcb, &mut data);
         }
     }
 }
 
-fn handle_hack_callback(
-    hack_callback: HackCallback,
-    queued_actions: &mut QueuedPlayerActions,
-    hackable: &mut WriteStorage<'_, Hackable>,
-    callbacks: &mut Callbacks,
-) {
+fn handle_hack_callback(hack_callback: HackCallback, data: &mut HackCallbackHandlerSystemData) {
     match hack_callback {
         HackCallback::InitiateHack { target, turn_duration } => {
-            for _ in 0..turn_duration {
-                queued_actions.action_queue.push_back(QueuedPlayerAction::Wait);
-            }
-
-            queued_actions.action_queue.push_back(QueuedPlayerAction::Hack { target });
+            data.ongoing_action.current = Some(OngoingAction::new(OngoingActionKind::Hack { target }, turn_duration));
         }
         HackCallback::ChooseHackTarget { entity } => {
-            let hackable = hackable
-                .get_mut(entity)
+            let hackable = data
+                .hackable
+                .get(entity)
                 .expect("If we initiated hack on an entity, it better be hackable");
 
-            let mut builder = DialogueBuilder::new(&format!("Hacking {}...", hackable.name));
+            let skill_level = hack_helpers::hacking_level(&data.skills);
+            let security = hackable.security;
+
+            let mut builder;
 
             match &hackable.hack_state {
                 HackState::Uncompromised => {
+                    let turn_duration = equipment_helpers::hack_turns(
+                        hack_helpers::hack_duration(constants::hacking::COMPROMISE_TURNS, skill_level, security),
+                        &worn_modifiers(data),
+                    );
+                    let chance = hack_helpers::success_chance_perc(skill_level, security, 0);
+
+                    builder = DialogueBuilder::new(&format!(
+                        "Hacking {}... (security {}; {} turns, {}% chance)",
+                        hackable.name, security, turn_duration, chance
+                    ));
                     builder = builder.with_option(
                         "[Compromise]",
                         vec![
@@ -59,66 +83,309 @@ fn handle_hack_callback(
                                 target: HackTarget {
                                     entity,
                                     hack_type: HackType::Compromise,
+                                    via: None,
                                 },
-                                turn_duration: 60,
+                                turn_duration,
                             }),
                             Callback::EndDialogue,
                         ],
                     );
-                }
-                HackState::Compromised => {
-                    builder = builder
-                        .with_option(
-                            "[Lock Shut]",
+                    if data.door.contains(entity) {
+                        builder = builder.with_option_if_known(
+                            &data.codex,
+                            constants::codex::DOOR_OVERRIDE_CODE,
+                            "[Enter the override code]",
                             vec![
                                 Callback::Hack(HackCallback::InitiateHack {
                                     target: HackTarget {
                                         entity,
-                                        hack_type: HackType::Door {
-                                            new_door_behavior: DoorBehavior::StayClosed,
-                                        },
+                                        hack_type: HackType::EnterCode,
+                                        via: None,
                                     },
-                                    turn_duration: 5,
-                                }),
-                                Callback::EndDialogue,
-                            ],
-                        )
-                        .with_option(
-                            "[Lock Open]",
-                            vec![
-                                Callback::Hack(HackCallback::InitiateHack {
-                                    target: HackTarget {
-                                        entity,
-                                        hack_type: HackType::Door {
-                                            new_door_behavior: DoorBehavior::StayOpen,
-                                        },
-                                    },
-                                    turn_duration: 5,
-                                }),
-                                Callback::EndDialogue,
-                            ],
-                        )
-                        .with_option(
-                            "[Set to Automatic]",
-                            vec![
-                                Callback::Hack(HackCallback::InitiateHack {
-                                    target: HackTarget {
-                                        entity,
-                                        hack_type: HackType::Door {
-                                            new_door_behavior: DoorBehavior::FullAuto,
-                                        },
-                                    },
-                                    turn_duration: 5,
+                                    turn_duration: constants::codex::ENTER_CODE_TURNS,
                                 }),
                                 Callback::EndDialogue,
                             ],
                         );
+                    }
+                }
+                HackState::LockedOut => {
+                    builder = DialogueBuilder::new(&format!("The {} has locked you out.", hackable.name));
+                }
+                HackState::Compromised => {
+                    let (turn_duration, chance) = reconfigure_stats(skill_level, security, &worn_modifiers(data));
+
+                    builder = DialogueBuilder::new(&format!(
+                        "Hacking {}... (each command takes {} turns, {}% chance)",
+                        hackable.name, turn_duration, chance
+                    ));
+                    builder = with_device_options(
+                        builder,
+                        data,
+                        CommandTarget {
+                            entity,
+                            via: None,
+                            turn_duration,
+                        },
+                    );
+                    if let Some(terminal) = data.terminal.get(entity) {
+                        builder = with_network_options(builder, data, entity, terminal.network);
+                    }
                 }
             };
 
             builder = builder.with_option("[Cancel]", vec![Callback::EndDialogue]);
 
-            launch_dialogue(builder, callbacks);
+            launch_dialogue(builder, &mut data.callbacks);
         }
+        HackCallback::ChooseRemoteTarget { terminal, entity } => {
+            let terminal_hackable = data
+                .hackable
+                .get(terminal)
+                .expect("If we went through a terminal, it better be hackable");
+
+            let mut builder = match terminal_hackable.hack_state {
+                HackState::Compromised => {
+                    let skill_level = hack_helpers::hacking_level(&data.skills);
+                    let (turn_duration, chance) = reconfigure_stats(skill_level, terminal_hackable.security, &worn_modifiers(data));
+
+                    let device_name = data.hackable.get(entity).map(|hackable| hackable.name.as_str()).unwrap_or("device");
+
+                    let builder = DialogueBuilder::new(&format!(
+                        "Controlling {} through the {}... (each command takes {} turns, {}% chance)",
+                        device_name, terminal_hackable.name, turn_duration, chance
+                    ));
+                    with_device_options(
+                        builder,
+                        data,
+                        CommandTarget {
+                            entity,
+                            via: Some(terminal),
+                            turn_duration,
+                        },
+                    )
+                }
+                _ => DialogueBuilder::new(&format!("The {} is no longer under your control.", terminal_hackable.name)),
+            };
+
+            builder = builder.with_option("[Cancel]", vec![Callback::EndDialogue]);
+
+            launch_dialogue(builder, &mut data.callbacks);
+        }
+    }
+}
+
+// Whatever the player is wearing which helps them hack
+fn worn_modifiers(data: &HackCallbackHandlerSystemData) -> EquipModifiers {
+    let equipment = (&data.player, &data.equipment).join().map(|(_, equipment)| equipment).next();
+    equipment_helpers::worn_modifiers(equipment, &data.equippable)
+}
+
+// How long each command takes on something already compromised, and how likely it is to work
+fn reconfigure_stats(skill_level: usize, security: usize, worn: &EquipModifiers) -> (usize, usize) {
+    let turn_duration = equipment_helpers::hack_turns(
+        hack_helpers::hack_duration(constants::hacking::RECONFIGURE_TURNS, skill_level, security),
+        worn,
+    );
+    let chance = hack_helpers::success_chance_perc(skill_level, security, constants::hacking::RECONFIGURE_BONUS_PERC);
+    (turn_duration, chance)
+}
+
+// What a command is going to, and how long it takes to get there
+#[derive(Copy, Clone)]
+struct CommandTarget {
+    entity: Entity,
+    // the terminal it's going through, if the device is being controlled remotely
+    via: Option<Entity>,
+    turn_duration: usize,
+}
+
+fn with_device_options(mut builder: DialogueBuilder, data: &HackCallbackHandlerSystemData, target: CommandTarget) -> DialogueBuilder {
+    let entity = target.entity;
+
+    if data.door.contains(entity) {
+        builder = with_door_options(builder, target);
+    }
+    if data.security_camera.contains(entity) {
+        builder = with_camera_options(builder, target);
+    }
+    if data.heat_source.contains(entity) {
+        builder = with_heat_source_options(builder, target);
+    }
+    if data.window.contains(entity) {
+        builder = with_window_options(builder, target);
+    }
+    if data.life_support.contains(entity) {
+        builder = with_life_support_options(builder, target);
+    }
+    if data.light_source.contains(entity) {
+        builder = with_light_options(builder, target);
+    }
+    if data.generator.contains(entity) {
+        builder = with_generator_options(builder, target);
+    }
+    if data.power_consumer.contains(entity) {
+        builder = with_power_consumer_options(builder, target);
+    }
+
+    builder
+}
+
+// Every device on the terminal's network, nearest first
+fn with_network_options(
+    mut builder: DialogueBuilder,
+    data: &HackCallbackHandlerSystemData,
+    terminal: Entity,
+    network: usize,
+) -> DialogueBuilder {
+    let terminal_pos = data.has_position.get(terminal).map(|hp| hp.position);
+
+    let mut devices: Vec<(Entity, &Hackable, TilePos)> = (&data.entities, &data.network_device, &data.hackable, &data.has_position)
+        .join()
+        .filter(|(_, device, _, _)| device.network == network)
+        .map(|(entity, _, hackable, hp)| (entity, hackable, hp.position))
+        .collect();
+
+    if let Some(terminal_pos) = terminal_pos {
+        devices.sort_by_key(|&(_, _, pos)| (pos.manhattan_distance(terminal_pos), pos.y, pos.x));
+    }
+
+    for (entity, hackable, pos) in devices {
+        let text = match terminal_pos {
+            Some(terminal_pos) => format!("[{} ({})]", hackable.name, describe_offset(terminal_pos, pos)),
+            None => format!("[{}]", hackable.name),
+        };
+
+        builder = builder.with_option(
+            &text,
+            vec![
+                Callback::EndDialogue,
+                Callback::Hack(HackCallback::ChooseRemoteTarget { terminal, entity }),
+            ],
+        );
+    }
+
+    builder
+}
+
+// Something like "3N 12E", for where a device is relative to the terminal
+fn describe_offset(from: TilePos, to: TilePos) -> String {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+
+    let mut parts = Vec::new();
+    if dy < 0 {
+        parts.push(format!("{}N", -dy));
+    } else if dy > 0 {
+        parts.push(format!("{}S", dy));
+    }
+    if dx > 0 {
+        parts.push(format!("{}E", dx));
+    } else if dx < 0 {
+        parts.push(format!("{}W", -dx));
+    }
+
+    if parts.is_empty() {
+        "Here".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn reconfigure_option(builder: DialogueBuilder, text: &str, target: CommandTarget, hack_type: HackType) -> DialogueBuilder {
+    builder.with_option(
+        text,
+        vec![
+            Callback::Hack(HackCallback::InitiateHack {
+                target: HackTarget {
+                    entity: target.entity,
+                    hack_type,
+                    via: target.via,
+                },
+                turn_duration: target.turn_duration,
+            }),
+            Callback::EndDialogue,
+        ],
+    )
+}
+
+fn with_door_options(builder: DialogueBuilder, target: CommandTarget) -> DialogueBuilder {
+    let door_option = |builder, text, new_door_behavior| reconfigure_option(builder, text, target, HackType::Door { new_door_behavior });
+
+    let access_option = |builder, text, new_access| reconfigure_option(builder, text, target, HackType::DoorAccess { new_access });
+
+    let builder = door_option(builder, "[Lock Shut]", DoorBehavior::StayClosed);
+    let builder = door_option(builder, "[Lock Open]", DoorBehavior::StayOpen);
+    let builder = door_option(builder, "[Set to Automatic]", DoorBehavior::FullAuto);
+    let builder = door_option(builder, "[Open Automatically, Never Close]", DoorBehavior::AutoOpen);
+    let builder = door_option(builder, "[Close Automatically, Never Open]", DoorBehavior::AutoClose);
+
+    let builder = access_option(builder, "[Open for Everyone]", DoorAccess::Everyone);
+    let builder = access_option(builder, "[Open Only for You]", DoorAccess::PlayerOnly);
+    let builder = access_option(builder, "[Keep Aliens Out]", DoorAccess::NoAliens);
+    access_option(builder, "[Require Keycard]", DoorAccess::Keycard)
+}
+
+fn with_camera_options(builder: DialogueBuilder, target: CommandTarget) -> DialogueBuilder {
+    let builder = reconfigure_option(builder, "[Tap Feed]", target, HackType::CameraFeed { tapped: true });
+    reconfigure_option(builder, "[Cut Feed]", target, HackType::CameraFeed { tapped: false })
+}
+
+fn with_heat_source_options(builder: DialogueBuilder, target: CommandTarget) -> DialogueBuilder {
+    let heat_option = |builder, text, new_setting| reconfigure_option(builder, text, target, HackType::HeatSource { new_setting });
+
+    let builder = heat_option(builder, "[Set to Heat]", HeatSetting::Heat);
+    let builder = heat_option(builder, "[Set to Cool]", HeatSetting::Cool);
+    let builder = heat_option(builder, "[Turn Off]", HeatSetting::Off);
+    heat_option(builder, "[Overload]", HeatSetting::Overload)
+}
+
+fn with_window_options(builder: DialogueBuilder, target: CommandTarget) -> DialogueBuilder {
+    let window_option = |builder, text, new_window_state| reconfigure_option(builder, text, target, HackType::Window { new_window_state });
+
+    let builder = window_option(builder, "[Open Window]", WindowState::Open);
+    window_option(builder, "[Close Window]", WindowState::Closed)
+}
+
+fn with_life_support_options(builder: DialogueBuilder, target: CommandTarget) -> DialogueBuilder {
+    let builder = reconfigure_option(builder, "[Start Up]", target, HackType::LifeSupport { enabled: true });
+    reconfigure_option(builder, "[Shut Down]", target, HackType::LifeSupport { enabled: false })
+}
+
+fn with_light_options(builder: DialogueBuilder, target: CommandTarget) -> DialogueBuilder {
+    let builder = reconfigure_option(builder, "[Lights On]", target, HackType::Light { enabled: true });
+    reconfigure_option(builder, "[Lights Off]", target, HackType::Light { enabled: false })
+}
+
+fn with_generator_options(builder: DialogueBuilder, target: CommandTarget) -> DialogueBuilder {
+    let builder = reconfigure_option(builder, "[Start Generator]", target, HackType::Generator { enabled: true });
+    reconfigure_option(builder, "[Stop Generator]", target, HackType::Generator { enabled: false })
+}
+
+fn with_power_consumer_options(builder: DialogueBuilder, target: CommandTarget) -> DialogueBuilder {
+    let priority_option = |builder, text, new_priority| reconfigure_option(builder, text, target, HackType::PowerPriority { new_priority });
+
+    let builder = reconfigure_option(builder, "[Cut Power]", target, HackType::PowerConnection { connected: false });
+    let builder = reconfigure_option(builder, "[Restore Power]", target, HackType::PowerConnection { connected: true });
+    let builder = priority_option(builder, "[Prioritize Power]", PowerPriority::Critical);
+    priority_option(builder, "[Deprioritize Power]", PowerPriority::Low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_offset_test() {
+        let terminal = TilePos { x: 10, y: 10 };
+        let at = |x, y| describe_offset(terminal, TilePos { x, y });
+
+        assert_eq!("Here", at(10, 10));
+        assert_eq!("3N", at(10, 7));
+        assert_eq!("1S", at(10, 11));
+        assert_eq!("12E", at(22, 10));
+        assert_eq!("4W", at(6, 10));
+        // north/south comes first
+        assert_eq!("3N 12E", at(22, 7));
+        assert_eq!("2S 1W", at(9, 12));
     }
 }
@@ -0,0 +1,309 @@
+use super::*;
+
+use super::dialogue_helpers::{launch_dialogue, DialogueBuilder};
+
+use components::*;
+use resources::*;
+
+pub struct InventoryCallbackHandlerSystem;
+
+#[derive(SystemData)]
+pub struct InventoryCallbackHandlerSystemData<'a> {
+    player: ReadStorage<'a, Player>,
+    has_position: WriteStorage<'a, HasPosition>,
+    inventory: WriteStorage<'a, Inventory>,
+    item: ReadStorage<'a, Item>,
+    equipment: WriteStorage<'a, Equipment>,
+    equippable: ReadStorage<'a, Equippable>,
+    corpse: ReadStorage<'a, Corpse>,
+    entities: Entities<'a>,
+
+    npc_moves: Write<'a, NpcMoves>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+impl<'a> System<'a> for InventoryCallbackHandlerSystem {
+    type SystemData = InventoryCallbackHandlerSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        let inventory_callbacks = data.callbacks.take_some(|cb| match cb {
+            Callback::Inventory(icb) => TakeDecision::Take(icb),
+            x => TakeDecision::Leave(x),
+        });
+
+        for icb in inventory_callbacks {
+            match icb {
+                InventoryCallback::PickUp { item } => pick_up(&mut data, item),
+                InventoryCallback::Drop { item } => drop(&mut data, item),
+                InventoryCallback::ToggleWorn { item } => toggle_worn(&mut data, item),
+                InventoryCallback::Search { corpse } => search(&mut data, corpse),
+                InventoryCallback::TakeFrom { corpse, item } => take_from(&mut data, corpse, item),
+            }
+        }
+    }
+}
+
+fn pick_up(data: &mut InventoryCallbackHandlerSystemData, item: Entity) {
+    let inventory = (&data.player, &mut data.inventory)
+        .join()
+        .map(|(_, inventory)| inventory)
+        .next()
+        .expect("The player should have an inventory");
+
+    // someone else might have gotten to it first
+    if !data.has_position.contains(item) {
+        return;
+    }
+
+    if inventory.is_full() {
+        launch_too_full_dialogue(data, item);
+        return;
+    }
+
+    inventory.items.push(item);
+    data.has_position.remove(item);
+
+    turn_state_helpers::yield_to_npc(&mut data.npc_moves);
+}
+
+fn drop(data: &mut InventoryCallbackHandlerSystemData, item: Entity) {
+    let (inventory, player_entity) = match (&data.player, &mut data.inventory, &data.entities).join().next() {
+        Some((_, inventory, entity)) => (inventory, entity),
+        None => return,
+    };
+
+    let position = data
+        .has_position
+        .get(player_entity)
+        .expect("The player should have a position")
+        .position;
+
+    if !inventory.items.contains(&item) {
+        return;
+    }
+
+    inventory.items.retain(|&carried| carried != item);
+    if let Some(equipment) = data.equipment.get_mut(player_entity) {
+        equipment.take_off(item);
+    }
+    data.has_position
+        .insert(item, HasPosition { position })
+        .expect("The item should be current");
+
+    turn_state_helpers::yield_to_npc(&mut data.npc_moves);
+}
+
+fn toggle_worn(data: &mut InventoryCallbackHandlerSystemData, item: Entity) {
+    let slot = match data.equippable.get(item) {
+        Some(equippable) => equippable.slot,
+        // nothing to do; the inventory screen says what can be worn
+        None => return,
+    };
+
+    let equipment = match (&data.player, &mut data.equipment).join().next() {
+        Some((_, equipment)) => equipment,
+        None => return,
+    };
+
+    if equipment.is_wearing(item) {
+        equipment.take_off(item);
+    } else {
+        // whatever was in that slot goes back to just being carried
+        *equipment.slot_mut(slot) = Some(item);
+    }
+
+    turn_state_helpers::yield_to_npc(&mut data.npc_moves);
+}
+
+fn launch_too_full_dialogue(data: &mut InventoryCallbackHandlerSystemData, item: Entity) {
+    let name = data.item.get(item).map(|i| i.name.as_str()).unwrap_or("it");
+    let builder = DialogueBuilder::new(&format!("You can't carry any more; drop something before picking up the {}.", name))
+        .with_option("[Continue]", vec![Callback::EndDialogue]);
+    launch_dialogue(builder, &mut data.callbacks);
+}
+
+fn search(data: &mut InventoryCallbackHandlerSystemData, corpse_entity: Entity) {
+    let corpse = match data.corpse.get(corpse_entity) {
+        Some(corpse) => corpse,
+        None => return,
+    };
+
+    let mut text = format!("The dead {}. {}", corpse.name, death_helpers::corpse_text(corpse.cause));
+    if let Some(clue) = corpse.clue.as_ref() {
+        text = format!("{}\n\n{}", text, clue);
+    }
+
+    let carried = data
+        .inventory
+        .get(corpse_entity)
+        .map(|inventory| inventory.items.clone())
+        .unwrap_or_default();
+
+    if carried.is_empty() {
+        text = format!("{}\n\nIt has nothing on it.", text);
+    }
+
+    let mut builder = DialogueBuilder::new(&text);
+
+    for item in carried {
+        let name = data.item.get(item).map(|i| i.name.as_str()).unwrap_or("Something");
+        builder = builder.with_option(
+            &format!("[Take the {}]", name),
+            vec![
+                Callback::EndDialogue,
+                Callback::Inventory(InventoryCallback::TakeFrom {
+                    corpse: corpse_entity,
+                    item,
+                }),
+            ],
+        );
+    }
+
+    builder = builder.with_option("[Leave it]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, &mut data.callbacks);
+}
+
+fn take_from(data: &mut InventoryCallbackHandlerSystemData, corpse: Entity, item: Entity) {
+    let player_full = (&data.player, &data.inventory)
+        .join()
+        .map(|(_, inventory)| inventory.is_full())
+        .next()
+        .expect("The player should have an inventory");
+
+    if player_full {
+        launch_too_full_dialogue(data, item);
+        return;
+    }
+
+    match data.inventory.get_mut(corpse) {
+        Some(inventory) if inventory.items.contains(&item) => inventory.items.retain(|&carried| carried != item),
+        _ => return,
+    }
+
+    if let Some((_, inventory)) = (&data.player, &mut data.inventory).join().next() {
+        inventory.items.push(item);
+    }
+
+    turn_state_helpers::yield_to_npc(&mut data.npc_moves);
+
+    // see what else is left on it
+    search(data, corpse);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{Builder, RunNow, World, WorldExt};
+
+    const PLAYER_POS: TilePos = TilePos { x: 3, y: 4 };
+
+    fn world(capacity: usize) -> (World, Entity) {
+        let mut world = World::new();
+        System::setup(&mut InventoryCallbackHandlerSystem, &mut world);
+
+        let player = world
+            .create_entity()
+            .with(Player {})
+            .with(HasPosition { position: PLAYER_POS })
+            .with(Inventory::new(capacity))
+            .with(Equipment::default())
+            .build();
+
+        (world, player)
+    }
+
+    fn item(world: &mut World, name: &str) -> Entity {
+        world
+            .create_entity()
+            .with(Item { name: name.to_string() })
+            .with(HasPosition {
+                position: TilePos { x: 4, y: 4 },
+            })
+            .build()
+    }
+
+    fn handle(world: &mut World, callback: InventoryCallback) {
+        world.write_resource::<Callbacks>().push(Callback::Inventory(callback));
+        InventoryCallbackHandlerSystem.run_now(world);
+        world.maintain();
+    }
+
+    fn carried(world: &World, player: Entity) -> Vec<Entity> {
+        world.read_storage::<Inventory>().get(player).unwrap().items.clone()
+    }
+
+    #[test]
+    fn pick_up_test() {
+        let (mut world, player) = world(5);
+        let wrench = item(&mut world, "Wrench");
+
+        handle(&mut world, InventoryCallback::PickUp { item: wrench });
+
+        assert_eq!(vec![wrench], carried(&world, player));
+        assert!(!world.read_storage::<HasPosition>().contains(wrench));
+        assert_eq!(1, world.read_resource::<NpcMoves>().npc_moves_remaining);
+
+        // it's not lying around anymore, so it can't be picked up twice
+        handle(&mut world, InventoryCallback::PickUp { item: wrench });
+        assert_eq!(vec![wrench], carried(&world, player));
+    }
+
+    #[test]
+    fn drop_test() {
+        let (mut world, player) = world(5);
+        let wrench = item(&mut world, "Wrench");
+        handle(&mut world, InventoryCallback::PickUp { item: wrench });
+
+        handle(&mut world, InventoryCallback::Drop { item: wrench });
+
+        assert!(carried(&world, player).is_empty());
+        assert_eq!(PLAYER_POS, world.read_storage::<HasPosition>().get(wrench).unwrap().position);
+    }
+
+    #[test]
+    fn dropping_takes_it_off_test() {
+        let (mut world, player) = world(5);
+        let helmet = item(&mut world, "Helmet");
+        world
+            .write_storage::<Equippable>()
+            .insert(
+                helmet,
+                Equippable {
+                    slot: EquipSlot::Head,
+                    modifiers: EquipModifiers::default(),
+                },
+            )
+            .unwrap();
+
+        handle(&mut world, InventoryCallback::PickUp { item: helmet });
+        handle(&mut world, InventoryCallback::ToggleWorn { item: helmet });
+        assert_eq!(Some(helmet), world.read_storage::<Equipment>().get(player).unwrap().head);
+
+        handle(&mut world, InventoryCallback::Drop { item: helmet });
+        assert_eq!(None, world.read_storage::<Equipment>().get(player).unwrap().head);
+    }
+
+    #[test]
+    fn capacity_test() {
+        let (mut world, player) = world(2);
+        let items: Vec<Entity> = ["Wrench", "Keycard", "Flashlight"]
+            .iter()
+            .map(|name| item(&mut world, name))
+            .collect();
+
+        for &item in &items {
+            handle(&mut world, InventoryCallback::PickUp { item });
+        }
+
+        assert_eq!(items[..2].to_vec(), carried(&world, player));
+        // the last one stays where it was, and the player is told why
+        assert!(world.read_storage::<HasPosition>().contains(items[2]));
+        assert!(world
+            .read_resource::<Callbacks>()
+            .iter()
+            .any(|cb| matches!(cb, Callback::StartDialogue(_))));
+        assert_eq!(2, world.read_resource::<NpcMoves>().npc_moves_remaining);
+    }
+}
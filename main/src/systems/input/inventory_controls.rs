@@ -0,0 +1,70 @@
+//! System which opens and closes the inventory screen, and handles player input while it's open
+
+use super::*;
+
+use components::{Inventory, Player};
+use resources::*;
+
+pub struct InventoryControlSystem;
+
+#[derive(SystemData)]
+pub struct InventoryControlSystemData<'a> {
+    player: ReadStorage<'a, Player>,
+    inventory: ReadStorage<'a, Inventory>,
+
+    keyboard: ReadExpect<'a, Keyboard>,
+    npc_moves: Read<'a, NpcMoves>,
+    keyboard_focus: Write<'a, KeyboardFocus>,
+    inventory_screen: Write<'a, InventoryScreen>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+impl<'a> System<'a> for InventoryControlSystem {
+    type SystemData = InventoryControlSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        match *data.keyboard_focus {
+            KeyboardFocus::GameMap => {
+                if data.keyboard[Key::I] == ButtonState::Pressed {
+                    *data.keyboard_focus = KeyboardFocus::Inventory;
+                    data.inventory_screen.selected_index = 0;
+                }
+            }
+            KeyboardFocus::Inventory => handle_inventory_input(&mut data),
+            KeyboardFocus::Dialogue => {}
+        }
+    }
+}
+
+fn handle_inventory_input(data: &mut InventoryControlSystemData) {
+    let items: Vec<Entity> = (&data.player, &data.inventory)
+        .join()
+        .map(|(_, inventory)| inventory.items.clone())
+        .next()
+        .unwrap_or_default();
+
+    let screen = &mut data.inventory_screen;
+
+    // dropping things can leave the highlight past the end of the list
+    if screen.selected_index >= items.len() {
+        screen.selected_index = items.len().saturating_sub(1);
+    }
+
+    if data.keyboard[Key::Escape] == ButtonState::Pressed || data.keyboard[Key::I] == ButtonState::Pressed {
+        *data.keyboard_focus = KeyboardFocus::GameMap;
+    } else if data.keyboard[Key::Up] == ButtonState::Pressed {
+        screen.selected_index = screen.selected_index.saturating_sub(1);
+    } else if data.keyboard[Key::Down] == ButtonState::Pressed {
+        if screen.selected_index + 1 < items.len() {
+            screen.selected_index += 1;
+        }
+    } else if data.keyboard[Key::D] == ButtonState::Pressed && data.npc_moves.player_can_move() {
+        if let Some(&item) = items.get(screen.selected_index) {
+            data.callbacks.push(Callback::Inventory(InventoryCallback::Drop { item }));
+        }
+    } else if data.keyboard[Key::E] == ButtonState::Pressed && data.npc_moves.player_can_move() {
+        if let Some(&item) = items.get(screen.selected_index) {
+            data.callbacks.push(Callback::Inventory(InventoryCallback::ToggleWorn { item }));
+        }
+    }
+}
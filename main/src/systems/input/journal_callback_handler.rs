@@ -0,0 +1,91 @@
+use super::*;
+
+use super::dialogue_helpers::{launch_dialogue, DialogueBuilder};
+
+use codex::{Codex, CodexTopic};
+use resources::*;
+
+pub struct JournalCallbackHandlerSystem;
+
+#[derive(SystemData)]
+pub struct JournalCallbackHandlerSystemData<'a> {
+    codex: Read<'a, Codex>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+impl<'a> System<'a> for JournalCallbackHandlerSystem {
+    type SystemData = JournalCallbackHandlerSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        let journal_callbacks = data.callbacks.take_some(|cb| match cb {
+            Callback::Journal(jcb) => TakeDecision::Take(jcb),
+            x => TakeDecision::Leave(x),
+        });
+
+        for jcb in journal_callbacks {
+            handle_journal_callback(jcb, &data.codex, &mut data.callbacks);
+        }
+    }
+}
+
+fn handle_journal_callback(jcb: JournalCallback, codex: &Codex, callbacks: &mut Callbacks) {
+    let builder = match jcb {
+        JournalCallback::Contents => topics_dialogue(codex),
+        JournalCallback::OpenTopic { topic } => topic_dialogue(codex, topic),
+        JournalCallback::OpenEntry { key } => entry_dialogue(codex, &key),
+    };
+
+    launch_dialogue(builder, callbacks);
+}
+
+fn topics_dialogue(codex: &Codex) -> DialogueBuilder {
+    let topics = codex.topics();
+
+    if topics.is_empty() {
+        return DialogueBuilder::new(
+            "Your journal is empty. Whatever you write in it, you will remember, even after the tether pulls you back.",
+        )
+        .with_option("[Close]", vec![Callback::EndDialogue]);
+    }
+
+    let mut builder = DialogueBuilder::new("Your journal. What do you want to look over?");
+
+    for (topic, count) in topics {
+        builder = builder.with_option(
+            &format!("[{} ({})]", topic.label(), count),
+            vec![Callback::EndDialogue, Callback::Journal(JournalCallback::OpenTopic { topic })],
+        );
+    }
+
+    builder.with_option("[Close]", vec![Callback::EndDialogue])
+}
+
+fn topic_dialogue(codex: &Codex, topic: CodexTopic) -> DialogueBuilder {
+    let mut builder = DialogueBuilder::new(topic.label());
+
+    for entry in codex.entries_on(topic) {
+        builder = builder.with_option(
+            &format!("[{}]", entry.title),
+            vec![
+                Callback::EndDialogue,
+                Callback::Journal(JournalCallback::OpenEntry { key: entry.key.clone() }),
+            ],
+        );
+    }
+
+    builder.with_option("[Back]", vec![Callback::EndDialogue, Callback::Journal(JournalCallback::Contents)])
+}
+
+fn entry_dialogue(codex: &Codex, key: &str) -> DialogueBuilder {
+    match codex.get(key) {
+        Some(entry) => DialogueBuilder::new(&format!("{}\n\n{}", entry.title, entry.text)).with_option(
+            "[Back]",
+            vec![
+                Callback::EndDialogue,
+                Callback::Journal(JournalCallback::OpenTopic { topic: entry.topic }),
+            ],
+        ),
+        None => DialogueBuilder::new("You can't find that page any more.")
+            .with_option("[Back]", vec![Callback::EndDialogue, Callback::Journal(JournalCallback::Contents)]),
+    }
+}
@@ -3,17 +3,36 @@ use super::*;
 use quicksilver::input::{ButtonState, Key, Keyboard};
 
 mod dialogue_controls;
+mod directions;
 mod hack_callback_handler;
+mod inventory_callback_handler;
+mod inventory_controls;
+mod journal_callback_handler;
 mod player_move;
+mod read_callback_handler;
+mod repair_callback_handler;
 mod talk_callback_handler;
 mod toggle_controls;
 mod toggle_hack;
+mod toggle_journal;
+mod toggle_pick_up;
+mod toggle_read;
+mod toggle_repair;
 mod toggle_talk;
 
 pub use dialogue_controls::DialogueControlSystem;
 pub use hack_callback_handler::HackCallbackHandlerSystem;
+pub use inventory_callback_handler::InventoryCallbackHandlerSystem;
+pub use inventory_controls::InventoryControlSystem;
+pub use journal_callback_handler::JournalCallbackHandlerSystem;
 pub use player_move::PlayerMoveSystem;
+pub use read_callback_handler::ReadCallbackHandlerSystem;
+pub use repair_callback_handler::RepairCallbackHandlerSystem;
 pub use talk_callback_handler::TalkCallbackHandlerSystem;
 pub use toggle_controls::ToggleControlSystem;
 pub use toggle_hack::ToggleHackSystem;
+pub use toggle_journal::ToggleJournalSystem;
+pub use toggle_pick_up::TogglePickUpSystem;
+pub use toggle_read::ToggleReadSystem;
+pub use toggle_repair::ToggleRepairSystem;
 pub use toggle_talk::ToggleTalkSystem;
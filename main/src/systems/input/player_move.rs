@@ -1,10 +1,15 @@
 use super::*;
 
 use quicksilver::input::{ButtonState, Key, Keyboard};
+use rand::Rng;
 use specs::Join;
 
+use action_helpers::{check_interruption, interruption_text};
+use dialogue_helpers::{launch_dialogue, DialogueBuilder};
+
 use components::*;
 use resources::*;
+use skills::{ExpGain, SkillTree};
 
 use world::TilePos;
 
@@ -15,11 +20,28 @@ pub struct PlayerMoveSystemData<'a> {
     blocks_movement: ReadStorage<'a, BlocksMovement>,
     hackable: WriteStorage<'a, Hackable>,
     door: WriteStorage<'a, Door>,
+    heat_source: WriteStorage<'a, HeatSource>,
+    window: WriteStorage<'a, Window>,
+    life_support: WriteStorage<'a, LifeSupport>,
+    light_source: WriteStorage<'a, LightSource>,
+    generator: WriteStorage<'a, PowerGenerator>,
+    power_consumer: WriteStorage<'a, PowerConsumer>,
+    security_camera: WriteStorage<'a, SecurityCamera>,
+    breakable: WriteStorage<'a, Breakable>,
+    repairable: ReadStorage<'a, Repairable>,
+    spare_parts: WriteStorage<'a, SpareParts>,
+    broken: WriteStorage<'a, Broken>,
     camera: ReadStorage<'a, Camera>,
+    breathes: ReadStorage<'a, Breathes>,
+    npc: ReadStorage<'a, NPC>,
+    entities: Entities<'a>,
     keyboard: ReadExpect<'a, Keyboard>,
     keyboard_focus: Read<'a, KeyboardFocus>,
-    queued_player_actions: Write<'a, QueuedPlayerActions>,
+    ongoing_action: Write<'a, OngoingPlayerAction>,
     npc_moves: Write<'a, NpcMoves>,
+    game_clock: Read<'a, GameClock>,
+    skills: Write<'a, SkillTree>,
+    callbacks: Write<'a, Callbacks>,
 }
 
 pub struct PlayerMoveSystem;
@@ -35,10 +57,17 @@ impl<'a> System<'a> for PlayerMoveSystem {
             return;
         }
 
-        let mut player_moved = do_queued_move(&mut data);
-        if !player_moved {
-            player_moved = do_manual_move(&mut data);
-        }
+        // while the player is busy, the only thing they can do is stop
+        let player_moved = if data.ongoing_action.current.is_some() {
+            if data.keyboard[Key::Escape] == ButtonState::Pressed {
+                data.ongoing_action.current = None;
+                false
+            } else {
+                do_ongoing_action(&mut data)
+            }
+        } else {
+            do_manual_move(&mut data)
+        };
 
         if player_moved {
             turn_state_helpers::yield_to_npc(&mut data.npc_moves);
@@ -46,32 +75,259 @@ impl<'a> System<'a> for PlayerMoveSystem {
     }
 }
 
-fn do_queued_move(data: &mut PlayerMoveSystemData) -> bool {
-    let next_action = data.queued_player_actions.action_queue.pop_front();
-    if next_action.is_none() {
-        return false;
+// Spends a turn on whatever the player is busy with, unless something has come up that they should
+// stop for; returns whether a turn was actually spent
+fn do_ongoing_action(data: &mut PlayerMoveSystemData) -> bool {
+    let mut action = match data.ongoing_action.current.take() {
+        Some(action) => action,
+        None => return false,
+    };
+
+    let now = action_surroundings(data, action.target());
+
+    if let Some(before) = action.last_seen.as_ref() {
+        if let Some(interruption) = check_interruption(before, &now) {
+            let builder = DialogueBuilder::new(interruption_text(interruption)).with_option("[Continue]", vec![Callback::EndDialogue]);
+            launch_dialogue(builder, &mut data.callbacks);
+            return false;
+        }
+    }
+
+    action.last_seen = Some(now);
+    action.elapsed_turns += 1;
+
+    if action.elapsed_turns < action.total_turns {
+        data.ongoing_action.current = Some(action);
+        return true;
+    }
+
+    match action.kind {
+        OngoingActionKind::Hack { target } => resolve_hack(data, target),
+        OngoingActionKind::Repair { target } => resolve_repair(data, target),
+    }
+
+    true
+}
+
+fn action_surroundings(data: &PlayerMoveSystemData, target: Entity) -> ActionSurroundings {
+    let player_position = get_pos(&data.player, &data.has_position);
+
+    let breath = (&data.player, &data.breathes).join().map(|(_, breathes)| breathes.contents).next();
+
+    let adjacent_npcs = (&data.entities, &data.npc, &data.has_position)
+        .join()
+        .filter(|(_, _, hp)| hp.position.manhattan_distance(player_position) <= 1)
+        .map(|(entity, _, _)| entity)
+        .collect();
+
+    let target = if data.entities.is_alive(target) {
+        data.has_position.get(target).map(|hp| TargetSnapshot {
+            position: hp.position,
+            hack_state: data.hackable.get(target).map(|hackable| hackable.hack_state.clone()),
+            broken: data.broken.contains(target),
+        })
+    } else {
+        None
+    };
+
+    ActionSurroundings {
+        breath,
+        player_position,
+        adjacent_npcs,
+        target,
+    }
+}
+
+// Repairs always work, as long as you still have the parts for them by the time you're done
+fn resolve_repair(data: &mut PlayerMoveSystemData, target: Entity) {
+    let repairable = match data.repairable.get(target) {
+        Some(repairable) => *repairable,
+        None => return,
+    };
+
+    let spare_parts = (&data.player, &mut data.spare_parts)
+        .join()
+        .map(|(_, spare_parts)| spare_parts)
+        .next();
+
+    let have = spare_parts.as_ref().map(|spare_parts| spare_parts.count).unwrap_or(0);
+    let skill_level = repair_helpers::repair_level(&data.skills);
+
+    if let Err(blocker) = repair_helpers::check_repair(&repairable, skill_level, have) {
+        let builder = DialogueBuilder::new(&repair_helpers::blocker_text(blocker)).with_option("[Continue]", vec![Callback::EndDialogue]);
+        launch_dialogue(builder, &mut data.callbacks);
+        return;
+    }
+
+    if let Some(spare_parts) = spare_parts {
+        spare_parts.count -= repairable.parts;
+    }
+
+    if let Some(breakable) = data.breakable.get_mut(target) {
+        breakable.condition = breakable.max_condition;
     }
+    // a shattered window gets a fresh pane
+    if let Some(window) = data.window.get_mut(target) {
+        if window.window_state == WindowState::Broken {
+            window.window_state = WindowState::Closed;
+        }
+        window.integrity = constants::window::MAX_INTEGRITY;
+    }
+    data.broken.remove(target);
 
-    match next_action.unwrap() {
-        QueuedPlayerAction::Wait => true,
-        QueuedPlayerAction::Hack { target } => {
-            let HackTarget { entity, hack_type } = target;
+    // if the skill tree doesn't have repair in it, there's just nothing to learn
+    let _ = data.skills.gain_experience(ExpGain {
+        exp: constants::repair::EXP_PER_PART * std::cmp::max(repairable.parts, 1),
+        skill_name: repair_helpers::REPAIR_SKILL,
+    });
+}
 
-            match hack_type {
-                HackType::Compromise => {
-                    if let Some(hackable) = data.hackable.get_mut(entity) {
-                        hackable.hack_state = HackState::Compromised;
-                    }
+const HACK_RNG_SALT: u64 = 0x4841_434b;
+
+// Hacks only work some of the time; the better the hacker and the weaker the security, the likelier
+// it is. Success teaches you something, and failure has consequences.
+fn resolve_hack(data: &mut PlayerMoveSystemData, target: HackTarget) {
+    use constants::hacking::*;
+
+    let HackTarget { entity, hack_type, via } = target;
+
+    // remote commands are only as hard as getting them through the terminal; that's also what
+    // locks you out if they go wrong
+    let controller = via.unwrap_or(entity);
+
+    let (security, bonus) = match data.hackable.get(controller) {
+        // it might have locked us out since the hack was started
+        Some(Hackable {
+            hack_state: HackState::LockedOut,
+            ..
+        }) => return,
+        Some(hackable) => match hack_type {
+            HackType::Compromise => (hackable.security, 0),
+            _ => (hackable.security, RECONFIGURE_BONUS_PERC),
+        },
+        None => return,
+    };
+
+    // there's no skill in knowing the code, so nothing to learn either
+    if let HackType::EnterCode = hack_type {
+        apply_hack(data, entity, HackType::Compromise);
+        return;
+    }
+
+    let skill_level = hack_helpers::hacking_level(&data.skills);
+    let chance = hack_helpers::success_chance_perc(skill_level, security, bonus);
+
+    let mut rng = turn_state_helpers::turn_rng(&data.game_clock, HACK_RNG_SALT);
+
+    if rng.gen_range(0, 100) < chance {
+        apply_hack(data, entity, hack_type);
+
+        // if the skill tree doesn't have hacking in it, there's just nothing to learn
+        let _ = data.skills.gain_experience(ExpGain {
+            exp: EXP_PER_SECURITY * std::cmp::max(security, 1),
+            skill_name: hack_helpers::HACKING_SKILL,
+        });
+    } else {
+        hack_failed(data, controller, security);
+    }
+}
+
+fn apply_hack(data: &mut PlayerMoveSystemData, entity: Entity, hack_type: HackType) {
+    match hack_type {
+        HackType::Compromise | HackType::EnterCode => {
+            if let Some(hackable) = data.hackable.get_mut(entity) {
+                hackable.hack_state = HackState::Compromised;
+            }
+            // getting into a camera means getting into its feed
+            if let Some(camera) = data.security_camera.get_mut(entity) {
+                camera.feed_tapped = true;
+            }
+        }
+        HackType::CameraFeed { tapped } => {
+            if let Some(camera) = data.security_camera.get_mut(entity) {
+                camera.feed_tapped = tapped;
+            }
+        }
+        HackType::Door { new_door_behavior } => {
+            if let Some(door) = data.door.get_mut(entity) {
+                door.door_behavior = new_door_behavior;
+            }
+        }
+        HackType::DoorAccess { new_access } => {
+            if let Some(door) = data.door.get_mut(entity) {
+                door.access = new_access;
+            }
+        }
+        HackType::HeatSource { new_setting } => {
+            if let Some(heat_source) = data.heat_source.get_mut(entity) {
+                heat_source.setting = new_setting;
+            }
+        }
+        HackType::Window { new_window_state } => {
+            // a broken window is broken; no amount of hacking will fix that
+            if let Some(window) = data.window.get_mut(entity) {
+                if window.window_state != WindowState::Broken {
+                    window.window_state = new_window_state;
                 }
-                HackType::Door { new_door_behavior } => {
-                    if let Some(door) = data.door.get_mut(entity) {
-                        door.door_behavior = new_door_behavior;
-                    }
+            }
+        }
+        HackType::LifeSupport { enabled } => {
+            if let Some(life_support) = data.life_support.get_mut(entity) {
+                life_support.enabled = enabled;
+            }
+        }
+        HackType::Light { enabled } => {
+            if let Some(light) = data.light_source.get_mut(entity) {
+                light.enabled = enabled;
+            }
+        }
+        HackType::Generator { enabled } => {
+            if let Some(generator) = data.generator.get_mut(entity) {
+                generator.enabled = enabled;
+            }
+        }
+        HackType::PowerConnection { connected } => {
+            if let Some(consumer) = data.power_consumer.get_mut(entity) {
+                consumer.connected = connected;
+            }
+        }
+        HackType::PowerPriority { new_priority } => {
+            if let Some(consumer) = data.power_consumer.get_mut(entity) {
+                consumer.priority = new_priority;
+            }
+        }
+    }
+}
+
+fn hack_failed(data: &mut PlayerMoveSystemData, entity: Entity, security: usize) {
+    use constants::hacking::*;
+
+    let mut text = "The hack failed.".to_string();
+
+    if security >= ALARM_SECURITY {
+        // an alarm goes off, and every door nearby locks itself shut
+        if let Some(&HasPosition { position: target_pos }) = data.has_position.get(entity) {
+            for (door, hp) in (&mut data.door, &data.has_position).join() {
+                if hp.position.manhattan_distance(target_pos) <= ALARM_RADIUS {
+                    door.door_behavior = DoorBehavior::StayClosed;
                 }
             }
-            true
+        }
+
+        text.push_str(" An alarm blares, and the doors nearby slam shut.");
+    }
+
+    if let Some(hackable) = data.hackable.get_mut(entity) {
+        hackable.failed_attempts += 1;
+
+        if hackable.failed_attempts >= MAX_FAILED_ATTEMPTS {
+            hackable.hack_state = HackState::LockedOut;
+            text.push_str(&format!(" The {} has locked you out.", hackable.name));
         }
     }
+
+    let builder = DialogueBuilder::new(&text).with_option("[Continue]", vec![Callback::EndDialogue]);
+    launch_dialogue(builder, &mut data.callbacks);
 }
 
 fn do_manual_move(data: &mut PlayerMoveSystemData) -> bool {
@@ -0,0 +1,71 @@
+use super::*;
+
+use super::dialogue_helpers::{launch_dialogue, DialogueBuilder};
+
+use codex::Codex;
+use components::*;
+use lore::LoreDatabase;
+use resources::*;
+
+pub struct ReadCallbackHandlerSystem;
+
+#[derive(SystemData)]
+pub struct ReadCallbackHandlerSystemData<'a> {
+    readable: ReadStorage<'a, Readable>,
+    lore: Read<'a, LoreDatabase>,
+    codex: Write<'a, Codex>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+impl<'a> System<'a> for ReadCallbackHandlerSystem {
+    type SystemData = ReadCallbackHandlerSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        let read_callbacks = data.callbacks.take_some(|cb| match cb {
+            Callback::Read(rcb) => TakeDecision::Take(rcb),
+            x => TakeDecision::Leave(x),
+        });
+
+        for rcb in read_callbacks {
+            handle_read_callback(rcb, &data.readable, &data.lore, &mut data.codex, &mut data.callbacks);
+        }
+    }
+}
+
+fn handle_read_callback(
+    rcb: ReadCallback,
+    readable: &ReadStorage<'_, Readable>,
+    lore: &LoreDatabase,
+    codex: &mut Codex,
+    callbacks: &mut Callbacks,
+) {
+    match rcb {
+        ReadCallback::ChooseReadTarget { entity } => {
+            let readable = readable
+                .get(entity)
+                .expect("If we started reading an entity, it better be readable");
+
+            let text = match lore.get(&readable.lore_id) {
+                Some(entry) => {
+                    let mut learned_something = false;
+                    for learned in entry.codex_entries() {
+                        learned_something |= codex.learn(learned);
+                    }
+
+                    let text = format!("{}\n\n{}", entry.title, entry.text);
+                    if learned_something {
+                        format!("{}\n\nYou make a note of this in your journal.", text)
+                    } else {
+                        text
+                    }
+                }
+                // a save from a build with different lore can point at an entry that's gone
+                None => "Whatever was written here is too faded to make out.".to_string(),
+            };
+
+            let builder = DialogueBuilder::new(&text).with_option("[Done]", vec![Callback::EndDialogue]);
+
+            launch_dialogue(builder, callbacks);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use super::*;
+
+use super::dialogue_helpers::{launch_dialogue, DialogueBuilder};
+
+use components::*;
+use resources::*;
+use skills::SkillTree;
+
+pub struct RepairCallbackHandlerSystem;
+
+#[derive(SystemData)]
+pub struct RepairCallbackHandlerSystemData<'a> {
+    ongoing_action: Write<'a, OngoingPlayerAction>,
+    player: ReadStorage<'a, Player>,
+    has_position: ReadStorage<'a, HasPosition>,
+    spare_parts: ReadStorage<'a, SpareParts>,
+    repairable: ReadStorage<'a, Repairable>,
+    hackable: ReadStorage<'a, Hackable>,
+    skills: Read<'a, SkillTree>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+impl<'a> System<'a> for RepairCallbackHandlerSystem {
+    type SystemData = RepairCallbackHandlerSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        let repair_callbacks = data.callbacks.take_some(|cb| match cb {
+            Callback::Repair(rcb) => TakeDecision::Take(rcb),
+            x => TakeDecision::Leave(x),
+        });
+
+        for rcb in repair_callbacks {
+            match rcb {
+                // whatever was chosen might have moved off (or been destroyed) in the meantime
+                _ if !within_reach(&data, &rcb) => launch_out_of_reach_dialogue(&mut data.callbacks),
+                RepairCallback::InitiateRepair { target, turn_duration } => {
+                    data.ongoing_action.current = Some(OngoingAction::new(OngoingActionKind::Repair { target }, turn_duration));
+                }
+                RepairCallback::ChooseRepairTarget { entity } => {
+                    choose_repair_target(&mut data, entity);
+                }
+            }
+        }
+    }
+}
+
+fn within_reach(data: &RepairCallbackHandlerSystemData, rcb: &RepairCallback) -> bool {
+    let target = match *rcb {
+        RepairCallback::InitiateRepair { target, .. } => target,
+        RepairCallback::ChooseRepairTarget { entity } => entity,
+    };
+
+    let player_pos = (&data.player, &data.has_position).join().map(|(_, hp)| hp.position).next();
+    let target_pos = data.has_position.get(target).map(|hp| hp.position);
+
+    match (player_pos, target_pos) {
+        (Some(player_pos), Some(target_pos)) => data.repairable.contains(target) && direct_neighbors(player_pos).contains(&target_pos),
+        _ => false,
+    }
+}
+
+fn launch_out_of_reach_dialogue(callbacks: &mut Callbacks) {
+    let builder = DialogueBuilder::new("That's out of reach now.").with_option("[Continue]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, callbacks);
+}
+
+fn choose_repair_target(data: &mut RepairCallbackHandlerSystemData, entity: Entity) {
+    let repairable = data
+        .repairable
+        .get(entity)
+        .expect("If we chose to repair an entity, it better be repairable");
+
+    let name = data.hackable.get(entity).map(|h| h.name.as_str()).unwrap_or("machine");
+
+    let parts = (&data.player, &data.spare_parts)
+        .join()
+        .map(|(_, spare_parts)| spare_parts.count)
+        .next()
+        .unwrap_or(0);
+
+    let skill_level = repair_helpers::repair_level(&data.skills);
+
+    let mut builder = match repair_helpers::check_repair(repairable, skill_level, parts) {
+        Ok(()) => {
+            let turn_duration = repair_helpers::repair_duration(repairable, skill_level);
+
+            DialogueBuilder::new(&format!(
+                "Repairing the {}... ({} turns, {} of your {} spare parts)",
+                name, turn_duration, repairable.parts, parts
+            ))
+            .with_option(
+                "[Repair]",
+                vec![
+                    Callback::Repair(RepairCallback::InitiateRepair {
+                        target: entity,
+                        turn_duration,
+                    }),
+                    Callback::EndDialogue,
+                ],
+            )
+        }
+        Err(blocker) => DialogueBuilder::new(&repair_helpers::blocker_text(blocker)),
+    };
+
+    builder = builder.with_option("[Cancel]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, &mut data.callbacks);
+}
@@ -2,6 +2,7 @@ use super::*;
 
 use super::dialogue_helpers::{launch_dialogue, DialogueBuilder};
 
+use codex::Codex;
 use components::*;
 use resources::*;
 
@@ -10,6 +11,7 @@ pub struct TalkCallbackHandlerSystem;
 #[derive(SystemData)]
 pub struct TalkCallbackHandlerSystemData<'a> {
     talkable: ReadStorage<'a, Talkable>,
+    codex: Read<'a, Codex>,
     callbacks: Write<'a, Callbacks>,
 }
 
@@ -23,12 +25,12 @@ impl<'a> System<'a> for TalkCallbackHandlerSystem {
         });
 
         for tcb in talk_callbacks {
-            handle_talk_callback(tcb, &mut data.talkable, &mut data.callbacks);
+            handle_talk_callback(tcb, &mut data.talkable, &data.codex, &mut data.callbacks);
         }
     }
 }
 
-fn handle_talk_callback(tcb: TalkCallback, talkable: &mut ReadStorage<'_, Talkable>, callbacks: &mut Callbacks) {
+fn handle_talk_callback(tcb: TalkCallback, talkable: &mut ReadStorage<'_, Talkable>, codex: &Codex, callbacks: &mut Callbacks) {
     match tcb {
         TalkCallback::ChooseTalkTarget { entity } => {
             // just getting it to make sure it exists
@@ -39,8 +41,18 @@ fn handle_talk_callback(tcb: TalkCallback, talkable: &mut ReadStorage<'_, Talkab
             // TODO: obviously at some point we want a whole sophisticated dialogue tree structure here
             let mut builder = DialogueBuilder::new("The creature faces you and makes sounds and gestures you cannot understand");
 
+            let recognized = DialogueBuilder::new("It goes still at the word, then slowly repeats it back to you. It seems less afraid.")
+                .with_option("[Continue]", vec![Callback::EndDialogue])
+                .build();
+
             builder = builder
                 .with_option("[Try to imitate its language]", vec![])
+                .with_option_if_known(
+                    codex,
+                    constants::codex::ALIEN_NAME,
+                    "[Say \"Vaeli\"]",
+                    vec![Callback::EndDialogue, Callback::StartDialogue(recognized)],
+                )
                 .with_option("[Give up]", vec![Callback::EndDialogue]);
 
             launch_dialogue(builder, callbacks);
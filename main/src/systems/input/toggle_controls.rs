@@ -45,7 +45,7 @@ impl<'a> System<'a> for ToggleControlSystem {
         if keyboard[Key::C] == ButtonState::Pressed {
             display_options.display_controls_pane = !display_options.display_controls_pane;
         } else if keyboard[Key::O] == ButtonState::Pressed {
-            display_options.show_oxygen_overlay = !display_options.show_oxygen_overlay;
+            display_options.cycle_overlay();
         } else if keyboard[Key::Q] == ButtonState::Pressed {
             launch_quit_dialogue(callbacks);
         } else if keyboard[Key::S] == ButtonState::Pressed {
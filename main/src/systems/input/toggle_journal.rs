@@ -0,0 +1,33 @@
+//! System which opens the journal, only when the "game map" is the active panel
+
+use super::*;
+
+use resources::*;
+
+#[derive(SystemData)]
+pub struct ToggleJournalSystemData<'a> {
+    keyboard: ReadExpect<'a, Keyboard>,
+    npc_moves: Read<'a, NpcMoves>,
+    keyboard_focus: Read<'a, KeyboardFocus>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+pub struct ToggleJournalSystem;
+
+impl<'a> System<'a> for ToggleJournalSystem {
+    type SystemData = ToggleJournalSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        if *data.keyboard_focus != KeyboardFocus::GameMap {
+            return;
+        }
+
+        if !data.npc_moves.player_can_move() {
+            return;
+        }
+
+        if data.keyboard[Key::J] == ButtonState::Pressed {
+            data.callbacks.push(Callback::Journal(JournalCallback::Contents));
+        }
+    }
+}
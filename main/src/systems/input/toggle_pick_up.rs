@@ -0,0 +1,107 @@
+//! System which receives player input, only when the "game map" is the active panel
+
+use super::*;
+
+use dialogue_helpers::{launch_dialogue, DialogueBuilder};
+
+use super::directions::{get_direction, is_neighbor, to_string, Direction};
+
+use components::*;
+use resources::*;
+
+#[derive(SystemData)]
+pub struct TogglePickUpSystemData<'a> {
+    player: ReadStorage<'a, Player>,
+    has_position: ReadStorage<'a, HasPosition>,
+    item: ReadStorage<'a, Item>,
+    corpse: ReadStorage<'a, Corpse>,
+    entities: Entities<'a>,
+
+    keyboard: ReadExpect<'a, Keyboard>,
+    npc_moves: Read<'a, NpcMoves>,
+    keyboard_focus: Read<'a, KeyboardFocus>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+pub struct TogglePickUpSystem;
+
+impl<'a> System<'a> for TogglePickUpSystem {
+    type SystemData = TogglePickUpSystemData<'a>;
+
+    fn run(&mut self, data: Self::SystemData) {
+        if *data.keyboard_focus != KeyboardFocus::GameMap {
+            return;
+        }
+
+        if !data.npc_moves.player_can_move() {
+            return;
+        }
+
+        if data.keyboard[Key::G] == ButtonState::Pressed {
+            launch_pick_up(data);
+        }
+    }
+}
+
+fn launch_pick_up(mut data: TogglePickUpSystemData<'_>) {
+    let player_pos = (&data.player, &data.has_position)
+        .join()
+        .map(|(_, has_pos)| has_pos.position)
+        .next()
+        .expect("There should be a player with a position");
+
+    let neighbor_positions = direct_neighbors(player_pos);
+
+    let items: Vec<(String, Direction, Entity)> = (&data.item, &data.has_position, &data.entities)
+        .join()
+        .filter(|(_, hp, _)| is_neighbor(hp.position, &neighbor_positions))
+        .map(|(item, hp, entity)| (item.name.clone(), get_direction(player_pos, hp.position), entity))
+        .collect();
+
+    let corpses: Vec<(String, Direction, Entity)> = (&data.corpse, &data.has_position, &data.entities)
+        .join()
+        .filter(|(_, hp, _)| is_neighbor(hp.position, &neighbor_positions))
+        .map(|(corpse, hp, entity)| (corpse.name.clone(), get_direction(player_pos, hp.position), entity))
+        .collect();
+
+    if items.is_empty() && corpses.is_empty() {
+        launch_nothing_here_dialogue(&mut data.callbacks);
+    } else {
+        choose_pick_up_target_dialogue(items, corpses, &mut data.callbacks);
+    }
+}
+
+fn launch_nothing_here_dialogue(callbacks: &mut Callbacks) {
+    let builder =
+        DialogueBuilder::new("There is nothing nearby to pick up or search.").with_option("[Continue]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, callbacks);
+}
+
+fn choose_pick_up_target_dialogue(
+    items: Vec<(String, Direction, Entity)>,
+    corpses: Vec<(String, Direction, Entity)>,
+    callbacks: &mut Callbacks,
+) {
+    let mut builder = DialogueBuilder::new("What do you want to pick up or search?");
+
+    for (name, dir, item) in items {
+        let name = format!("[{} ({})]", name, to_string(dir));
+        builder = builder.with_option(
+            &name,
+            vec![Callback::EndDialogue, Callback::Inventory(InventoryCallback::PickUp { item })],
+        );
+    }
+
+    for (name, dir, corpse) in corpses {
+        let name = format!("[Search the dead {} ({})]", name, to_string(dir));
+        builder = builder.with_option(
+            &name,
+            vec![Callback::EndDialogue, Callback::Inventory(InventoryCallback::Search { corpse })],
+        );
+    }
+
+    builder = builder.with_option("[Cancel]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, callbacks);
+}
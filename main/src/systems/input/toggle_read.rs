@@ -0,0 +1,96 @@
+//! System which receives player input, only when the "game map" is the active panel
+
+use super::*;
+
+use dialogue_helpers::{launch_dialogue, DialogueBuilder};
+
+use super::directions::{get_direction, is_neighbor, to_string, Direction};
+
+use components::*;
+use lore::LoreDatabase;
+use resources::*;
+
+#[derive(SystemData)]
+pub struct ToggleReadSystemData<'a> {
+    player: ReadStorage<'a, Player>,
+    has_position: ReadStorage<'a, HasPosition>,
+    readables: ReadStorage<'a, Readable>,
+    entities: Entities<'a>,
+
+    lore: Read<'a, LoreDatabase>,
+    keyboard: ReadExpect<'a, Keyboard>,
+    npc_moves: Read<'a, NpcMoves>,
+    keyboard_focus: Read<'a, KeyboardFocus>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+pub struct ToggleReadSystem;
+
+impl<'a> System<'a> for ToggleReadSystem {
+    type SystemData = ToggleReadSystemData<'a>;
+
+    fn run(&mut self, data: Self::SystemData) {
+        if *data.keyboard_focus != KeyboardFocus::GameMap {
+            return;
+        }
+
+        if !data.npc_moves.player_can_move() {
+            return;
+        }
+
+        if data.keyboard[Key::E] == ButtonState::Pressed {
+            launch_read(data);
+        }
+    }
+}
+
+fn launch_read(mut data: ToggleReadSystemData<'_>) {
+    let player_pos = (&data.player, &data.has_position)
+        .join()
+        .map(|(_, has_pos)| has_pos.position)
+        .next()
+        .expect("There should be a player with a position");
+
+    let neighbor_positions = direct_neighbors(player_pos);
+
+    let lore = &data.lore;
+    let readables: Vec<(String, Direction, Entity)> = (&data.readables, &data.has_position, &data.entities)
+        .join()
+        .filter(|(_, hp, _)| is_neighbor(hp.position, &neighbor_positions))
+        .map(|(readable, hp, entity)| {
+            let title = lore
+                .get(&readable.lore_id)
+                .map(|entry| entry.title.clone())
+                .unwrap_or_else(|| "Something illegible".to_string());
+            (title, get_direction(player_pos, hp.position), entity)
+        })
+        .collect();
+
+    if readables.is_empty() {
+        launch_nothing_to_read_dialogue(&mut data.callbacks);
+    } else {
+        choose_read_target_dialogue(readables, &mut data.callbacks);
+    }
+}
+
+fn launch_nothing_to_read_dialogue(callbacks: &mut Callbacks) {
+    let builder = DialogueBuilder::new("There is nothing nearby to read.").with_option("[Continue]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, callbacks);
+}
+
+fn choose_read_target_dialogue(readables: Vec<(String, Direction, Entity)>, callbacks: &mut Callbacks) {
+    let mut builder = DialogueBuilder::new("What do you want to read?");
+
+    for (title, dir, entity) in readables {
+        let name = format!("[{} ({})]", title, to_string(dir));
+        builder = builder.with_option(
+            &name,
+            vec![Callback::EndDialogue, Callback::Read(ReadCallback::ChooseReadTarget { entity })],
+        );
+    }
+
+    builder = builder.with_option("[Cancel]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, callbacks);
+}
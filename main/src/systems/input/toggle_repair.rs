@@ -0,0 +1,100 @@
+//! System which receives player input, only when the "game map" is the active panel
+
+use super::*;
+
+use dialogue_helpers::{launch_dialogue, DialogueBuilder};
+
+use super::directions::{get_direction, is_neighbor, to_string, Direction};
+
+use components::*;
+use resources::*;
+
+#[derive(SystemData)]
+pub struct ToggleRepairSystemData<'a> {
+    player: ReadStorage<'a, Player>,
+    has_position: ReadStorage<'a, HasPosition>,
+    broken: ReadStorage<'a, Broken>,
+    repairable: ReadStorage<'a, Repairable>,
+    hackables: ReadStorage<'a, Hackable>,
+    entities: Entities<'a>,
+
+    keyboard: ReadExpect<'a, Keyboard>,
+    npc_moves: Read<'a, NpcMoves>,
+    keyboard_focus: Read<'a, KeyboardFocus>,
+    callbacks: Write<'a, Callbacks>,
+}
+
+pub struct ToggleRepairSystem;
+
+impl<'a> System<'a> for ToggleRepairSystem {
+    type SystemData = ToggleRepairSystemData<'a>;
+
+    fn run(&mut self, data: Self::SystemData) {
+        if *data.keyboard_focus != KeyboardFocus::GameMap {
+            return;
+        }
+
+        if !data.npc_moves.player_can_move() {
+            return;
+        }
+
+        if data.keyboard[Key::R] == ButtonState::Pressed {
+            launch_repair(data);
+        }
+    }
+}
+
+fn launch_repair(mut data: ToggleRepairSystemData<'_>) {
+    let player_pos = (&data.player, &data.has_position)
+        .join()
+        .map(|(_, has_pos)| has_pos.position)
+        .next()
+        .expect("There should be a player with a position");
+
+    let neighbor_positions = direct_neighbors(player_pos);
+
+    let repairables: Vec<(String, Direction, Entity)> = (&data.broken, &data.repairable, &data.has_position, &data.entities)
+        .join()
+        .filter(|(_, _, hp, _)| is_neighbor(hp.position, &neighbor_positions))
+        .map(|(_, _, hp, entity)| {
+            let name = data
+                .hackables
+                .get(entity)
+                .map(|h| h.name.clone())
+                .unwrap_or_else(|| "Machine".to_string());
+            (name, get_direction(player_pos, hp.position), entity)
+        })
+        .collect();
+
+    if repairables.is_empty() {
+        launch_no_repairs_dialogue(&mut data.callbacks);
+    } else {
+        choose_repair_target_dialogue(repairables, &mut data.callbacks);
+    }
+}
+
+fn launch_no_repairs_dialogue(callbacks: &mut Callbacks) {
+    let builder =
+        DialogueBuilder::new("There is nothing nearby that needs repairing.").with_option("[Continue]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, callbacks);
+}
+
+fn choose_repair_target_dialogue(repairables: Vec<(String, Direction, Entity)>, callbacks: &mut Callbacks) {
+    let mut builder = DialogueBuilder::new("What do you want to repair?");
+
+    for (name, dir, entity) in repairables {
+        let name = format!("[{} ({})]", name, to_string(dir));
+        builder = builder.with_option(
+            &name,
+            vec![
+                Callback::EndDialogue,
+                Callback::Repair(RepairCallback::ChooseRepairTarget { entity }),
+            ],
+        );
+    }
+
+    builder = builder.with_option("[Cancel]", vec![Callback::EndDialogue]);
+
+    launch_dialogue(builder, callbacks);
+}
@@ -4,6 +4,8 @@ use super::*;
 
 use dialogue_helpers::{launch_dialogue, DialogueBuilder};
 
+use super::directions::{get_direction, is_neighbor, to_string, Direction};
+
 use components::*;
 use resources::*;
 
@@ -63,49 +65,12 @@ fn launch_talk(mut data: ToggleTalkSystemData<'_>) {
     }
 }
 
-fn is_neighbor(pos: TilePos, neighbor_positions: &[TilePos]) -> bool {
-    neighbor_positions.iter().any(|&np| np == pos)
-}
-
 fn launch_no_talks_dialogue(callbacks: &mut Callbacks) {
     let builder = DialogueBuilder::new("There is no one nearby to talk to.").with_option("[Continue]", vec![Callback::EndDialogue]);
 
     launch_dialogue(builder, callbacks);
 }
 
-fn get_direction(my_pos: TilePos, other_pos: TilePos) -> Direction {
-    if my_pos.y < other_pos.y {
-        Direction::South
-    } else if my_pos.y > other_pos.y {
-        Direction::North
-    } else if my_pos.x < other_pos.x {
-        Direction::East
-    } else if my_pos.x > other_pos.x {
-        Direction::West
-    } else {
-        Direction::Here
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-    Here,
-}
-
-fn to_string(dir: Direction) -> &'static str {
-    match dir {
-        Direction::South => "South",
-        Direction::East => "East",
-        Direction::North => "North",
-        Direction::West => "West",
-        Direction::Here => "Here",
-    }
-}
-
 fn talkable_name(talkable: &Talkable, dir: Direction) -> String {
     let dir_string = to_string(dir);
 
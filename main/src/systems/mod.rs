@@ -4,8 +4,19 @@ use specs::{Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
 
 use world::TilePos;
 
+mod action_helpers;
+mod ai_helpers;
 mod camera_helpers;
+mod death_helpers;
 mod dialogue_helpers;
+mod diffusion_helpers;
+mod door_helpers;
+mod equipment_helpers;
+mod fov_helpers;
+mod hack_helpers;
+mod path_helpers;
+mod power_helpers;
+mod repair_helpers;
 mod turn_state_helpers;
 
 mod input;
@@ -0,0 +1,367 @@
+//! Route planning over the station's tiles, for anything which needs to get somewhere: NPCs going
+//! about their business, or the player travelling somewhere they've already been
+
+use super::*;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use components::*;
+use resources::{CachedPath, PathCache};
+
+use door_helpers::DoorOpener;
+
+/// Whatever is looking for a route; doors open for some things and not others, and bad air only
+/// matters to things that breathe
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PathMover {
+    pub opener: DoorOpener,
+    pub breathes: bool,
+}
+
+/// What it costs to step onto each tile; tiles which aren't in here can't be stepped onto at all
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PathGrid {
+    costs: HashMap<TilePos, usize>,
+}
+
+impl PathGrid {
+    pub fn set_cost(&mut self, pos: TilePos, cost: usize) {
+        self.costs.insert(pos, cost);
+    }
+
+    pub fn block(&mut self, pos: TilePos) {
+        self.costs.remove(&pos);
+    }
+
+    pub fn cost(&self, pos: TilePos) -> Option<usize> {
+        self.costs.get(&pos).copied()
+    }
+
+    pub fn is_passable(&self, pos: TilePos) -> bool {
+        self.costs.contains_key(&pos)
+    }
+}
+
+/// Everything (besides positions) which decides where something can walk, and how much it wants to
+#[derive(SystemData)]
+pub struct PathGridData<'a> {
+    oxygen_container: ReadStorage<'a, OxygenContainer>,
+    blocks_movement: ReadStorage<'a, BlocksMovement>,
+    burning: ReadStorage<'a, Burning>,
+    door: ReadStorage<'a, Door>,
+    broken: ReadStorage<'a, Broken>,
+    power_consumer: ReadStorage<'a, PowerConsumer>,
+}
+
+/// The grid as the mover sees it; positions are passed in separately, so that systems which move
+/// things around can plan routes too. Only tiles of the station itself can be walked on; open
+/// space is left out entirely.
+pub fn path_grid(positions: &[(Entity, TilePos)], data: &PathGridData, mover: PathMover) -> PathGrid {
+    use constants::oxygen::{SLOW_DROP_THRESHOLD, SLOW_GAIN_THRESHOLD};
+    use constants::pathing::*;
+
+    let mut grid = PathGrid::default();
+
+    for &(entity, pos) in positions {
+        if let Some(container) = data.oxygen_container.get(entity) {
+            let mut cost = STEP_COST;
+
+            if mover.breathes {
+                let oxygen = container.contents.oxygen;
+                if oxygen < SLOW_DROP_THRESHOLD {
+                    cost += NO_AIR_COST;
+                } else if oxygen < SLOW_GAIN_THRESHOLD {
+                    cost += THIN_AIR_COST;
+                }
+            }
+
+            grid.set_cost(pos, cost);
+        }
+    }
+
+    for &(entity, pos) in positions {
+        if let (true, Some(cost)) = (data.burning.contains(entity), grid.cost(pos)) {
+            grid.set_cost(pos, cost + FIRE_COST);
+        }
+    }
+
+    for &(entity, pos) in positions {
+        if !data.blocks_movement.contains(entity) {
+            continue;
+        }
+
+        let opens = data.door.get(entity).map(|door| {
+            door_helpers::opens_for(
+                door,
+                power_helpers::is_powered(data.power_consumer.get(entity)),
+                data.broken.contains(entity),
+                mover.opener,
+            )
+        });
+
+        if opens != Some(true) {
+            grid.block(pos);
+        }
+    }
+
+    grid
+}
+
+fn distance(a: TilePos, b: TilePos) -> usize {
+    ((a.x - b.x).abs() + (a.y - b.y).abs()) as usize
+}
+
+/// The cheapest route from start to goal, as the steps to take (so not including the start); the
+/// start itself doesn't have to be passable, since something is usually standing on it. Ties are
+/// broken the same way every time, so the same grid always gives the same route.
+pub fn find_path(grid: &PathGrid, start: TilePos, goal: TilePos) -> Option<Vec<TilePos>> {
+    if start == goal {
+        return Some(Vec::new());
+    }
+
+    if !grid.is_passable(goal) {
+        return None;
+    }
+
+    // every step costs at least this much, which keeps the distance heuristic admissible
+    let step = constants::pathing::STEP_COST;
+
+    let mut best_cost: HashMap<TilePos, usize> = HashMap::new();
+    let mut came_from: HashMap<TilePos, TilePos> = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+
+    best_cost.insert(start, 0);
+    frontier.push(Reverse((distance(start, goal) * step, 0, start.y, start.x)));
+
+    while let Some(Reverse((_, cost_so_far, y, x))) = frontier.pop() {
+        let pos = TilePos { x, y };
+
+        if pos == goal {
+            let mut path = vec![goal];
+            let mut at = goal;
+            while let Some(&prev) = came_from.get(&at) {
+                if prev == start {
+                    break;
+                }
+                path.push(prev);
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        // a cheaper way here was already found
+        if best_cost.get(&pos).map(|&best| best < cost_so_far).unwrap_or(false) {
+            continue;
+        }
+
+        for &next in direct_neighbors(pos)[1..].iter() {
+            let next_cost = match grid.cost(next) {
+                Some(cost) => cost_so_far + cost,
+                None => continue,
+            };
+
+            if best_cost.get(&next).map(|&best| best <= next_cost).unwrap_or(false) {
+                continue;
+            }
+
+            best_cost.insert(next, next_cost);
+            came_from.insert(next, pos);
+            frontier.push(Reverse((next_cost + distance(next, goal) * step, next_cost, next.y, next.x)));
+        }
+    }
+
+    None
+}
+
+/// Like find_path, but reuses whatever route was planned for the mover last time, so long as it's
+/// headed to the same place, the mover is still on it, it isn't too old, and nothing has moved in
+/// the way since
+pub fn cached_path(
+    cache: &mut PathCache,
+    mover: Entity,
+    start: TilePos,
+    goal: TilePos,
+    grid: &PathGrid,
+    turn: u64,
+) -> Option<Vec<TilePos>> {
+    if let Some(cached) = cache.paths.get(&mover) {
+        let fresh = turn.saturating_sub(cached.planned_turn) <= constants::pathing::MAX_PATH_AGE_TURNS;
+
+        if fresh && cached.goal == goal {
+            if let Some(at) = cached.route.iter().position(|&pos| pos == start) {
+                let remaining = &cached.route[at + 1..];
+                if remaining.iter().all(|&pos| grid.is_passable(pos)) {
+                    return Some(remaining.to_vec());
+                }
+            }
+        }
+    }
+
+    match find_path(grid, start, goal) {
+        Some(steps) => {
+            let mut route = Vec::with_capacity(steps.len() + 1);
+            route.push(start);
+            route.extend(steps.iter().copied());
+
+            cache.paths.insert(
+                mover,
+                CachedPath {
+                    goal,
+                    route,
+                    planned_turn: turn,
+                },
+            );
+
+            Some(steps)
+        }
+        None => {
+            cache.paths.remove(&mover);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // '#' is a wall, '~' is thin air, '!' is fire and anything else is ordinary floor
+    fn grid_from_rows(rows: &[&str]) -> PathGrid {
+        use constants::pathing::*;
+
+        let mut grid = PathGrid::default();
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let pos = TilePos { x: x as i32, y: y as i32 };
+                match c {
+                    '#' => {}
+                    '~' => grid.set_cost(pos, STEP_COST + THIN_AIR_COST),
+                    '!' => grid.set_cost(pos, STEP_COST + FIRE_COST),
+                    _ => grid.set_cost(pos, STEP_COST),
+                }
+            }
+        }
+
+        grid
+    }
+
+    fn pos(x: i32, y: i32) -> TilePos {
+        TilePos { x, y }
+    }
+
+    #[test]
+    fn straight_line_test() {
+        let grid = grid_from_rows(&["....."]);
+
+        let path = find_path(&grid, pos(0, 0), pos(4, 0));
+
+        assert_eq!(Some(vec![pos(1, 0), pos(2, 0), pos(3, 0), pos(4, 0)]), path);
+    }
+
+    #[test]
+    fn already_there_test() {
+        let grid = grid_from_rows(&["."]);
+
+        assert_eq!(Some(vec![]), find_path(&grid, pos(0, 0), pos(0, 0)));
+    }
+
+    #[test]
+    fn around_wall_test() {
+        let grid = grid_from_rows(&[
+            "...", //
+            ".#.", //
+            ".#.", //
+        ]);
+
+        let path = find_path(&grid, pos(0, 2), pos(2, 2)).expect("Should be a way around");
+
+        assert_eq!(6, path.len());
+        assert!(path.iter().all(|&p| grid.is_passable(p)));
+        assert_eq!(Some(&pos(2, 2)), path.last());
+    }
+
+    #[test]
+    fn no_way_through_test() {
+        let grid = grid_from_rows(&[
+            ".#.", //
+            ".#.", //
+        ]);
+
+        assert_eq!(None, find_path(&grid, pos(0, 0), pos(2, 0)));
+        assert_eq!(None, find_path(&grid, pos(0, 0), pos(1, 0)));
+    }
+
+    #[test]
+    fn avoids_danger_test() {
+        let grid = grid_from_rows(&[
+            "..!..", //
+            ".....", //
+            ".~~~.", //
+        ]);
+
+        let path = find_path(&grid, pos(0, 0), pos(4, 0)).expect("Should be a way");
+
+        // going round the fire is cheaper than going through it, and the middle row has the air
+        assert!(!path.contains(&pos(2, 0)));
+        assert!(path.contains(&pos(2, 1)));
+    }
+
+    #[test]
+    fn deterministic_test() {
+        let grid = grid_from_rows(&[
+            "....", //
+            "....", //
+            "....", //
+        ]);
+
+        let first = find_path(&grid, pos(0, 0), pos(3, 2));
+        for _ in 0..10 {
+            assert_eq!(first, find_path(&grid, pos(0, 0), pos(3, 2)));
+        }
+    }
+
+    #[test]
+    fn cache_test() {
+        let mut specs_world = World::new();
+        let mover = specs_world.create_entity().build();
+
+        let mut grid = grid_from_rows(&["....."]);
+        let mut cache = PathCache::default();
+
+        let first = cached_path(&mut cache, mover, pos(0, 0), pos(4, 0), &grid, 0);
+        assert_eq!(Some(vec![pos(1, 0), pos(2, 0), pos(3, 0), pos(4, 0)]), first);
+
+        // a step along, the rest of the same route comes back
+        let second = cached_path(&mut cache, mover, pos(1, 0), pos(4, 0), &grid, 1);
+        assert_eq!(Some(vec![pos(2, 0), pos(3, 0), pos(4, 0)]), second);
+        assert_eq!(0, cache.paths[&mover].planned_turn);
+
+        // something in the way means planning again, which finds nothing here
+        grid.block(pos(3, 0));
+        assert_eq!(None, cached_path(&mut cache, mover, pos(1, 0), pos(4, 0), &grid, 2));
+        assert!(!cache.paths.contains_key(&mover));
+    }
+
+    #[test]
+    fn stale_cache_test() {
+        let mut specs_world = World::new();
+        let mover = specs_world.create_entity().build();
+
+        let grid = grid_from_rows(&["....."]);
+        let mut cache = PathCache::default();
+
+        cached_path(&mut cache, mover, pos(0, 0), pos(4, 0), &grid, 0);
+
+        // still fresh at the limit, replanned just after it
+        let limit = constants::pathing::MAX_PATH_AGE_TURNS;
+        cached_path(&mut cache, mover, pos(1, 0), pos(4, 0), &grid, limit);
+        assert_eq!(0, cache.paths[&mover].planned_turn);
+
+        cached_path(&mut cache, mover, pos(2, 0), pos(4, 0), &grid, limit + 1);
+        assert_eq!(limit + 1, cache.paths[&mover].planned_turn);
+        assert_eq!(pos(2, 0), cache.paths[&mover].route[0]);
+    }
+}
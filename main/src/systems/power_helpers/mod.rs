@@ -0,0 +1,148 @@
+//! Pure logic for the power grid; which conduits are connected to each other, and which machines
+//! get power when there isn't enough to go around.
+
+use super::*;
+
+use std::collections::{HashMap, HashSet};
+
+use components::{PowerConsumer, PowerPriority};
+
+/// Whether a machine has the power to run; things which don't draw power always do
+pub fn is_powered(consumer: Option<&PowerConsumer>) -> bool {
+    consumer.map_or(true, |consumer| consumer.powered)
+}
+
+/// Splits the conduits into networks of (directly) adjacent conduits; returns the network
+/// number for each conduit position. Network numbers are arbitrary, but distinct.
+pub fn find_networks(conduits: &HashSet<TilePos>) -> HashMap<TilePos, usize> {
+    let mut networks = HashMap::new();
+    let mut next_network = 0;
+
+    for &start in conduits.iter() {
+        if networks.contains_key(&start) {
+            continue;
+        }
+
+        networks.insert(start, next_network);
+        let mut to_process = vec![start];
+
+        while let Some(pos) = to_process.pop() {
+            for &neighbor in direct_neighbors(pos).iter() {
+                if conduits.contains(&neighbor) && !networks.contains_key(&neighbor) {
+                    networks.insert(neighbor, next_network);
+                    to_process.push(neighbor);
+                }
+            }
+        }
+
+        next_network += 1;
+    }
+
+    networks
+}
+
+/// Given the supply on a network and the (priority, load) of everything drawing from it, decides
+/// what gets power; the result is in the same order as the demands.
+///
+/// Power goes out from the highest priority down (ties go to whatever came first); once
+/// something can't be powered, nothing of lower priority is either, so machines fail strictly
+/// in priority order.
+pub fn allocate_power(supply: usize, demands: &[(PowerPriority, usize)]) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..demands.len()).collect();
+    // stable, so ties keep their original order
+    order.sort_by(|&a, &b| demands[b].0.cmp(&demands[a].0));
+
+    let mut powered = vec![false; demands.len()];
+    let mut remaining = supply;
+
+    for ind in order {
+        let load = demands[ind].1;
+        if load > remaining {
+            break;
+        }
+        remaining -= load;
+        powered[ind] = true;
+    }
+
+    powered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use PowerPriority::*;
+
+    #[test]
+    fn find_networks_test() {
+        let conduits: HashSet<TilePos> = vec![
+            TilePos { x: 0, y: 0 },
+            TilePos { x: 1, y: 0 },
+            TilePos { x: 1, y: 1 },
+            // only diagonally adjacent, so not connected
+            TilePos { x: 2, y: 2 },
+            TilePos { x: 5, y: 5 },
+            TilePos { x: 5, y: 6 },
+        ]
+        .into_iter()
+        .collect();
+
+        let networks = find_networks(&conduits);
+
+        assert_eq!(networks.len(), conduits.len());
+
+        let network = |x, y| networks[&TilePos { x, y }];
+
+        assert_eq!(network(0, 0), network(1, 0));
+        assert_eq!(network(0, 0), network(1, 1));
+        assert_eq!(network(5, 5), network(5, 6));
+
+        assert_ne!(network(0, 0), network(2, 2));
+        assert_ne!(network(0, 0), network(5, 5));
+        assert_ne!(network(2, 2), network(5, 5));
+    }
+
+    #[test]
+    fn cut_conduit_test() {
+        let generator = TilePos { x: 0, y: 0 };
+        let consumer = TilePos { x: 4, y: 0 };
+
+        let mut conduits: HashSet<TilePos> = (0..=4).map(|x| TilePos { x, y: 0 }).collect();
+        let networks = find_networks(&conduits);
+        assert_eq!(networks[&generator], networks[&consumer]);
+
+        // take out one square of the run, and the two ends are on their own
+        conduits.remove(&TilePos { x: 2, y: 0 });
+        let networks = find_networks(&conduits);
+        assert_ne!(networks[&generator], networks[&consumer]);
+    }
+
+    #[test]
+    fn allocate_enough_power_test() {
+        let demands = vec![(Low, 3), (Critical, 4), (Normal, 2)];
+        assert_eq!(allocate_power(9, &demands), vec![true, true, true]);
+    }
+
+    #[test]
+    fn allocate_shortfall_test() {
+        let demands = vec![(Low, 3), (Critical, 4), (Normal, 2), (High, 1)];
+        // Critical and High fit, then Normal fits, then Low doesn't
+        assert_eq!(allocate_power(7, &demands), vec![false, true, true, true]);
+        // Critical and High fit, but Normal doesn't, so nothing below it does either
+        assert_eq!(allocate_power(6, &demands), vec![false, true, false, true]);
+        assert_eq!(allocate_power(0, &demands), vec![false, false, false, false]);
+    }
+
+    #[test]
+    fn allocate_fails_in_priority_order_test() {
+        // the low priority thing would fit in what's left over, but it doesn't get it
+        let demands = vec![(Low, 1), (High, 5)];
+        assert_eq!(allocate_power(4, &demands), vec![false, false]);
+    }
+
+    #[test]
+    fn allocate_ties_go_in_order_test() {
+        let demands = vec![(Normal, 2), (Normal, 2), (Normal, 2)];
+        assert_eq!(allocate_power(5, &demands), vec![true, true, false]);
+    }
+}
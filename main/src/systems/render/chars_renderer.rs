@@ -12,7 +12,7 @@ use quicksilver::{
 use specs::Join;
 
 use components::{Camera, CharRender, HasPosition, Visible};
-use resources::GameMapRenderParams;
+use resources::{GameMapRenderParams, LightMap};
 
 use world::{TilePos, VisibilityType};
 
@@ -25,6 +25,7 @@ pub struct CharsRendererSystemData<'a> {
     visible: ReadStorage<'a, Visible>,
     char_render: ReadStorage<'a, CharRender>,
     game_map_render_params: Read<'a, GameMapRenderParams>,
+    light_map: Read<'a, LightMap>,
 }
 
 pub struct CharsRendererSetup;
@@ -74,13 +75,14 @@ impl<'a, 'b> System<'a> for CharsRenderer<'b> {
                 // Then, paint them
                 for x in camera_bounds.x_min..=camera_bounds.x_max {
                     for y in camera_bounds.y_min..=camera_bounds.y_max {
+                        let light = data.light_map.level(TilePos { x, y });
+                        let render_pos = get_render_pos(x, y, camera_bounds, *data.game_map_render_params);
                         let draw_renderable = |renderable: Renderable, window: &mut Window| {
                             draw_drawable(
                                 renderable.visible.visibility,
+                                light,
                                 renderable.char_render,
-                                TilePos { x, y },
-                                camera_bounds,
-                                &data.game_map_render_params,
+                                render_pos,
                                 tileset,
                                 window,
                             );
@@ -104,10 +106,9 @@ impl<'a, 'b> System<'a> for CharsRenderer<'b> {
 
 fn draw_drawable(
     vis: VisibilityType,
+    light: f32,
     glyph_comp: &CharRender,
-    position: TilePos,
-    camera_bounds: CameraBounds,
-    render_params: &GameMapRenderParams,
+    render_pos: Vector,
     tileset: &HashMap<char, Image>,
     window: &mut Window,
 ) {
@@ -115,28 +116,47 @@ fn draw_drawable(
         return;
     }
 
-    let render_pos = get_render_pos(position.x, position.y, camera_bounds, *render_params);
     let image = tileset
         .get(&glyph_comp.glyph)
         .unwrap_or_else(|| panic!("Glyph {} should be defined in the tileset", glyph_comp.glyph));
 
     let rect = Rectangle::new(render_pos, image.area().size());
 
-    window.draw(&rect, Col(with_vis(glyph_comp.bg_color, vis)));
-    window.draw(&rect, Blended(&image, with_vis(glyph_comp.fg_color, vis)));
+    window.draw(&rect, Col(with_vis(glyph_comp.bg_color, vis, light)));
+    window.draw(&rect, Blended(&image, with_vis(glyph_comp.fg_color, vis, light)));
 }
 
 fn get_render_pos(x: i32, y: i32, bounds: CameraBounds, render_params: GameMapRenderParams) -> Vector {
     Vector::new(x - bounds.x_min, y - bounds.y_min).times(render_params.tile_size_px) + render_params.map_offset
 }
 
-fn with_vis(mut color: Color, visibility: VisibilityType) -> Color {
+fn with_vis(mut color: Color, visibility: VisibilityType, light: f32) -> Color {
     let alpha = match visibility {
         VisibilityType::NotSeen => 0.0,
         VisibilityType::Remembered => 0.3,
         VisibilityType::CurrentlyVisible => 1.0,
+        VisibilityType::SeenThroughFeed => 0.8,
     };
 
+    // Things you can currently see are darker the dimmer the light on them; remembered things are
+    // drawn as they were
+    if visibility == VisibilityType::CurrentlyVisible || visibility == VisibilityType::SeenThroughFeed {
+        use constants::lighting::MIN_SHADE;
+
+        let shade = MIN_SHADE + (1.0 - MIN_SHADE) * light.min(1.0);
+        color.r *= shade;
+        color.g *= shade;
+        color.b *= shade;
+    }
+
+    // Camera feeds are grainy green monochrome
+    if visibility == VisibilityType::SeenThroughFeed {
+        let brightness = 0.3 * color.r + 0.59 * color.g + 0.11 * color.b;
+        color.r = 0.4 * brightness;
+        color.g = brightness;
+        color.b = 0.5 * brightness;
+    }
+
     color.a *= alpha;
     color
 }
@@ -7,8 +7,10 @@ use std::collections::HashMap;
 
 use quicksilver::graphics::Image;
 
-use components::{Breathes, Hackable, HasPosition, Player, Talkable};
-use resources::{GameClock, GameMapDisplayOptions, GameMapRenderParams};
+use components::{Breathes, Broken, Corpse, Equipment, Hackable, HasPosition, Item, OxygenTank, Player, Readable, Talkable};
+use resources::{GameClock, GameMapDisplayOptions, GameMapRenderParams, OngoingPlayerAction};
+
+use action_helpers::progress_bar;
 
 use image_render_helper::{render_images_corner, Alignment, Corner};
 use numerics::force_max;
@@ -17,17 +19,26 @@ use numerics::force_max;
 pub struct ControlsRendererSystemData<'a> {
     player: ReadStorage<'a, Player>,
     breathes: ReadStorage<'a, Breathes>,
+    equipment: ReadStorage<'a, Equipment>,
+    oxygen_tank: ReadStorage<'a, OxygenTank>,
     game_clock: Read<'a, GameClock>,
+    ongoing_action: Read<'a, OngoingPlayerAction>,
 
     has_position: ReadStorage<'a, HasPosition>,
     hackable: ReadStorage<'a, Hackable>,
     talkable: ReadStorage<'a, Talkable>,
+    broken: ReadStorage<'a, Broken>,
+    item: ReadStorage<'a, Item>,
+    corpse: ReadStorage<'a, Corpse>,
+    readable: ReadStorage<'a, Readable>,
     entities: Entities<'a>,
 
     game_map_render_params: Read<'a, GameMapRenderParams>,
     game_map_display_options: Read<'a, GameMapDisplayOptions>,
 }
 
+const PROGRESS_BAR_WIDTH: usize = 10;
+
 /// Noop system for setup
 pub struct ControlsRendererSetup;
 
@@ -55,53 +66,76 @@ impl<'a, 'b> ControlsRenderer<'b> {
         let adj_positions = super::super::direct_neighbors(player_position);
 
         let window = &mut self.window;
-        let (hack, repair, talk) = (
+        let (hack, repair, talk, pick_up, read) = (
             &mut self.controls_image.hack,
             &mut self.controls_image.repair,
             &mut self.controls_image.talk,
+            &mut self.controls_image.pick_up,
+            &mut self.controls_image.read,
         );
 
         hack.execute(|hack| {
             repair.execute(|repair| {
                 talk.execute(|talk| {
-                    let mut is_hack = false;
-                    let mut is_talk = false;
-                    let is_repair = false;
-
-                    for (entity, hp) in (&data.entities, &data.has_position).join() {
-                        if adj_positions.iter().any(|ap| *ap == hp.position) {
-                            if data.hackable.contains(entity) {
-                                is_hack = true;
+                    pick_up.execute(|pick_up| {
+                        read.execute(|read| {
+                            let mut is_hack = false;
+                            let mut is_talk = false;
+                            let mut is_repair = false;
+                            let mut is_pick_up = false;
+                            let mut is_read = false;
+
+                            for (entity, hp) in (&data.entities, &data.has_position).join() {
+                                if adj_positions.iter().any(|ap| *ap == hp.position) {
+                                    if data.hackable.contains(entity) {
+                                        is_hack = true;
+                                    }
+                                    if data.talkable.contains(entity) {
+                                        is_talk = true;
+                                    }
+                                    if data.broken.contains(entity) {
+                                        is_repair = true;
+                                    }
+                                    if data.item.contains(entity) || data.corpse.contains(entity) {
+                                        is_pick_up = true;
+                                    }
+                                    if data.readable.contains(entity) {
+                                        is_read = true;
+                                    }
+                                }
+                            }
+
+                            let mut images: Vec<&Image> = Vec::new();
+                            if is_hack {
+                                images.push(&hack);
+                            }
+                            if is_talk {
+                                images.push(&talk);
                             }
-                            if data.talkable.contains(entity) {
-                                is_talk = true;
+                            if is_repair {
+                                images.push(&repair);
                             }
-                        }
-                    }
-
-                    let mut images: Vec<&Image> = Vec::new();
-                    if is_hack {
-                        images.push(&hack);
-                    }
-                    if is_talk {
-                        images.push(&talk);
-                    }
-                    if is_repair {
-                        images.push(&repair);
-                    }
-
-                    let offset = data.game_map_render_params.controls_image_offset_px;
-
-                    image_render_helper::render_images_corner(
-                        window,
-                        &images,
-                        offset,
-                        Vector::new(5.0, 5.0),
-                        Corner::UpperRight,
-                        Alignment::Vertical,
-                    );
-
-                    Ok(())
+                            if is_pick_up {
+                                images.push(pick_up);
+                            }
+                            if is_read {
+                                images.push(read);
+                            }
+
+                            let offset = data.game_map_render_params.controls_image_offset_px;
+
+                            image_render_helper::render_images_corner(
+                                window,
+                                &images,
+                                offset,
+                                Vector::new(5.0, 5.0),
+                                Corner::UpperRight,
+                                Alignment::Vertical,
+                            );
+
+                            Ok(())
+                        })
+                    })
                 })
             })
         })
@@ -167,14 +201,30 @@ impl<'a, 'b> System<'a> for ControlsRenderer<'b> {
                     return Ok(());
                 }
 
+                let mut meters = Vec::new();
+
                 let breathes = player_breathe.unwrap().1;
                 let (capacity, contents) = (breathes.capacity, breathes.contents);
-                if contents >= capacity {
+                if contents < capacity {
+                    let air_perc = 100.0 * ((contents as f32) / (capacity as f32));
+                    meters.push(format!("Oxygen: {:.0}%", air_perc));
+                }
+
+                // whatever is left in the tank the player has on, if any
+                let tank = (&data.player, &data.equipment)
+                    .join()
+                    .flat_map(|(_, equipment)| equipment.worn())
+                    .find_map(|item| data.oxygen_tank.get(item));
+                if let Some(tank) = tank {
+                    let tank_perc = 100.0 * ((tank.contents as f32) / (tank.capacity as f32));
+                    meters.push(format!("Tank: {:.0}%", tank_perc));
+                }
+
+                if meters.is_empty() {
                     return Ok(());
                 }
 
-                let air_perc = 100.0 * ((contents as f32) / (capacity as f32));
-                let air_meter_str = format!("Oxygen: {:.0}%", air_perc);
+                let air_meter_str = meters.join("   ");
 
                 let mut total_width = 0.0;
                 let mut max_height = 0.0;
@@ -206,5 +256,42 @@ impl<'a, 'b> System<'a> for ControlsRenderer<'b> {
                 Ok(())
             })
             .expect("Rendering O2 meter should work");
+
+        // Render progress of whatever the player is busy with
+        self.tileset
+            .execute(|tileset| {
+                let action = match data.ongoing_action.current.as_ref() {
+                    Some(action) => action,
+                    None => return Ok(()),
+                };
+
+                let progress_str = format!(
+                    "{}: {} {}",
+                    action.label(),
+                    progress_bar(action.elapsed_turns, action.total_turns, PROGRESS_BAR_WIDTH),
+                    action.total_turns - action.elapsed_turns
+                );
+
+                let to_render: Vec<&Image> = progress_str
+                    .chars()
+                    .map(|c| {
+                        tileset
+                            .get(&c)
+                            .unwrap_or_else(|| panic!("Should have defined a tileset item for {}", c))
+                    })
+                    .collect();
+
+                render_images_corner(
+                    window,
+                    &to_render,
+                    data.game_map_render_params.action_progress_offset_px,
+                    Vector::new(0, 0),
+                    Corner::UpperLeft,
+                    Alignment::Horizontal,
+                );
+
+                Ok(())
+            })
+            .expect("Rendering action progress should work");
     }
 }
@@ -0,0 +1,135 @@
+//! Render system for the inventory screen; draws over the game map, while the inventory has focus
+
+use super::*;
+
+use std::collections::HashMap;
+
+use quicksilver::{
+    geom::{Rectangle, Vector},
+    graphics::{Color, Image},
+};
+
+use components::{EquipSlot, Equipment, Equippable, Inventory, Item, Player};
+use resources::{GameMapRenderParams, InventoryScreen, KeyboardFocus};
+
+use image_render_helper::{render_images_corner, Alignment, Corner};
+
+#[derive(SystemData)]
+pub struct InventoryRendererSystemData<'a> {
+    player: ReadStorage<'a, Player>,
+    inventory: ReadStorage<'a, Inventory>,
+    item: ReadStorage<'a, Item>,
+    equipment: ReadStorage<'a, Equipment>,
+    equippable: ReadStorage<'a, Equippable>,
+
+    keyboard_focus: Read<'a, KeyboardFocus>,
+    inventory_screen: Read<'a, InventoryScreen>,
+    game_map_render_params: Read<'a, GameMapRenderParams>,
+}
+
+/// Noop system for setup
+pub struct InventoryRendererSetup;
+
+impl<'a> System<'a> for InventoryRendererSetup {
+    type SystemData = InventoryRendererSystemData<'a>;
+
+    fn run(&mut self, _data: Self::SystemData) {}
+}
+
+pub struct InventoryRenderer<'a> {
+    pub window: &'a mut Window,
+    pub tileset: &'a mut Asset<HashMap<char, Image>>,
+}
+
+impl<'a, 'b> System<'a> for InventoryRenderer<'b> {
+    type SystemData = InventoryRendererSystemData<'a>;
+
+    fn run(&mut self, data: Self::SystemData) {
+        if *data.keyboard_focus != KeyboardFocus::Inventory {
+            return;
+        }
+
+        let (inventory, equipment) = match (&data.player, &data.inventory, data.equipment.maybe()).join().next() {
+            Some((_, inventory, equipment)) => (inventory, equipment),
+            None => return,
+        };
+
+        let mut lines = vec![
+            format!("Inventory: {} of {}", inventory.items.len(), inventory.capacity),
+            String::new(),
+        ];
+
+        if inventory.items.is_empty() {
+            lines.push("  You aren't carrying anything".to_string());
+        }
+
+        for (index, &item) in inventory.items.iter().enumerate() {
+            let name = data.item.get(item).map(|i| i.name.as_str()).unwrap_or("Something");
+            let marker = if index == data.inventory_screen.selected_index { '*' } else { ' ' };
+            let worn = match data.equippable.get(item) {
+                Some(equippable) if equipment.map(|e| e.is_wearing(item)).unwrap_or(false) => {
+                    format!(", worn on {}", slot_name(equippable.slot))
+                }
+                Some(equippable) => format!(", {}", slot_name(equippable.slot)),
+                None => String::new(),
+            };
+            lines.push(format!("{} {}{}", marker, name, worn));
+        }
+
+        lines.push(String::new());
+        lines.push("D: Drop   E: Wear or Remove   I or Esc: Close".to_string());
+
+        let window = &mut self.window;
+
+        window.draw(
+            &Rectangle {
+                pos: Vector::new(0, 0),
+                size: window.screen_size(),
+            },
+            Col(Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.7,
+            }),
+        );
+
+        let line_height = data.game_map_render_params.tile_size_px.y + 5.0;
+        let top_left = data.game_map_render_params.map_offset;
+
+        self.tileset
+            .execute(|tileset| {
+                for (row, line) in lines.iter().enumerate() {
+                    let to_render: Vec<&Image> = line
+                        .chars()
+                        .map(|c| {
+                            tileset
+                                .get(&c)
+                                .unwrap_or_else(|| panic!("Should have defined a tileset item for {}", c))
+                        })
+                        .collect();
+
+                    render_images_corner(
+                        window,
+                        &to_render,
+                        top_left + Vector::new(0.0, line_height * row as f32),
+                        Vector::new(0, 0),
+                        Corner::UpperLeft,
+                        Alignment::Horizontal,
+                    );
+                }
+
+                Ok(())
+            })
+            .expect("Rendering the inventory should work");
+    }
+}
+
+fn slot_name(slot: EquipSlot) -> &'static str {
+    match slot {
+        EquipSlot::Head => "Head",
+        EquipSlot::Body => "Body",
+        EquipSlot::Hands => "Hands",
+        EquipSlot::Back => "Back",
+    }
+}
@@ -16,9 +16,11 @@ mod image_render_helper;
 mod centered_image_renderer;
 mod chars_renderer;
 mod controls_renderer;
+mod inventory_renderer;
 mod oxygen_overlay;
 
 pub use centered_image_renderer::CenteredVerticalImagesRenderer;
 pub use chars_renderer::{CharsRenderer, CharsRendererSetup};
 pub use controls_renderer::{ControlsRenderer, ControlsRendererSetup};
+pub use inventory_renderer::{InventoryRenderer, InventoryRendererSetup};
 pub use oxygen_overlay::{OxygenOverlayRenderer, OxygenOverlaySetup};
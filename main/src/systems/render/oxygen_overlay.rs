@@ -11,8 +11,8 @@ use quicksilver::{
 
 use specs::Join;
 
-use components::{Camera, HasPosition, OxygenContainer, Visible};
-use resources::{GameMapDisplayOptions, GameMapRenderParams};
+use components::{Camera, Gas, GasMixture, HasPosition, OxygenContainer, Temperature, Visible};
+use resources::{GameMapDisplayOptions, GameMapRenderParams, MapOverlay};
 
 use world::{TilePos, VisibilityType};
 
@@ -20,6 +20,7 @@ use world::{TilePos, VisibilityType};
 pub struct OxygenOverlaySystemData<'a> {
     has_pos: ReadStorage<'a, HasPosition>,
     oxygen_cont: ReadStorage<'a, OxygenContainer>,
+    temperature: ReadStorage<'a, Temperature>,
     camera: ReadStorage<'a, Camera>,
     visible: ReadStorage<'a, Visible>,
     entities: Entities<'a>,
@@ -46,9 +47,10 @@ impl<'a, 'b> System<'a> for OxygenOverlayRenderer<'b> {
     type SystemData = OxygenOverlaySystemData<'a>;
 
     fn run(&mut self, data: Self::SystemData) {
-        if !data.display_options.show_oxygen_overlay {
-            return;
-        }
+        let overlay = match data.display_options.overlay {
+            Some(overlay) => overlay,
+            None => return,
+        };
 
         let camera_bounds = (&data.has_pos, &data.camera)
             .join()
@@ -57,12 +59,17 @@ impl<'a, 'b> System<'a> for OxygenOverlayRenderer<'b> {
             .expect("Camera should be defined and have a position");
 
         let mut oxygen_contents = HashMap::new();
+        let mut temperatures = HashMap::new();
 
         for (has_pos, ent, vis) in (&data.has_pos, &data.entities, &data.visible).join() {
             // Note: if there are two oxygen containers in a square it will look weird :shrug:
             if camera_bounds.contains_pos(has_pos.position) && vis.visibility == VisibilityType::CurrentlyVisible {
-                let local_contents = data.oxygen_cont.get(ent).map(|ox| ox.contents).unwrap_or(0);
-                *oxygen_contents.entry(has_pos.position).or_insert(0) += local_contents;
+                let local_contents = data.oxygen_cont.get(ent).map(|ox| ox.contents).unwrap_or_default();
+                *oxygen_contents.entry(has_pos.position).or_default() += local_contents;
+
+                if let Some(temp) = data.temperature.get(ent) {
+                    temperatures.insert(has_pos.position, temp.degrees);
+                }
             }
         }
 
@@ -71,25 +78,68 @@ impl<'a, 'b> System<'a> for OxygenOverlayRenderer<'b> {
                 // NB: contents is None when there is nothing visible in the area, but 0 when it's visible
                 // and there is no oxygen container (or it's empty or etc.)
                 if let Some(contents) = oxygen_contents.get(&TilePos { x, y }) {
+                    let color = to_color(overlay, contents, temperatures.get(&TilePos { x, y }).copied());
+
                     let render_pos = get_render_pos(x, y, camera_bounds, *data.render_params);
                     let rect = Rectangle::new(
                         render_pos,
                         Vector::new(data.render_params.font_width, data.render_params.font_width),
                     );
-                    self.window.draw(&rect, Col(to_color(*contents)));
+                    self.window.draw(&rect, Col(color));
                 }
             }
         }
     }
 }
 
-fn to_color(oxygen_content: usize) -> Color {
-    let b = (oxygen_content as f32) / (constants::oxygen::DEFAULT_FULL_OXYGEN as f32);
+fn to_color(overlay: MapOverlay, contents: &GasMixture, degrees: Option<f32>) -> Color {
+    use constants::oxygen::*;
+
+    // breathable gases go from red (none) to blue (full); harmful gases go from blue (none) to red (dangerous)
+    let (amount, full, harmful) = match overlay {
+        MapOverlay::Gas(Gas::Oxygen) => (contents.oxygen, DEFAULT_FULL_OXYGEN, false),
+        MapOverlay::Gas(Gas::Nitrogen) => (contents.nitrogen, DEFAULT_FULL_NITROGEN, false),
+        MapOverlay::Gas(Gas::CarbonDioxide) => (contents.carbon_dioxide, CARBON_DIOXIDE_FAST_DROP_THRESHOLD, true),
+        MapOverlay::Gas(Gas::Toxin) => (contents.toxin, TOXIN_FAST_DROP_THRESHOLD, true),
+        MapOverlay::Pressure => (contents.pressure(), DEFAULT_FULL_OXYGEN + DEFAULT_FULL_NITROGEN, false),
+        MapOverlay::Temperature => return temperature_color(degrees),
+    };
+
+    let mut b = ((amount as f32) / (full as f32)).min(1.0);
+    if harmful {
+        b = 1.0 - b;
+    }
     let r = 1.0 - b;
 
     Color { r, b, g: 0.0, a: 0.6 }
 }
 
+// Blue when freezing, through clear when comfortable, to red when scalding; squares without
+// a temperature (there's nothing to hold any heat) are as cold as space
+fn temperature_color(degrees: Option<f32>) -> Color {
+    use constants::temperature::*;
+
+    let degrees = degrees.unwrap_or(SPACE_TEMPERATURE);
+
+    if degrees < DEFAULT_STATION_TEMPERATURE {
+        let cold = (DEFAULT_STATION_TEMPERATURE - degrees) / (DEFAULT_STATION_TEMPERATURE - SEVERE_COLD_THRESHOLD);
+        Color {
+            r: 0.0,
+            g: 0.0,
+            b: 1.0,
+            a: 0.6 * cold.min(1.0),
+        }
+    } else {
+        let hot = (degrees - DEFAULT_STATION_TEMPERATURE) / (SEVERE_HEAT_THRESHOLD - DEFAULT_STATION_TEMPERATURE);
+        Color {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.6 * hot.min(1.0),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 struct CameraBounds {
     x_min: i32,
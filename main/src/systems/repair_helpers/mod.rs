@@ -0,0 +1,81 @@
+//! What it takes to fix broken things, given the repairer's skill and what they have on hand
+
+use super::*;
+
+use components::Repairable;
+use skills::SkillTree;
+
+pub const REPAIR_SKILL: &str = "Repair";
+
+/// The player's repair level; zero if the skill tree doesn't have repair in it at all
+pub fn repair_level(skills: &SkillTree) -> usize {
+    skills.check_level(REPAIR_SKILL).unwrap_or(0)
+}
+
+/// How many turns a repair takes; skill beyond what the job needs makes it go faster
+pub fn repair_duration(repairable: &Repairable, skill_level: usize) -> usize {
+    std::cmp::max(1, repairable.turns * (repairable.skill_level + 1) / (skill_level + 1))
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RepairBlocker {
+    NotSkilled { needed: usize },
+    NotEnoughParts { needed: usize, have: usize },
+}
+
+pub fn check_repair(repairable: &Repairable, skill_level: usize, parts: usize) -> Result<(), RepairBlocker> {
+    if skill_level < repairable.skill_level {
+        Err(RepairBlocker::NotSkilled {
+            needed: repairable.skill_level,
+        })
+    } else if parts < repairable.parts {
+        Err(RepairBlocker::NotEnoughParts {
+            needed: repairable.parts,
+            have: parts,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+pub fn blocker_text(blocker: RepairBlocker) -> String {
+    match blocker {
+        RepairBlocker::NotSkilled { needed } => format!("You don't know enough to fix this (it needs Repair level {}).", needed),
+        RepairBlocker::NotEnoughParts { needed, have } => {
+            format!("This needs {} spare parts, and you only have {}.", needed, have)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOB: Repairable = Repairable {
+        skill_level: 1,
+        parts: 2,
+        turns: 20,
+    };
+
+    #[test]
+    fn repair_duration_test() {
+        // just enough skill takes the full time
+        assert_eq!(repair_duration(&JOB, 1), 20);
+        // more skill is faster
+        assert_eq!(repair_duration(&JOB, 3), 10);
+        // but nothing is instant
+        assert_eq!(repair_duration(&JOB, 100), 1);
+    }
+
+    #[test]
+    fn check_repair_test() {
+        assert_eq!(check_repair(&JOB, 1, 2), Ok(()));
+        assert_eq!(check_repair(&JOB, 5, 10), Ok(()));
+
+        assert_eq!(check_repair(&JOB, 0, 10), Err(RepairBlocker::NotSkilled { needed: 1 }));
+        assert_eq!(check_repair(&JOB, 1, 1), Err(RepairBlocker::NotEnoughParts { needed: 2, have: 1 }));
+
+        // not knowing how is the bigger problem
+        assert_eq!(check_repair(&JOB, 0, 0), Err(RepairBlocker::NotSkilled { needed: 1 }));
+    }
+}
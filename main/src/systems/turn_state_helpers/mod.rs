@@ -1,5 +1,7 @@
 use super::*;
 
+use rand::SeedableRng;
+
 use resources::{GameClock, NpcMoves, RenderStale};
 
 // how many update frames after a manual move until NPC can move
@@ -50,3 +52,14 @@ fn advance_clock(game_clock: &mut GameClock, num_turns: usize) {
         game_clock.minutes %= 60;
     }
 }
+
+// How many turns into the game the clock is; handy for comparing how long ago something happened
+pub fn current_turn(game_clock: &GameClock) -> u64 {
+    game_clock.total_seconds() / SECONDS_PER_TURN as u64
+}
+
+// A fresh rng for the current turn, so a given turn always plays out the same way, even after a
+// reload. Each system should use its own salt, so they aren't all rolling the same numbers.
+pub fn turn_rng(game_clock: &GameClock, salt: u64) -> crate::rng::PalladRng {
+    crate::rng::PalladRng::seed_from_u64(game_clock.total_seconds() ^ salt)
+}
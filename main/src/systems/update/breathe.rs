@@ -2,7 +2,9 @@ use super::*;
 
 use std::collections::HashMap;
 
-use components::{Breathes, CanSuffocate, HasPosition, OxygenContainer};
+use death_helpers::{process_death, DeathCause};
+
+use components::{Breathes, CanSuffocate, Equipment, Equippable, GasMixture, HasPosition, OxygenContainer, OxygenTank, Temperature};
 use resources::{Callbacks, NpcMoves};
 
 fn safe_subtract(start: usize, subtraction: usize) -> usize {
@@ -21,12 +23,17 @@ fn add_and_cap(start: usize, add: usize, cap: usize) -> usize {
 pub struct BreathSystemData<'a> {
     has_pos: ReadStorage<'a, HasPosition>,
     oxygen_cont: WriteStorage<'a, OxygenContainer>,
+    temperature: ReadStorage<'a, Temperature>,
     breathes: WriteStorage<'a, Breathes>,
     can_suffocate: ReadStorage<'a, CanSuffocate>,
+    equipment: ReadStorage<'a, Equipment>,
+    equippable: ReadStorage<'a, Equippable>,
+    oxygen_tank: WriteStorage<'a, OxygenTank>,
     entities: Entities<'a>,
 
     npc_moves: Read<'a, NpcMoves>,
     callbacks: Write<'a, Callbacks>,
+    lazy_update: Read<'a, LazyUpdate>,
 }
 
 pub struct BreatheSystem;
@@ -39,39 +46,139 @@ impl<'a> System<'a> for BreatheSystem {
             return;
         }
 
-        // first build the amount of oxygen available at all places
-        let mut oxygen_map: HashMap<TilePos, usize> = HashMap::new();
+        // first build the air available at all places
+        let mut air_map: HashMap<TilePos, GasMixture> = HashMap::new();
         for (hp, oc) in (&data.has_pos, &data.oxygen_cont).join() {
-            *oxygen_map.entry(hp.position).or_insert(0) += oc.contents;
+            *air_map.entry(hp.position).or_default() += oc.contents;
         }
 
+        let temp_map: HashMap<TilePos, f32> = (&data.has_pos, &data.temperature)
+            .join()
+            .map(|(hp, temp)| (hp.position, temp.degrees))
+            .collect();
+
+        // how much oxygen was breathed in at each place, so it can be replaced by carbon dioxide
+        let mut breaths: HashMap<TilePos, usize> = HashMap::new();
+
+        let tanks = &mut data.oxygen_tank;
+
         // then look through all the breathers and see what happens to them
         for (breathe, hp, entity) in (&mut data.breathes, &data.has_pos, &data.entities).join() {
-            let oxygen_here = oxygen_map.get(&hp.position).copied().unwrap_or(0);
-
-            if oxygen_here >= breathe.fast_gain_threshold {
-                add_oxygen(breathe, constants::oxygen::FAST_GAIN_SPEED);
-            } else if oxygen_here >= breathe.slow_gain_threshold {
-                add_oxygen(breathe, constants::oxygen::SLOW_GAIN_SPEED);
-            } else if oxygen_here >= breathe.slow_drop_threshold {
-                lose_oxygen(breathe, constants::oxygen::SLOW_DROP_SPEED);
-                if breathe.contents == 0 {
-                    if let Some(cs) = data.can_suffocate.get(entity) {
-                        process_suffocate(entity, cs, &mut data.callbacks, &data.entities);
-                    }
+            let outside_air = air_map.get(&hp.position).copied().unwrap_or_default();
+            let equipment = data.equipment.get(entity);
+            let modifiers = equipment_helpers::worn_modifiers(equipment, &data.equippable);
+            let mut air_here = equipment_helpers::breathed_air(&outside_air, &modifiers);
+            let mut on_tank = false;
+
+            if equipment_helpers::needs_tank(&air_here, breathe, &modifiers) {
+                let tank = equipment
+                    .into_iter()
+                    .flat_map(|equipment| equipment.worn())
+                    .find(|&item| tanks.get(item).map(|tank| tank.contents > 0).unwrap_or(false))
+                    .and_then(|item| tanks.get_mut(item));
+
+                if let Some(tank) = tank {
+                    tank.contents = safe_subtract(tank.contents, constants::equipment::TANK_DRAW_PER_TURN);
+                    air_here = equipment_helpers::tank_air();
+                    on_tank = true;
                 }
+            }
+
+            // no temperature recorded means there's nothing to hold heat in; that's just space;
+            // but a sealed suit keeps the wearer comfortable either way
+            let temp_here = if modifiers.sealed {
+                constants::temperature::DEFAULT_STATION_TEMPERATURE
             } else {
-                lose_oxygen(breathe, constants::oxygen::FAST_DROP_SPEED);
-                if breathe.contents == 0 {
-                    if let Some(cs) = data.can_suffocate.get(entity) {
-                        process_suffocate(entity, cs, &mut data.callbacks, &data.entities);
+                temp_map
+                    .get(&hp.position)
+                    .copied()
+                    .unwrap_or(constants::temperature::SPACE_TEMPERATURE)
+            };
+
+            match breath_change(breathe, &air_here, temp_here) {
+                BreathChange::Gain(amount) => add_oxygen(breathe, amount),
+                BreathChange::Lose(amount, cause) => {
+                    lose_oxygen(breathe, amount);
+                    if breathe.contents == 0 {
+                        if let Some(cs) = data.can_suffocate.get(entity) {
+                            process_death(entity, cs, cause, &mut data.callbacks, &data.lazy_update);
+                        }
                     }
                 }
             }
+
+            // someone breathing from their own supply doesn't touch the air around them
+            if modifiers.breathed_oxygen.is_none() && !on_tank && air_here.oxygen > 0 {
+                *breaths.entry(hp.position).or_insert(0) += constants::oxygen::OXYGEN_PER_BREATH;
+            }
+        }
+
+        // finally, breathing uses up the oxygen and leaves carbon dioxide behind
+        for (hp, oc) in (&data.has_pos, &mut data.oxygen_cont).join() {
+            if let Some(breathed) = breaths.remove(&hp.position) {
+                exhale(&mut oc.contents, breathed);
+            }
         }
     }
 }
 
+// Every bit of oxygen breathed in comes back out as carbon dioxide; there's only so much to breathe
+fn exhale(air: &mut GasMixture, breathed: usize) {
+    let breathed = std::cmp::min(breathed, air.oxygen);
+    air.oxygen -= breathed;
+    air.carbon_dioxide += breathed;
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum BreathChange {
+    Gain(usize),
+    Lose(usize, DeathCause),
+}
+
+fn breath_change(breathe: &Breathes, air: &GasMixture, degrees: f32) -> BreathChange {
+    use constants::oxygen::*;
+    use constants::temperature::*;
+
+    let temp_cause = if degrees <= MILD_COLD_THRESHOLD {
+        Some(DeathCause::Cold)
+    } else if degrees >= MILD_HEAT_THRESHOLD {
+        Some(DeathCause::Heat)
+    } else {
+        None
+    };
+
+    // Poisonous air is deadly no matter how much oxygen there is
+    if air.toxin >= TOXIN_FAST_DROP_THRESHOLD || air.carbon_dioxide >= CARBON_DIOXIDE_FAST_DROP_THRESHOLD {
+        return BreathChange::Lose(FAST_DROP_SPEED, DeathCause::Air);
+    }
+
+    // Same with extreme temperatures
+    if let Some(cause) = temp_cause {
+        if degrees <= SEVERE_COLD_THRESHOLD || degrees >= SEVERE_HEAT_THRESHOLD {
+            return BreathChange::Lose(FAST_DROP_SPEED, cause);
+        }
+    }
+
+    let from_oxygen = if air.oxygen >= breathe.fast_gain_threshold {
+        BreathChange::Gain(FAST_GAIN_SPEED)
+    } else if air.oxygen >= breathe.slow_gain_threshold {
+        BreathChange::Gain(SLOW_GAIN_SPEED)
+    } else if air.oxygen >= breathe.slow_drop_threshold {
+        BreathChange::Lose(SLOW_DROP_SPEED, DeathCause::Air)
+    } else {
+        BreathChange::Lose(FAST_DROP_SPEED, DeathCause::Air)
+    };
+
+    // Stale air (or uncomfortable temperatures) mean you can't catch your breath, even if there is oxygen around
+    match (from_oxygen, temp_cause) {
+        (BreathChange::Gain(_), _) if air.carbon_dioxide >= CARBON_DIOXIDE_SLOW_DROP_THRESHOLD => {
+            BreathChange::Lose(SLOW_DROP_SPEED, DeathCause::Air)
+        }
+        (BreathChange::Gain(_), Some(cause)) => BreathChange::Lose(SLOW_DROP_SPEED, cause),
+        (other, _) => other,
+    }
+}
+
 fn add_oxygen(breathe: &mut Breathes, addition: usize) {
     breathe.contents = add_and_cap(breathe.contents, addition, breathe.capacity);
 }
@@ -80,22 +187,98 @@ fn lose_oxygen(breathe: &mut Breathes, loss: usize) {
     breathe.contents = safe_subtract(breathe.contents, loss);
 }
 
-fn process_suffocate(entity: Entity, cs: &CanSuffocate, callbacks: &mut Callbacks, entities: &Entities) {
-    use resources::Callback;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    match cs {
-        CanSuffocate::Player => {
-            let builder = dialogue_helpers::DialogueBuilder::new(
-                "You have been without air for too long.\n\nThis life is over, but the tether pulls you back.",
-            )
-            .with_option("[Continue]", vec![Callback::EndDialogue, Callback::LoadGame]);
+    use constants::oxygen::*;
 
-            dialogue_helpers::launch_dialogue(builder, callbacks);
-        }
-        CanSuffocate::Death => {
-            // thing dies, uh, delete it
-            // TODO: do something more interesting here?
-            entities.delete(entity).expect("Entity should be live, since it just came up");
+    const COMFORTABLE: f32 = constants::temperature::DEFAULT_STATION_TEMPERATURE;
+
+    fn air(oxygen: usize, carbon_dioxide: usize) -> GasMixture {
+        GasMixture {
+            oxygen,
+            carbon_dioxide,
+            ..GasMixture::station_air()
         }
     }
+
+    #[test]
+    fn oxygen_thresholds_test() {
+        let breathes = Breathes::default();
+        let change = |oxygen| breath_change(&breathes, &air(oxygen, 0), COMFORTABLE);
+
+        assert_eq!(BreathChange::Gain(FAST_GAIN_SPEED), change(FAST_GAIN_THRESHOLD));
+        assert_eq!(BreathChange::Gain(SLOW_GAIN_SPEED), change(FAST_GAIN_THRESHOLD - 1));
+        assert_eq!(BreathChange::Gain(SLOW_GAIN_SPEED), change(SLOW_GAIN_THRESHOLD));
+        assert_eq!(
+            BreathChange::Lose(SLOW_DROP_SPEED, DeathCause::Air),
+            change(SLOW_GAIN_THRESHOLD - 1)
+        );
+        assert_eq!(BreathChange::Lose(SLOW_DROP_SPEED, DeathCause::Air), change(SLOW_DROP_THRESHOLD));
+        assert_eq!(
+            BreathChange::Lose(FAST_DROP_SPEED, DeathCause::Air),
+            change(SLOW_DROP_THRESHOLD - 1)
+        );
+        assert_eq!(BreathChange::Lose(FAST_DROP_SPEED, DeathCause::Air), change(0));
+    }
+
+    #[test]
+    fn stale_air_test() {
+        let breathes = Breathes::default();
+        let change = |carbon_dioxide| breath_change(&breathes, &air(FAST_GAIN_THRESHOLD, carbon_dioxide), COMFORTABLE);
+
+        // a little carbon dioxide is fine
+        assert_eq!(BreathChange::Gain(FAST_GAIN_SPEED), change(CARBON_DIOXIDE_SLOW_DROP_THRESHOLD - 1));
+        // more, and plenty of oxygen won't help you catch your breath
+        assert_eq!(
+            BreathChange::Lose(SLOW_DROP_SPEED, DeathCause::Air),
+            change(CARBON_DIOXIDE_SLOW_DROP_THRESHOLD)
+        );
+        assert_eq!(
+            BreathChange::Lose(SLOW_DROP_SPEED, DeathCause::Air),
+            change(CARBON_DIOXIDE_FAST_DROP_THRESHOLD - 1)
+        );
+        // and much more suffocates you outright
+        assert_eq!(
+            BreathChange::Lose(FAST_DROP_SPEED, DeathCause::Air),
+            change(CARBON_DIOXIDE_FAST_DROP_THRESHOLD)
+        );
+    }
+
+    #[test]
+    fn stale_air_doesnt_help_thin_air_test() {
+        let breathes = Breathes::default();
+
+        let change = breath_change(
+            &breathes,
+            &air(SLOW_DROP_THRESHOLD - 1, CARBON_DIOXIDE_SLOW_DROP_THRESHOLD),
+            COMFORTABLE,
+        );
+
+        assert_eq!(BreathChange::Lose(FAST_DROP_SPEED, DeathCause::Air), change);
+    }
+
+    #[test]
+    fn exhale_test() {
+        let mut here = air(FAST_GAIN_THRESHOLD, 5);
+        let before = here;
+
+        exhale(&mut here, 3);
+
+        assert_eq!(before.oxygen - 3, here.oxygen);
+        assert_eq!(before.carbon_dioxide + 3, here.carbon_dioxide);
+        assert_eq!(before.nitrogen, here.nitrogen);
+    }
+
+    #[test]
+    fn exhale_all_the_oxygen_test() {
+        let mut here = air(2, 0);
+
+        exhale(&mut here, 5);
+
+        // you can't breathe out more than you breathed in
+        assert_eq!(0, here.oxygen);
+        assert_eq!(2, here.carbon_dioxide);
+    }
 }
@@ -0,0 +1,352 @@
+use super::*;
+
+use std::collections::{HashMap, HashSet};
+
+use components::*;
+use resources::NpcMoves;
+
+pub struct DecompressionSystem;
+
+#[derive(SystemData)]
+pub struct DecompressionSystemData<'a> {
+    has_pos: WriteStorage<'a, HasPosition>,
+    pushable: ReadStorage<'a, Pushable>,
+    player: ReadStorage<'a, Player>,
+    camera: ReadStorage<'a, Camera>,
+    oxygen_cont: ReadStorage<'a, OxygenContainer>,
+    blocks_airflow: WriteStorage<'a, BlocksAirflow>,
+    blocks_movement: ReadStorage<'a, BlocksMovement>,
+    window: WriteStorage<'a, Window>,
+    entities: Entities<'a>,
+
+    npc_moves: Read<'a, NpcMoves>,
+}
+
+impl<'a> System<'a> for DecompressionSystem {
+    type SystemData = DecompressionSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        if !data.npc_moves.move_was_made {
+            return;
+        }
+
+        let airblocks: HashSet<TilePos> = (&data.has_pos, &data.blocks_airflow).join().map(|(hp, _)| hp.position).collect();
+
+        let mut pressures: HashMap<TilePos, usize> = HashMap::new();
+        for (hp, oc) in (&data.has_pos, &data.oxygen_cont).join() {
+            if !airblocks.contains(&hp.position) {
+                *pressures.entry(hp.position).or_insert(0) += oc.contents.pressure();
+            }
+        }
+
+        // Squares which block airflow don't take part at all; anywhere else without any air
+        // container is out in open space
+        let pressure_at = |pos: TilePos| {
+            if airblocks.contains(&pos) {
+                None
+            } else {
+                Some(pressures.get(&pos).copied().unwrap_or(0))
+            }
+        };
+
+        let mut blocks: HashMap<TilePos, u32> = HashMap::new();
+        for (hp, _) in (&data.has_pos, &data.blocks_movement).join() {
+            *blocks.entry(hp.position).or_insert(0) += 1;
+        }
+
+        let windows: HashMap<TilePos, Entity> = (&data.has_pos, &data.window, &data.entities)
+            .join()
+            .map(|(hp, _, entity)| (hp.position, entity))
+            .collect();
+
+        let mut moves: Vec<(Entity, TilePos)> = Vec::new();
+        let mut plugs: Vec<Entity> = Vec::new();
+        let mut impacts: Vec<Entity> = Vec::new();
+
+        for (pushable, hp, entity, _) in (&data.pushable, &data.has_pos, &data.entities, !&data.blocks_airflow).join() {
+            let pos = hp.position;
+
+            let target = match push_target(pos, pressure_at) {
+                Some(target) => target,
+                None => continue,
+            };
+
+            // Loose objects can't fit through the breach, so they get stuck in it instead
+            if !pressures.contains_key(&target) && pushable.plugs_breaches {
+                plugs.push(entity);
+                continue;
+            }
+
+            // Anything blown into an open window (where the air is rushing out) hits the pane
+            if *blocks.get(&target).unwrap_or(&0) > 0 {
+                if let Some(&window) = windows.get(&target) {
+                    impacts.push(window);
+                }
+                continue;
+            }
+
+            if data.blocks_movement.contains(entity) {
+                *blocks.get_mut(&pos).expect("Should be an entry at position since this blocks") -= 1;
+                *blocks.entry(target).or_insert(0) += 1;
+            }
+
+            moves.push((entity, target));
+        }
+
+        for (entity, target) in moves {
+            let has_pos = data.has_pos.get_mut(entity).expect("Entity was just seen with a position");
+            let delta = TilePos {
+                x: target.x - has_pos.position.x,
+                y: target.y - has_pos.position.y,
+            };
+            has_pos.position = target;
+
+            // The camera follows the player, wherever they get blown to
+            if data.player.contains(entity) {
+                for (_, cam_pos) in (&data.camera, &mut data.has_pos).join() {
+                    cam_pos.position += delta;
+                }
+            }
+        }
+
+        for window in impacts {
+            if let Some(window) = data.window.get_mut(window) {
+                window.take_damage(constants::window::IMPACT_DAMAGE);
+            }
+        }
+
+        for entity in plugs {
+            data.blocks_airflow
+                .insert(entity, BlocksAirflow)
+                .expect("Entity should be live, since it just came up");
+        }
+    }
+}
+
+// Pushable things get shoved toward the lowest pressure around them, if the difference is big enough
+// to matter. Since the lowest pressure is always at the breach, this pulls things toward it, and then
+// through it.
+fn push_target<F>(pos: TilePos, pressure_at: F) -> Option<TilePos>
+where
+    F: Fn(TilePos) -> Option<usize>,
+{
+    let here = pressure_at(pos)?;
+
+    let mut lowest = (pos, here);
+    let mut highest = here;
+
+    for &neighbor in direct_neighbors(pos).iter().filter(|&&np| np != pos) {
+        if let Some(pressure) = pressure_at(neighbor) {
+            if pressure < lowest.1 {
+                lowest = (neighbor, pressure);
+            }
+            highest = std::cmp::max(highest, pressure);
+        }
+    }
+
+    if lowest.0 != pos && highest - lowest.1 >= constants::decompression::PUSH_PRESSURE_DIFFERENCE {
+        Some(lowest.0)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{Builder, RunNow, World, WorldExt};
+
+    fn take_turn(world: &mut World) {
+        world.write_resource::<NpcMoves>().move_was_made = true;
+        DecompressionSystem.run_now(world);
+        world.maintain();
+    }
+
+    fn pos_of(world: &World, entity: Entity) -> TilePos {
+        world.read_storage::<HasPosition>().get(entity).unwrap().position
+    }
+
+    const CENTER: TilePos = TilePos { x: 0, y: 0 };
+
+    // Pressure at the center, then west, north, south and east of it; None blocks airflow
+    fn around(pressures: [Option<usize>; 5]) -> impl Fn(TilePos) -> Option<usize> {
+        move |pos| {
+            direct_neighbors(CENTER)
+                .iter()
+                .position(|&np| np == pos)
+                .and_then(|ind| pressures[ind])
+        }
+    }
+
+    #[test]
+    fn push_target_direction_test() {
+        let west = around([Some(100), Some(0), Some(100), Some(100), Some(100)]);
+        assert_eq!(Some(TilePos { x: -1, y: 0 }), push_target(CENTER, west));
+
+        let south = around([Some(100), Some(100), Some(100), Some(0), Some(100)]);
+        assert_eq!(Some(TilePos { x: 0, y: 1 }), push_target(CENTER, south));
+
+        // the lowest pressure wins, even when the biggest difference is somewhere else
+        let east = around([Some(100), Some(50), Some(300), Some(100), Some(40)]);
+        assert_eq!(Some(TilePos { x: 1, y: 0 }), push_target(CENTER, east));
+
+        // walls don't count as low pressure
+        let walled = around([Some(100), None, Some(100), Some(100), Some(50)]);
+        assert_eq!(Some(TilePos { x: 1, y: 0 }), push_target(CENTER, walled));
+
+        // nothing in a wall gets pushed
+        let inside = around([None, Some(0), Some(100), Some(100), Some(100)]);
+        assert_eq!(None, push_target(CENTER, inside));
+    }
+
+    #[test]
+    fn push_target_threshold_test() {
+        use constants::decompression::PUSH_PRESSURE_DIFFERENCE;
+
+        let barely = around([Some(100), Some(100 - PUSH_PRESSURE_DIFFERENCE), Some(100), Some(100), Some(100)]);
+        assert_eq!(Some(TilePos { x: -1, y: 0 }), push_target(CENTER, barely));
+
+        let not_quite = around([Some(100), Some(101 - PUSH_PRESSURE_DIFFERENCE), Some(100), Some(100), Some(100)]);
+        assert_eq!(None, push_target(CENTER, not_quite));
+
+        // the difference is measured from the highest neighbor, not from here
+        let behind = around([Some(100), Some(90), Some(90 + PUSH_PRESSURE_DIFFERENCE), Some(100), Some(100)]);
+        assert_eq!(Some(TilePos { x: -1, y: 0 }), push_target(CENTER, behind));
+    }
+
+    #[test]
+    fn push_target_ties_test() {
+        // between equally low neighbors, the first one (west, north, south, east) wins
+        let tied = around([Some(100), Some(100), Some(0), Some(0), Some(0)]);
+        assert_eq!(Some(TilePos { x: 0, y: -1 }), push_target(CENTER, tied));
+
+        // and nothing moves if it's already (tied for) the lowest
+        let lowest = around([Some(0), Some(100), Some(0), Some(100), Some(100)]);
+        assert_eq!(None, push_target(CENTER, lowest));
+    }
+
+    #[test]
+    fn rubbish_plugs_the_breach_test() {
+        let mut world = World::new();
+        System::setup(&mut DecompressionSystem, &mut world);
+
+        // a room venting through an open airlock (x=2) into space (x=3)
+        for (x, &oxygen) in [280, 140, 20].iter().enumerate() {
+            world
+                .create_entity()
+                .with(HasPosition {
+                    position: TilePos { x: x as i32, y: 0 },
+                })
+                .with(OxygenContainer {
+                    capacity: constants::oxygen::DEFAULT_CONTAINER_CAPACITY,
+                    contents: GasMixture {
+                        oxygen,
+                        ..GasMixture::default()
+                    },
+                })
+                .build();
+
+            for &y in [-1, 1].iter() {
+                world
+                    .create_entity()
+                    .with(HasPosition {
+                        position: TilePos { x: x as i32, y },
+                    })
+                    .with(BlocksAirflow)
+                    .build();
+            }
+        }
+
+        world
+            .create_entity()
+            .with(HasPosition {
+                position: TilePos { x: -1, y: 0 },
+            })
+            .with(BlocksAirflow)
+            .build();
+
+        let rubbish = world
+            .create_entity()
+            .with(HasPosition {
+                position: TilePos { x: 0, y: 0 },
+            })
+            .with(Pushable { plugs_breaches: true })
+            .build();
+
+        take_turn(&mut world);
+        assert_eq!(TilePos { x: 1, y: 0 }, pos_of(&world, rubbish));
+
+        take_turn(&mut world);
+        assert_eq!(TilePos { x: 2, y: 0 }, pos_of(&world, rubbish));
+        assert!(!world.read_storage::<BlocksAirflow>().contains(rubbish));
+
+        // it can't fit through, so it jams in the airlock and seals it
+        take_turn(&mut world);
+        assert_eq!(TilePos { x: 2, y: 0 }, pos_of(&world, rubbish));
+        assert!(world.read_storage::<BlocksAirflow>().contains(rubbish));
+
+        // and then stays put
+        take_turn(&mut world);
+        assert_eq!(TilePos { x: 2, y: 0 }, pos_of(&world, rubbish));
+    }
+
+    #[test]
+    fn blown_into_a_window_test() {
+        let mut world = World::new();
+        System::setup(&mut DecompressionSystem, &mut world);
+
+        // a room full of air, then an open window onto space
+        for (x, contents) in [GasMixture::station_air(), GasMixture::default()].iter().enumerate() {
+            world
+                .create_entity()
+                .with(HasPosition {
+                    position: TilePos { x: x as i32, y: 0 },
+                })
+                .with(OxygenContainer {
+                    capacity: constants::oxygen::DEFAULT_CONTAINER_CAPACITY,
+                    contents: *contents,
+                })
+                .build();
+        }
+
+        // walled in everywhere else
+        for &(x, y) in [(-1, 0), (0, -1), (0, 1)].iter() {
+            world
+                .create_entity()
+                .with(HasPosition {
+                    position: TilePos { x, y },
+                })
+                .with(BlocksAirflow)
+                .build();
+        }
+
+        let window = world
+            .create_entity()
+            .with(HasPosition {
+                position: TilePos { x: 1, y: 0 },
+            })
+            .with(Window {
+                window_state: WindowState::Open,
+                integrity: constants::window::MAX_INTEGRITY,
+            })
+            .with(BlocksMovement)
+            .build();
+
+        let rubbish = world
+            .create_entity()
+            .with(HasPosition {
+                position: TilePos { x: 0, y: 0 },
+            })
+            .with(Pushable { plugs_breaches: true })
+            .build();
+
+        take_turn(&mut world);
+
+        assert_eq!(TilePos { x: 0, y: 0 }, pos_of(&world, rubbish));
+        assert_eq!(
+            constants::window::MAX_INTEGRITY - constants::window::IMPACT_DAMAGE,
+            world.read_storage::<Window>().get(window).unwrap().integrity
+        );
+    }
+}
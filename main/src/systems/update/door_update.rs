@@ -1,10 +1,12 @@
 use super::*;
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use components::*;
 use resources::NpcMoves;
 
+use door_helpers::{allows, carries_keycard, DoorOpener};
+
 pub struct DoorOpenSystem;
 
 #[derive(SystemData)]
@@ -12,6 +14,13 @@ pub struct DoorOpenSystemData<'a> {
     has_position: ReadStorage<'a, HasPosition>,
     door: WriteStorage<'a, Door>,
     opens_doors: ReadStorage<'a, OpensDoors>,
+    player: ReadStorage<'a, Player>,
+    npc: ReadStorage<'a, NPC>,
+    has_keycard: ReadStorage<'a, HasKeycard>,
+    inventory: ReadStorage<'a, Inventory>,
+    keycard: ReadStorage<'a, Keycard>,
+    power_consumer: ReadStorage<'a, PowerConsumer>,
+    broken: ReadStorage<'a, Broken>,
     char_render: WriteStorage<'a, CharRender>,
 
     blocks_airflow: WriteStorage<'a, BlocksAirflow>,
@@ -30,15 +39,36 @@ impl<'a> System<'a> for DoorOpenSystem {
             return;
         }
 
-        // All the squares which have a DoorOpen component on them
-        let mut has_door_open = HashSet::new();
+        // Everything which could open a door, by the square it's standing on
+        let mut openers: HashMap<TilePos, Vec<DoorOpener>> = HashMap::new();
 
-        for (_, has_pos) in (&data.opens_doors, &data.has_position).join() {
-            has_door_open.insert(has_pos.position);
+        for (_, has_pos, entity) in (&data.opens_doors, &data.has_position, &data.entities).join() {
+            let opener = DoorOpener {
+                is_player: data.player.contains(entity),
+                is_alien: matches!(data.npc.get(entity), Some(NPC::Alien(_))),
+                has_keycard: data.has_keycard.contains(entity) || carries_keycard(data.inventory.get(entity), &data.keycard),
+            };
+            openers.entry(has_pos.position).or_default().push(opener);
         }
 
         for (mut door, has_pos, entity) in (&mut data.door, &data.has_position, &data.entities).join() {
-            let has_adjacent = full_neighbors(has_pos.position).iter().any(|pos| has_door_open.contains(&pos));
+            // Without power, doors stay however they were left
+            if !power_helpers::is_powered(data.power_consumer.get(entity)) {
+                continue;
+            }
+
+            // Same with broken doors, until somebody fixes them
+            if data.broken.contains(entity) {
+                continue;
+            }
+
+            let access = door.access;
+            let has_adjacent = full_neighbors(has_pos.position).iter().any(|pos| {
+                openers
+                    .get(pos)
+                    .map(|here| here.iter().any(|&opener| allows(access, opener)))
+                    .unwrap_or(false)
+            });
             let renderable = data.char_render.get_mut(entity);
 
             let (should_open, should_close) = match door.door_behavior {
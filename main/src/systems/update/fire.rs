@@ -0,0 +1,349 @@
+use super::*;
+
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+
+use death_helpers::{process_death, DeathCause};
+
+use components::{
+    Breathes, Burning, CanSuffocate, CharRender, Flammable, HasPosition, HeatSetting, HeatSource, OxygenContainer, PowerConsumer,
+    Temperature,
+};
+use resources::{Callbacks, GameClock, NpcMoves};
+
+pub struct FireSystem;
+
+#[derive(SystemData)]
+pub struct FireSystemData<'a> {
+    has_pos: ReadStorage<'a, HasPosition>,
+    flammable: WriteStorage<'a, Flammable>,
+    burning: WriteStorage<'a, Burning>,
+    heat_source: ReadStorage<'a, HeatSource>,
+    power_consumer: ReadStorage<'a, PowerConsumer>,
+    oxygen_cont: WriteStorage<'a, OxygenContainer>,
+    temperature: WriteStorage<'a, Temperature>,
+    char_render: WriteStorage<'a, CharRender>,
+    breathes: WriteStorage<'a, Breathes>,
+    can_suffocate: ReadStorage<'a, CanSuffocate>,
+    entities: Entities<'a>,
+
+    npc_moves: Read<'a, NpcMoves>,
+    game_clock: Read<'a, GameClock>,
+    callbacks: Write<'a, Callbacks>,
+    lazy_update: Read<'a, LazyUpdate>,
+}
+
+impl<'a> System<'a> for FireSystem {
+    type SystemData = FireSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        use constants::fire::*;
+
+        if !data.npc_moves.move_was_made {
+            return;
+        }
+
+        let mut rng = turn_state_helpers::turn_rng(&data.game_clock, FIRE_RNG_SALT);
+
+        let mut oxygen_map: HashMap<TilePos, usize> = HashMap::new();
+        for (hp, oc) in (&data.has_pos, &data.oxygen_cont).join() {
+            *oxygen_map.entry(hp.position).or_insert(0) += oc.contents.oxygen;
+        }
+        let oxygen_at = |pos: TilePos| oxygen_map.get(&pos).copied().unwrap_or(0);
+
+        let mut to_ignite: Vec<Entity> = Vec::new();
+        let mut to_extinguish: Vec<Entity> = Vec::new();
+        let mut burnt_out: Vec<Entity> = Vec::new();
+
+        // how much oxygen each fire is burning through, by position
+        let mut oxygen_burned: HashMap<TilePos, usize> = HashMap::new();
+
+        // First, existing fires burn (if they can)
+        for (_, flammable, hp, entity) in (&data.burning, &mut data.flammable, &data.has_pos, &data.entities).join() {
+            if oxygen_at(hp.position) < MIN_OXYGEN {
+                to_extinguish.push(entity);
+                continue;
+            }
+
+            *oxygen_burned.entry(hp.position).or_insert(0) += OXYGEN_PER_TURN;
+
+            flammable.fuel = flammable.fuel.saturating_sub(1);
+            if flammable.fuel == 0 {
+                burnt_out.push(entity);
+            }
+        }
+
+        let fire_positions: HashSet<TilePos> = oxygen_burned.keys().copied().collect();
+
+        // Then, fires spread to nearby flammable things; the more air around the target, the likelier it is to catch
+        for (_, hp, entity, _) in (&data.flammable, &data.has_pos, &data.entities, !&data.burning).join() {
+            let oxygen = std::cmp::min(oxygen_at(hp.position), constants::oxygen::DEFAULT_FULL_OXYGEN);
+            if oxygen < MIN_OXYGEN {
+                continue;
+            }
+
+            let overloaded = data.heat_source.get(entity).map(|hs| hs.setting) == Some(HeatSetting::Overload)
+                && power_helpers::is_powered(data.power_consumer.get(entity));
+
+            let chance = SPREAD_CHANCE_PERC * oxygen / constants::oxygen::DEFAULT_FULL_OXYGEN;
+            let catches = direct_neighbors(hp.position)
+                .iter()
+                .filter(|pos| fire_positions.contains(pos))
+                .any(|_| rng.gen_range(0, 100) < chance);
+
+            if overloaded || catches {
+                to_ignite.push(entity);
+            }
+        }
+
+        for entity in to_ignite {
+            data.burning
+                .insert(entity, Burning)
+                .expect("Entity should be live, since it just came up");
+            if let Some(cr) = data.char_render.get_mut(entity) {
+                cr.fg_color = FIRE_FG;
+                cr.bg_color = FIRE_BG;
+            }
+        }
+
+        for entity in to_extinguish {
+            data.burning.remove(entity);
+            if let Some(cr) = data.char_render.get_mut(entity) {
+                cr.fg_color = CHARRED_FG;
+                cr.bg_color = CLEAR;
+            }
+        }
+
+        for entity in burnt_out {
+            data.entities.delete(entity).expect("Entity should be live, since it just came up");
+        }
+
+        // Fires use up the oxygen around them ...
+        for (hp, oc) in (&data.has_pos, &mut data.oxygen_cont).join() {
+            if let Some(burned) = oxygen_burned.remove(&hp.position) {
+                let burned = std::cmp::min(burned, oc.contents.oxygen);
+                oc.contents.oxygen -= burned;
+                oc.contents.carbon_dioxide += burned;
+            }
+        }
+
+        // ... and heat it up ...
+        for (hp, temp) in (&data.has_pos, &mut data.temperature).join() {
+            if fire_positions.contains(&hp.position) && temp.degrees < FIRE_TEMPERATURE {
+                temp.degrees = FIRE_TEMPERATURE;
+            }
+        }
+
+        // ... and burn anything breathing nearby
+        for (breathes, hp, entity) in (&mut data.breathes, &data.has_pos, &data.entities).join() {
+            if !direct_neighbors(hp.position).iter().any(|pos| fire_positions.contains(pos)) {
+                continue;
+            }
+
+            breathes.contents = breathes.contents.saturating_sub(BURN_DAMAGE);
+            if breathes.contents == 0 {
+                if let Some(cs) = data.can_suffocate.get(entity) {
+                    process_death(entity, cs, DeathCause::Fire, &mut data.callbacks, &data.lazy_update);
+                }
+            }
+        }
+    }
+}
+
+const FIRE_RNG_SALT: u64 = 0x4649_5245;
+
+const FIRE_FG: Color = Color {
+    r: 1.0,
+    g: 0.6,
+    b: 0.1,
+    a: 1.0,
+};
+
+const FIRE_BG: Color = Color {
+    r: 0.7,
+    g: 0.15,
+    b: 0.0,
+    a: 1.0,
+};
+
+const CHARRED_FG: Color = Color {
+    r: 0.25,
+    g: 0.25,
+    b: 0.25,
+    a: 1.0,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{Builder, RunNow, World, WorldExt};
+
+    use components::GasMixture;
+    use constants::fire::*;
+
+    const FULL_AIR: usize = constants::oxygen::DEFAULT_FULL_OXYGEN;
+    const THIN_AIR: usize = MIN_OXYGEN - 1;
+
+    // Every square in the row gets a container with this much oxygen in it; None is open space
+    fn row(oxygen: &[Option<usize>]) -> World {
+        let mut world = World::new();
+        System::setup(&mut FireSystem, &mut world);
+
+        for (x, &oxygen) in oxygen.iter().enumerate() {
+            if let Some(oxygen) = oxygen {
+                let contents = GasMixture {
+                    oxygen,
+                    ..GasMixture::station_air()
+                };
+                world
+                    .create_entity()
+                    .with(OxygenContainer { capacity: 1000, contents })
+                    .with(HasPosition { position: pos(x) })
+                    .build();
+            }
+        }
+
+        world
+    }
+
+    fn pos(x: usize) -> TilePos {
+        TilePos { x: x as i32, y: 0 }
+    }
+
+    fn flammable(world: &mut World, x: usize, fuel: usize) -> Entity {
+        world
+            .create_entity()
+            .with(Flammable { fuel })
+            .with(HasPosition { position: pos(x) })
+            .build()
+    }
+
+    fn fire(world: &mut World, x: usize, fuel: usize) -> Entity {
+        let entity = flammable(world, x, fuel);
+        world.write_storage::<Burning>().insert(entity, Burning).unwrap();
+        entity
+    }
+
+    fn take_turn(world: &mut World) {
+        world.write_resource::<NpcMoves>().move_was_made = true;
+        FireSystem.run_now(world);
+        world.maintain();
+
+        // the next turn rolls different numbers
+        world.write_resource::<GameClock>().seconds += 6;
+    }
+
+    fn is_burning(world: &World, entity: Entity) -> bool {
+        world.read_storage::<Burning>().contains(entity)
+    }
+
+    #[test]
+    fn spreads_through_air_test() {
+        let mut world = row(&[Some(FULL_AIR), Some(FULL_AIR)]);
+        fire(&mut world, 0, 100);
+        let rubbish = flammable(&mut world, 1, 100);
+
+        for _ in 0..10 {
+            take_turn(&mut world);
+        }
+
+        assert!(is_burning(&world, rubbish));
+    }
+
+    #[test]
+    fn doesnt_spread_without_air_test() {
+        let mut world = row(&[Some(FULL_AIR), Some(THIN_AIR), None]);
+        fire(&mut world, 0, 100);
+        let thin_air = flammable(&mut world, 1, 100);
+        let no_air = flammable(&mut world, 2, 100);
+
+        for _ in 0..10 {
+            take_turn(&mut world);
+        }
+
+        assert!(!is_burning(&world, thin_air));
+        assert!(!is_burning(&world, no_air));
+    }
+
+    #[test]
+    fn goes_out_without_air_test() {
+        let mut world = row(&[Some(THIN_AIR), None]);
+        let thin_air = fire(&mut world, 0, 100);
+        let no_air = fire(&mut world, 1, 100);
+
+        take_turn(&mut world);
+
+        // they're put out, but they aren't burnt up
+        assert!(!is_burning(&world, thin_air));
+        assert!(!is_burning(&world, no_air));
+        assert!(world.is_alive(thin_air));
+        assert!(world.is_alive(no_air));
+    }
+
+    #[test]
+    fn uses_up_its_air_test() {
+        let mut world = row(&[Some(FULL_AIR)]);
+        let burning = fire(&mut world, 0, 100);
+
+        take_turn(&mut world);
+
+        let contents = world.read_storage::<OxygenContainer>().join().next().unwrap().contents;
+        assert_eq!(FULL_AIR - OXYGEN_PER_TURN, contents.oxygen);
+        assert_eq!(OXYGEN_PER_TURN, contents.carbon_dioxide);
+
+        // and burns until there isn't enough left
+        for _ in 0..(FULL_AIR - MIN_OXYGEN) / OXYGEN_PER_TURN {
+            take_turn(&mut world);
+        }
+        assert!(is_burning(&world, burning));
+
+        take_turn(&mut world);
+        assert!(!is_burning(&world, burning));
+    }
+
+    #[test]
+    fn burns_out_test() {
+        let mut world = row(&[Some(FULL_AIR)]);
+        let burning = fire(&mut world, 0, 3);
+
+        take_turn(&mut world);
+        take_turn(&mut world);
+        assert!(world.is_alive(burning));
+
+        take_turn(&mut world);
+        assert!(!world.is_alive(burning));
+    }
+
+    #[test]
+    fn overloaded_heater_ignites_test() {
+        let mut world = row(&[Some(FULL_AIR), Some(FULL_AIR), Some(FULL_AIR)]);
+        let heater = |world: &mut World, x, setting, powered| {
+            let entity = flammable(world, x, 100);
+            world.write_storage::<HeatSource>().insert(entity, HeatSource { setting }).unwrap();
+            world
+                .write_storage::<PowerConsumer>()
+                .insert(
+                    entity,
+                    PowerConsumer {
+                        load: 1,
+                        priority: components::PowerPriority::Normal,
+                        connected: true,
+                        powered,
+                    },
+                )
+                .unwrap();
+            entity
+        };
+        let overloaded = heater(&mut world, 0, HeatSetting::Overload, true);
+        let heating = heater(&mut world, 1, HeatSetting::Heat, true);
+        let unpowered = heater(&mut world, 2, HeatSetting::Overload, false);
+
+        take_turn(&mut world);
+
+        assert!(is_burning(&world, overloaded));
+        assert!(!is_burning(&world, heating));
+        assert!(!is_burning(&world, unpowered));
+    }
+}
@@ -0,0 +1,214 @@
+use super::*;
+
+use std::collections::{HashMap, HashSet};
+
+use components::{Door, DoorState, HasPosition, HeatSetting, HeatSource, Insulated, PowerConsumer, Temperature, Vacuum};
+use resources::NpcMoves;
+
+pub struct HeatSpreadSystem;
+
+#[derive(SystemData)]
+pub struct HeatSpreadSystemData<'a> {
+    has_pos: ReadStorage<'a, HasPosition>,
+    temperature: WriteStorage<'a, Temperature>,
+    insulated: ReadStorage<'a, Insulated>,
+    door: ReadStorage<'a, Door>,
+    vacuums: ReadStorage<'a, Vacuum>,
+    heat_sources: ReadStorage<'a, HeatSource>,
+    power_consumer: ReadStorage<'a, PowerConsumer>,
+
+    npc_moves: Read<'a, NpcMoves>,
+}
+
+impl<'a> System<'a> for HeatSpreadSystem {
+    type SystemData = HeatSpreadSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        use constants::temperature::*;
+
+        if !data.npc_moves.move_was_made {
+            return;
+        }
+
+        let insulated = timed!("Computing insulated places", {
+            (&data.has_pos, &data.insulated, data.door.maybe())
+                .join()
+                .filter(|(_, _, door)| insulates(*door))
+                .map(|(hp, _, _)| hp.position)
+                .collect::<HashSet<TilePos>>()
+        });
+
+        // First, vacuums lose all their heat into space
+        for (temp, _) in (&mut data.temperature, &data.vacuums).join() {
+            temp.degrees = SPACE_TEMPERATURE;
+        }
+
+        // Then heaters and coolers do their thing
+        let mut temps: HashMap<TilePos, f32> = (&data.has_pos, &data.temperature)
+            .join()
+            .map(|(hp, temp)| (hp.position, temp.degrees))
+            .collect();
+
+        for (hp, heat_source, power) in (&data.has_pos, &data.heat_sources, data.power_consumer.maybe()).join() {
+            if !power_helpers::is_powered(power) {
+                continue;
+            }
+            if let Some(degrees) = temps.get_mut(&hp.position) {
+                *degrees = apply_heat_source(*degrees, heat_source.setting);
+            }
+        }
+
+        let temps = spread_heat(temps, &insulated, HEAT_SYSTEM_ITERATIONS);
+
+        for (hp, temp, _) in (&data.has_pos, &mut data.temperature, !&data.vacuums).join() {
+            if let Some(&degrees) = temps.get(&hp.position) {
+                temp.degrees = degrees;
+            }
+        }
+    }
+}
+
+// An open door is just a hole in the wall, as far as heat is concerned
+fn insulates(door: Option<&Door>) -> bool {
+    door.map(|door| door.door_state != DoorState::Open).unwrap_or(true)
+}
+
+// Heat evens out between neighbors; the exchange between two squares is symmetric, so heat is
+// only ever lost to space (anywhere without a temperature)
+fn spread_heat(mut temps: HashMap<TilePos, f32>, insulated: &HashSet<TilePos>, iterations: usize) -> HashMap<TilePos, f32> {
+    use constants::temperature::*;
+
+    for _ in 0..iterations {
+        temps = temps
+            .iter()
+            .map(|(&pos, &degrees)| {
+                let mut change = 0.0;
+
+                for &neighbor in direct_neighbors(pos).iter().filter(|&&np| np != pos) {
+                    let (neighbor_degrees, conductance) = match temps.get(&neighbor) {
+                        Some(&nd) if insulated.contains(&pos) || insulated.contains(&neighbor) => (nd, INSULATED_CONDUCTANCE),
+                        Some(&nd) => (nd, OPEN_CONDUCTANCE),
+                        None => (SPACE_TEMPERATURE, SPACE_CONDUCTANCE),
+                    };
+
+                    change += (neighbor_degrees - degrees) * conductance;
+                }
+
+                (pos, degrees + change)
+            })
+            .collect();
+    }
+
+    temps
+}
+
+// Heaters and coolers act like a thermostat; they push toward their target, but never past it
+fn apply_heat_source(degrees: f32, setting: HeatSetting) -> f32 {
+    use constants::temperature::*;
+
+    match setting {
+        HeatSetting::Heat if degrees < HEATER_TARGET => (degrees + HEAT_SOURCE_POWER).min(HEATER_TARGET),
+        HeatSetting::Cool if degrees > COOLER_TARGET => (degrees - HEAT_SOURCE_POWER).max(COOLER_TARGET),
+        _ => degrees,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use components::{DoorAccess, DoorBehavior};
+    use constants::temperature::*;
+
+    fn pos(x: i32, y: i32) -> TilePos {
+        TilePos { x, y }
+    }
+
+    // '#' is an insulated wall, '.' is open floor and anything else is space; everything starts
+    // at the station's usual temperature, except for the '*', which is hot floor
+    fn room(rows: &[&str]) -> (HashMap<TilePos, f32>, HashSet<TilePos>) {
+        let mut temps = HashMap::new();
+        let mut insulated = HashSet::new();
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let here = pos(x as i32, y as i32);
+                match c {
+                    '#' => {
+                        temps.insert(here, DEFAULT_STATION_TEMPERATURE);
+                        insulated.insert(here);
+                    }
+                    '.' => {
+                        temps.insert(here, DEFAULT_STATION_TEMPERATURE);
+                    }
+                    '*' => {
+                        temps.insert(here, HEATER_TARGET);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        (temps, insulated)
+    }
+
+    #[test]
+    fn spreads_across_open_floor_test() {
+        let (temps, insulated) = room(&[
+            "#####", //
+            "#*..#", //
+            "#####", //
+        ]);
+
+        let after = spread_heat(temps, &insulated, HEAT_SYSTEM_ITERATIONS);
+
+        assert!(after[&pos(1, 1)] < HEATER_TARGET);
+        assert!(after[&pos(2, 1)] > DEFAULT_STATION_TEMPERATURE + 1.0);
+        assert!(after[&pos(3, 1)] > DEFAULT_STATION_TEMPERATURE);
+    }
+
+    #[test]
+    fn held_back_by_insulation_test() {
+        let (temps, insulated) = room(&[
+            "#####", //
+            "#*#.#", //
+            "#####", //
+        ]);
+
+        let after = spread_heat(temps, &insulated, HEAT_SYSTEM_ITERATIONS);
+
+        // barely anything gets through the wall in the middle
+        assert!(after[&pos(3, 1)] < DEFAULT_STATION_TEMPERATURE + 0.01);
+        assert!(after[&pos(1, 1)] > HEATER_TARGET - 0.1);
+    }
+
+    #[test]
+    fn space_is_a_sink_test() {
+        let (temps, insulated) = room(&[
+            "   ", //
+            " . ", //
+            "   ", //
+        ]);
+
+        let after = spread_heat(temps, &insulated, 100);
+
+        assert!(after[&pos(1, 1)] < DEFAULT_STATION_TEMPERATURE);
+        // and nothing is made up for the space around it
+        assert_eq!(1, after.len());
+    }
+
+    #[test]
+    fn open_doors_dont_insulate_test() {
+        let mut door = Door {
+            door_state: DoorState::Closed,
+            door_behavior: DoorBehavior::FullAuto,
+            access: DoorAccess::Everyone,
+        };
+
+        assert!(insulates(None));
+        assert!(insulates(Some(&door)));
+
+        door.door_state = DoorState::Open;
+        assert!(!insulates(Some(&door)));
+    }
+}
@@ -0,0 +1,370 @@
+use super::*;
+
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+
+use components::*;
+use resources::{GameClock, NpcMoves};
+
+pub struct LifeSupportSystem;
+
+#[derive(SystemData)]
+pub struct LifeSupportSystemData<'a> {
+    has_pos: ReadStorage<'a, HasPosition>,
+    life_support: ReadStorage<'a, LifeSupport>,
+    power_consumer: ReadStorage<'a, PowerConsumer>,
+    breakable: WriteStorage<'a, Breakable>,
+    broken: WriteStorage<'a, Broken>,
+    oxygen_cont: WriteStorage<'a, OxygenContainer>,
+    blocks_airflow: ReadStorage<'a, BlocksAirflow>,
+    char_render: WriteStorage<'a, CharRender>,
+    entities: Entities<'a>,
+
+    npc_moves: Read<'a, NpcMoves>,
+    game_clock: Read<'a, GameClock>,
+}
+
+impl<'a> System<'a> for LifeSupportSystem {
+    type SystemData = LifeSupportSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        use constants::life_support::*;
+
+        if !data.npc_moves.move_was_made {
+            return;
+        }
+
+        let mut rng = turn_state_helpers::turn_rng(&data.game_clock, LIFE_SUPPORT_RNG_SALT);
+
+        // First, running machines wear down, and maybe break
+        let mut newly_broken = Vec::new();
+        for (ls, breakable, entity, _) in (&data.life_support, &mut data.breakable, &data.entities, !&data.broken).join() {
+            if ls.enabled && rng.gen_range(0, 100) < WEAR_CHANCE_PERC {
+                breakable.condition = breakable.condition.saturating_sub(1);
+                if breakable.condition == 0 {
+                    newly_broken.push(entity);
+                }
+            }
+        }
+
+        for entity in newly_broken {
+            data.broken
+                .insert(entity, Broken)
+                .expect("Entity should be live, since it just came up");
+        }
+
+        let airblocks: HashSet<TilePos> = (&data.has_pos, &data.blocks_airflow).join().map(|(hp, _)| hp.position).collect();
+
+        // Only open air can be worked on; walls have containers too, but nothing gets in or out of them
+        let containers: HashMap<TilePos, Entity> = (&data.has_pos, &data.oxygen_cont, &data.entities)
+            .join()
+            .filter(|(hp, _, _)| !airblocks.contains(&hp.position))
+            .map(|(hp, _, entity)| (hp.position, entity))
+            .collect();
+
+        let mut vent_networks: HashMap<usize, Vec<Entity>> = HashMap::new();
+
+        for (ls, hp, entity) in (&data.life_support, &data.has_pos, &data.entities).join() {
+            let working = ls.enabled && !data.broken.contains(entity) && power_helpers::is_powered(data.power_consumer.get(entity));
+
+            if let Some(cr) = data.char_render.get_mut(entity) {
+                cr.fg_color = machine_color(ls.kind, working);
+            }
+
+            if !working {
+                continue;
+            }
+
+            let nearby = full_neighbors(hp.position)
+                .iter()
+                .filter_map(|pos| containers.get(pos))
+                .copied()
+                .collect::<Vec<Entity>>();
+
+            match ls.kind {
+                LifeSupportKind::OxygenGenerator => generate_oxygen(&nearby, &mut data.oxygen_cont),
+                LifeSupportKind::Scrubber => scrub(&nearby, &mut data.oxygen_cont),
+                LifeSupportKind::Vent { network } => {
+                    if let Some(&container) = containers.get(&hp.position) {
+                        vent_networks.entry(network).or_default().push(container);
+                    }
+                }
+            }
+        }
+
+        for (_, vents) in vent_networks {
+            share_air(&vents, &mut data.oxygen_cont);
+        }
+    }
+}
+
+const LIFE_SUPPORT_RNG_SALT: u64 = 0x4c49_4645;
+
+// Tops up the nearby air, but never past what a healthy room should have
+fn generate_oxygen(nearby: &[Entity], oxygen_cont: &mut WriteStorage<OxygenContainer>) {
+    let mut remaining = constants::life_support::GENERATOR_OXYGEN_PER_TURN;
+
+    for &entity in nearby {
+        if let Some(oc) = oxygen_cont.get_mut(entity) {
+            let wanted = constants::oxygen::DEFAULT_FULL_OXYGEN.saturating_sub(oc.contents.oxygen);
+            let added = std::cmp::min(wanted, remaining);
+            oc.contents.oxygen += added;
+            remaining -= added;
+        }
+    }
+}
+
+fn scrub(nearby: &[Entity], oxygen_cont: &mut WriteStorage<OxygenContainer>) {
+    let mut remaining = constants::life_support::SCRUBBER_GAS_PER_TURN;
+
+    for &entity in nearby {
+        if let Some(oc) = oxygen_cont.get_mut(entity) {
+            for &gas in &[Gas::CarbonDioxide, Gas::Toxin] {
+                let amount = oc.contents.get_mut(gas);
+                let removed = std::cmp::min(*amount, remaining);
+                *amount -= removed;
+                remaining -= removed;
+            }
+        }
+    }
+}
+
+// Every vent on a network ends up with the same air (give or take rounding); nothing is gained or lost
+fn share_air(vents: &[Entity], oxygen_cont: &mut WriteStorage<OxygenContainer>) {
+    if vents.is_empty() {
+        return;
+    }
+
+    let mut total = GasMixture::default();
+    for &entity in vents {
+        if let Some(oc) = oxygen_cont.get(entity) {
+            total += oc.contents;
+        }
+    }
+
+    let count = vents.len();
+
+    for (ind, &entity) in vents.iter().enumerate() {
+        if let Some(oc) = oxygen_cont.get_mut(entity) {
+            for &gas in Gas::ALL.iter() {
+                let total = total.get(gas);
+                // the first few vents pick up the remainder
+                let extra = if ind < total % count { 1 } else { 0 };
+                *oc.contents.get_mut(gas) = total / count + extra;
+            }
+        }
+    }
+}
+
+fn machine_color(kind: LifeSupportKind, working: bool) -> Color {
+    if !working {
+        return Color {
+            r: 0.4,
+            g: 0.4,
+            b: 0.4,
+            a: 1.0,
+        };
+    }
+
+    match kind {
+        LifeSupportKind::OxygenGenerator => Color {
+            r: 0.4,
+            g: 0.7,
+            b: 1.0,
+            a: 1.0,
+        },
+        LifeSupportKind::Scrubber => Color {
+            r: 0.5,
+            g: 1.0,
+            b: 0.5,
+            a: 1.0,
+        },
+        LifeSupportKind::Vent { .. } => Color {
+            r: 0.8,
+            g: 0.8,
+            b: 0.8,
+            a: 1.0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{Builder, RunNow, World, WorldExt};
+
+    use constants::life_support::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        System::setup(&mut LifeSupportSystem, &mut world);
+        world
+    }
+
+    fn pos(x: i32) -> TilePos {
+        TilePos { x, y: 0 }
+    }
+
+    fn air(world: &mut World, x: i32, contents: GasMixture) -> Entity {
+        world
+            .create_entity()
+            .with(OxygenContainer { capacity: 1000, contents })
+            .with(HasPosition { position: pos(x) })
+            .build()
+    }
+
+    fn machine(world: &mut World, x: i32, kind: LifeSupportKind) -> Entity {
+        world
+            .create_entity()
+            .with(LifeSupport { kind, enabled: true })
+            .with(Breakable {
+                condition: MAX_CONDITION,
+                max_condition: MAX_CONDITION,
+            })
+            .with(HasPosition { position: pos(x) })
+            .build()
+    }
+
+    fn take_turn(world: &mut World) {
+        world.write_resource::<NpcMoves>().move_was_made = true;
+        LifeSupportSystem.run_now(world);
+        world.maintain();
+
+        // the next turn rolls different numbers
+        world.write_resource::<GameClock>().seconds += 6;
+    }
+
+    fn contents(world: &World, entity: Entity) -> GasMixture {
+        world.read_storage::<OxygenContainer>().get(entity).unwrap().contents
+    }
+
+    fn thin_air(oxygen: usize) -> GasMixture {
+        GasMixture {
+            oxygen,
+            ..GasMixture::station_air()
+        }
+    }
+
+    #[test]
+    fn generator_tops_up_the_air_test() {
+        let mut world = world();
+        let full = air(&mut world, 0, GasMixture::station_air());
+        let empty = air(&mut world, 1, thin_air(0));
+        machine(&mut world, 1, LifeSupportKind::OxygenGenerator);
+
+        take_turn(&mut world);
+        assert_eq!(contents(&world, full).oxygen, constants::oxygen::DEFAULT_FULL_OXYGEN);
+        assert_eq!(contents(&world, empty).oxygen, GENERATOR_OXYGEN_PER_TURN);
+
+        for _ in 0..100 {
+            take_turn(&mut world);
+        }
+        assert_eq!(contents(&world, empty).oxygen, constants::oxygen::DEFAULT_FULL_OXYGEN);
+    }
+
+    #[test]
+    fn idle_generators_do_nothing_test() {
+        let mut world = world();
+        let by_disabled = air(&mut world, 0, thin_air(0));
+        let by_broken = air(&mut world, 10, thin_air(0));
+        let by_unpowered = air(&mut world, 20, thin_air(0));
+
+        let disabled = machine(&mut world, 0, LifeSupportKind::OxygenGenerator);
+        world.write_storage::<LifeSupport>().get_mut(disabled).unwrap().enabled = false;
+
+        let broken = machine(&mut world, 10, LifeSupportKind::OxygenGenerator);
+        world.write_storage::<Broken>().insert(broken, Broken).unwrap();
+
+        let unpowered = machine(&mut world, 20, LifeSupportKind::OxygenGenerator);
+        world
+            .write_storage::<PowerConsumer>()
+            .insert(
+                unpowered,
+                PowerConsumer {
+                    load: 1,
+                    priority: PowerPriority::Normal,
+                    connected: true,
+                    powered: false,
+                },
+            )
+            .unwrap();
+
+        take_turn(&mut world);
+
+        for &entity in &[by_disabled, by_broken, by_unpowered] {
+            assert_eq!(contents(&world, entity).oxygen, 0);
+        }
+    }
+
+    #[test]
+    fn generator_doesnt_fill_walls_test() {
+        let mut world = world();
+        let wall = air(&mut world, 0, thin_air(0));
+        world.write_storage::<BlocksAirflow>().insert(wall, BlocksAirflow).unwrap();
+        machine(&mut world, 1, LifeSupportKind::OxygenGenerator);
+
+        take_turn(&mut world);
+
+        assert_eq!(contents(&world, wall).oxygen, 0);
+    }
+
+    #[test]
+    fn scrubber_cleans_the_air_test() {
+        let mut world = world();
+        let dirty = air(
+            &mut world,
+            0,
+            GasMixture {
+                carbon_dioxide: 20,
+                toxin: 50,
+                ..GasMixture::station_air()
+            },
+        );
+        machine(&mut world, 0, LifeSupportKind::Scrubber);
+
+        take_turn(&mut world);
+        let after = contents(&world, dirty);
+        assert_eq!(after.carbon_dioxide + after.toxin, 70 - SCRUBBER_GAS_PER_TURN);
+        assert_eq!(after.oxygen, constants::oxygen::DEFAULT_FULL_OXYGEN);
+
+        take_turn(&mut world);
+        take_turn(&mut world);
+        assert_eq!(contents(&world, dirty), GasMixture::station_air());
+    }
+
+    #[test]
+    fn vents_share_air_across_their_network_test() {
+        let mut world = world();
+        let first = air(&mut world, 0, thin_air(0));
+        let second = air(&mut world, 10, thin_air(101));
+        let elsewhere = air(&mut world, 20, thin_air(300));
+
+        machine(&mut world, 0, LifeSupportKind::Vent { network: 1 });
+        machine(&mut world, 10, LifeSupportKind::Vent { network: 1 });
+        machine(&mut world, 20, LifeSupportKind::Vent { network: 2 });
+
+        take_turn(&mut world);
+
+        let (first, second) = (contents(&world, first), contents(&world, second));
+        assert_eq!(first.oxygen + second.oxygen, 101);
+        assert!((first.oxygen as i64 - second.oxygen as i64).abs() <= 1);
+        assert_eq!(first.nitrogen, constants::oxygen::DEFAULT_FULL_NITROGEN);
+        assert_eq!(contents(&world, elsewhere).oxygen, 300);
+    }
+
+    #[test]
+    fn running_machines_wear_out_test() {
+        let mut world = world();
+        let scrubber = machine(&mut world, 0, LifeSupportKind::Scrubber);
+        world.write_storage::<Breakable>().get_mut(scrubber).unwrap().condition = 1;
+
+        for _ in 0..200 {
+            take_turn(&mut world);
+        }
+
+        assert!(world.read_storage::<Broken>().contains(scrubber));
+        assert_eq!(world.read_storage::<Breakable>().get(scrubber).unwrap().condition, 0);
+    }
+}
@@ -0,0 +1,252 @@
+use super::*;
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use components::*;
+use resources::{GameClock, LightMap, NpcMoves, RenderStale};
+
+use fov_helpers::{field_of_view, Occlusions};
+
+pub struct LightingSystem;
+
+#[derive(SystemData)]
+pub struct LightingSystemData<'a> {
+    has_pos: ReadStorage<'a, HasPosition>,
+    light_source: ReadStorage<'a, LightSource>,
+    equipment: ReadStorage<'a, Equipment>,
+    equippable: ReadStorage<'a, Equippable>,
+    burning: ReadStorage<'a, Burning>,
+    blocks_visibility: ReadStorage<'a, BlocksVisibility>,
+    power_consumer: ReadStorage<'a, PowerConsumer>,
+    breakable: WriteStorage<'a, Breakable>,
+    broken: WriteStorage<'a, Broken>,
+    char_render: WriteStorage<'a, CharRender>,
+    player: ReadStorage<'a, Player>,
+    entities: Entities<'a>,
+
+    light_map: Write<'a, LightMap>,
+    npc_moves: Read<'a, NpcMoves>,
+    render_stale: Read<'a, RenderStale>,
+    game_clock: Read<'a, GameClock>,
+}
+
+impl<'a> System<'a> for LightingSystem {
+    type SystemData = LightingSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        use constants::lighting::*;
+
+        if !(data.npc_moves.move_was_made || data.render_stale.0) {
+            return;
+        }
+
+        // Lights which are on slowly burn out
+        if data.npc_moves.move_was_made {
+            let mut rng = turn_state_helpers::turn_rng(&data.game_clock, LIGHTING_RNG_SALT);
+
+            let mut newly_broken = Vec::new();
+            for (light, breakable, entity, _) in (&data.light_source, &mut data.breakable, &data.entities, !&data.broken).join() {
+                if light.enabled && rng.gen_range(0, 100) < WEAR_CHANCE_PERC {
+                    breakable.condition = breakable.condition.saturating_sub(1);
+                    if breakable.condition == 0 {
+                        newly_broken.push(entity);
+                    }
+                }
+            }
+
+            for entity in newly_broken {
+                data.broken
+                    .insert(entity, Broken)
+                    .expect("Entity should be live, since it just came up");
+            }
+        }
+
+        let occlusions: Occlusions = (&data.has_pos, &data.blocks_visibility).join().map(|(hp, _)| hp.position).collect();
+
+        let mut lights: Vec<(TilePos, i32)> = Vec::new();
+
+        for (light, hp, entity) in (&data.light_source, &data.has_pos, &data.entities).join() {
+            let working = light.enabled && !data.broken.contains(entity) && power_helpers::is_powered(data.power_consumer.get(entity));
+
+            // the player carries a light of their own, but only light fixtures show whether they're working
+            if !data.player.contains(entity) {
+                if let Some(cr) = data.char_render.get_mut(entity) {
+                    cr.fg_color = light_color(working);
+                }
+            }
+
+            if working {
+                let worn = equipment_helpers::worn_modifiers(data.equipment.get(entity), &data.equippable);
+                lights.push((hp.position, light.radius + worn.light_radius));
+            }
+        }
+
+        // worn lights work just as well on someone without a light of their own
+        for (equipment, hp, _) in (&data.equipment, &data.has_pos, !&data.light_source).join() {
+            let worn = equipment_helpers::worn_modifiers(Some(equipment), &data.equippable);
+            if worn.light_radius > 0 {
+                lights.push((hp.position, worn.light_radius));
+            }
+        }
+
+        for (_, hp) in (&data.burning, &data.has_pos).join() {
+            lights.push((hp.position, FIRE_LIGHT_RADIUS));
+        }
+
+        // Where lights overlap, the brighter one wins
+        let mut levels: HashMap<TilePos, f32> = HashMap::new();
+
+        for (source_pos, radius) in lights {
+            let mut light_up = |pos: TilePos| {
+                let level = light_level(source_pos.manhattan_distance(pos), radius);
+                let existing = levels.entry(pos).or_insert(0.0);
+                if level > *existing {
+                    *existing = level;
+                }
+            };
+
+            light_up(source_pos);
+            field_of_view(source_pos, radius + 1, |pos| occlusions.contains(&pos), light_up);
+        }
+
+        data.light_map.levels = levels;
+    }
+}
+
+const LIGHTING_RNG_SALT: u64 = 0x4c49_4748;
+
+// Full brightness right at the light, fading evenly to nothing just past its radius
+fn light_level(distance: i32, radius: i32) -> f32 {
+    1.0 - (distance as f32) / ((radius + 1) as f32)
+}
+
+fn light_color(working: bool) -> Color {
+    if working {
+        Color {
+            r: 1.0,
+            g: 1.0,
+            b: 0.8,
+            a: 1.0,
+        }
+    } else {
+        Color {
+            r: 0.4,
+            g: 0.4,
+            b: 0.4,
+            a: 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{Builder, RunNow, World, WorldExt};
+
+    const ORIGIN: TilePos = TilePos { x: 0, y: 0 };
+
+    fn world() -> World {
+        let mut world = World::new();
+        System::setup(&mut LightingSystem, &mut world);
+        world
+    }
+
+    fn light(world: &mut World, radius: i32) -> Entity {
+        world
+            .create_entity()
+            .with(LightSource { radius, enabled: true })
+            .with(HasPosition { position: ORIGIN })
+            .build()
+    }
+
+    fn take_turn(world: &mut World) {
+        world.write_resource::<NpcMoves>().move_was_made = true;
+        LightingSystem.run_now(world);
+        world.maintain();
+    }
+
+    fn level_at(world: &World, x: i32) -> f32 {
+        world
+            .read_resource::<LightMap>()
+            .levels
+            .get(&TilePos { x, y: 0 })
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    #[test]
+    fn light_fades_with_distance_test() {
+        let mut world = world();
+        light(&mut world, 4);
+
+        take_turn(&mut world);
+
+        assert_eq!(1.0, level_at(&world, 0));
+        for x in 1..=4 {
+            assert!(level_at(&world, x) < level_at(&world, x - 1));
+            assert!(level_at(&world, x) > 0.0);
+        }
+        assert_eq!(0.0, level_at(&world, 5));
+        assert_eq!(0.0, level_at(&world, 10));
+    }
+
+    #[test]
+    fn unpowered_light_is_dark_test() {
+        let mut world = world();
+        let lamp = light(&mut world, 4);
+        world
+            .write_storage::<PowerConsumer>()
+            .insert(
+                lamp,
+                PowerConsumer {
+                    load: 1,
+                    priority: PowerPriority::Normal,
+                    connected: true,
+                    powered: false,
+                },
+            )
+            .unwrap();
+
+        take_turn(&mut world);
+        assert!(world.read_resource::<LightMap>().levels.is_empty());
+
+        world.write_storage::<PowerConsumer>().get_mut(lamp).unwrap().powered = true;
+        take_turn(&mut world);
+        assert_eq!(1.0, level_at(&world, 0));
+    }
+
+    #[test]
+    fn broken_or_disabled_light_is_dark_test() {
+        let mut world = world();
+        let broken = light(&mut world, 4);
+        world.write_storage::<Broken>().insert(broken, Broken).unwrap();
+        let disabled = light(&mut world, 4);
+        world.write_storage::<LightSource>().get_mut(disabled).unwrap().enabled = false;
+
+        take_turn(&mut world);
+
+        assert!(world.read_resource::<LightMap>().levels.is_empty());
+    }
+
+    #[test]
+    fn brightest_light_wins_test() {
+        let mut world = world();
+        light(&mut world, 2);
+        world
+            .create_entity()
+            .with(LightSource { radius: 8, enabled: true })
+            .with(HasPosition {
+                position: TilePos { x: 4, y: 0 },
+            })
+            .build();
+
+        take_turn(&mut world);
+
+        // the big light reaches further, but the small one is right there
+        assert_eq!(1.0, level_at(&world, 0));
+        assert_eq!(light_level(2, 8), level_at(&world, 2));
+    }
+}
@@ -9,12 +9,33 @@ pub use upkeep::{CallbackCheckerSystem, DialogueUpdateSystem, GameIsQuitCheckerS
 mod visibility;
 pub use visibility::VisibilitySystem;
 
+mod lighting;
+pub use lighting::LightingSystem;
+
 mod oxygen_spread;
 pub use oxygen_spread::OxygenSpreadSystem;
 
+mod decompression;
+pub use decompression::DecompressionSystem;
+
+mod heat_spread;
+pub use heat_spread::HeatSpreadSystem;
+
+mod fire;
+pub use fire::FireSystem;
+
+mod power;
+pub use power::PowerSystem;
+
 mod door_update;
 pub use door_update::DoorOpenSystem;
 
+mod life_support;
+pub use life_support::LifeSupportSystem;
+
+mod window_update;
+pub use window_update::WindowUpdateSystem;
+
 mod fake_space;
 pub use fake_space::FakeSpaceInserterSystem;
 
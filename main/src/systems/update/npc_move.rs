@@ -2,22 +2,42 @@ use super::*;
 
 use std::collections::HashMap;
 
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 
-use components::{BlocksMovement, HasPosition, NPC};
-use resources::NpcMoves;
+use behaviors::{NpcBehaviors, NpcKind};
+use components::*;
+use resources::{GameClock, NpcMoves, PathCache};
+
+use ai_helpers::{decide, distance, Decision, NpcView};
+use door_helpers::{carries_keycard, DoorOpener};
+use fov_helpers::{has_line_of_sight, Occlusions};
+use path_helpers::{cached_path, path_grid, PathGrid, PathGridData, PathMover};
 
 pub struct NpcMoveSystem;
 
 #[derive(SystemData)]
 pub struct NpcMoveSystemData<'a> {
-    npc: ReadStorage<'a, NPC>,
+    npc: WriteStorage<'a, NPC>,
     has_position: WriteStorage<'a, HasPosition>,
     blocks_moves: ReadStorage<'a, BlocksMovement>,
+    blocks_visibility: ReadStorage<'a, BlocksVisibility>,
+    player: ReadStorage<'a, Player>,
+    breathes: ReadStorage<'a, Breathes>,
+    oxygen_container: ReadStorage<'a, OxygenContainer>,
+    has_keycard: ReadStorage<'a, HasKeycard>,
+    inventory: ReadStorage<'a, Inventory>,
+    keycard: ReadStorage<'a, Keycard>,
     entities: Entities<'a>,
+    path_grid: PathGridData<'a>,
+
+    npc_behaviors: Read<'a, NpcBehaviors>,
+    path_cache: Write<'a, PathCache>,
+    game_clock: Read<'a, GameClock>,
     npc_moves: Read<'a, NpcMoves>,
 }
 
+const NPC_MOVE_RNG_SALT: u64 = 0x4e50_434d;
+
 impl<'a> System<'a> for NpcMoveSystem {
     type SystemData = NpcMoveSystemData<'a>;
 
@@ -26,44 +46,168 @@ impl<'a> System<'a> for NpcMoveSystem {
             return;
         }
 
-        // TODO: make this a resource
-        let mut rng = crate::rng::PalladRng::seed_from_u64(34431);
+        // routes for anyone who's died (or stopped being an NPC some other way) will never be used again
+        let (entities, npc) = (&data.entities, &data.npc);
+        data.path_cache
+            .paths
+            .retain(|&entity, _| entities.is_alive(entity) && npc.contains(entity));
 
-        let mut blocks: HashMap<TilePos, u32> = get_all_blocks(&data.has_position, &data.blocks_moves);
-        let mut choices = Vec::with_capacity(5);
+        let mut rng = turn_state_helpers::turn_rng(&data.game_clock, NPC_MOVE_RNG_SALT);
+        let turn = turn_state_helpers::current_turn(&data.game_clock);
 
-        for (_, has_position, entity) in (&data.npc, &mut data.has_position, &data.entities).join() {
-            choices.clear();
+        let positions: Vec<(Entity, TilePos)> = (&data.entities, &data.has_position)
+            .join()
+            .map(|(entity, hp)| (entity, hp.position))
+            .collect();
 
-            let pos = has_position.position;
+        let player_pos = (&data.player, &data.has_position).join().map(|(_, hp)| hp.position).next();
 
-            for _ in 0..3 {
-                choices.push(TilePos { x: 0, y: 0 });
-            }
+        let occlusions: Occlusions = (&data.has_position, &data.blocks_visibility)
+            .join()
+            .map(|(hp, _)| hp.position)
+            .collect();
 
-            maybe_note_move_choice(pos, TilePos { x: -1, y: 0 }, &mut choices, &blocks);
-            maybe_note_move_choice(pos, TilePos { x: 1, y: 0 }, &mut choices, &blocks);
-            maybe_note_move_choice(pos, TilePos { x: 0, y: -1 }, &mut choices, &blocks);
-            maybe_note_move_choice(pos, TilePos { x: 0, y: 1 }, &mut choices, &blocks);
+        let air: HashMap<TilePos, usize> = (&data.oxygen_container, &data.has_position, !&data.blocks_moves)
+            .join()
+            .map(|(container, hp, _)| (hp.position, container.contents.oxygen))
+            .collect();
 
-            let choice_ind = rng.gen_range(0, choices.len());
-            let choice = *choices.get(choice_ind).expect("Choice index should be guaranteed valid");
+        let mut blocks: HashMap<TilePos, u32> = get_all_blocks(&data.has_position, &data.blocks_moves);
+        // everyone who opens doors the same way, and breathes the same way, sees the same grid
+        let mut grids: HashMap<PathMover, PathGrid> = HashMap::new();
+
+        let movers: Vec<Entity> = (&data.npc, &data.has_position, &data.entities)
+            .join()
+            .map(|(_, _, entity)| entity)
+            .collect();
+
+        for entity in movers {
+            let pos = data.has_position.get(entity).expect("Just checked it has a position").position;
+            let NPC::Alien(ai) = *data.npc.get(entity).expect("Just checked it's an NPC");
+
+            let mover = PathMover {
+                opener: DoorOpener {
+                    is_player: false,
+                    is_alien: true,
+                    has_keycard: data.has_keycard.contains(entity) || carries_keycard(data.inventory.get(entity), &data.keycard),
+                },
+                breathes: data.breathes.contains(entity),
+            };
+            let grid = grids.entry(mover).or_insert_with(|| path_grid(&positions, &data.path_grid, mover));
+
+            let view = NpcView {
+                pos,
+                home: ai.home,
+                breath_perc: data
+                    .breathes
+                    .get(entity)
+                    .map(|breathes| breathes.contents * 100 / std::cmp::max(breathes.capacity, 1)),
+                player: player_pos.filter(|&player| can_perceive(pos, player, &occlusions)),
+                best_air: best_air_near(pos, &air),
+            };
+
+            let (decision, state) = decide(data.npc_behaviors.behaviors_for(NpcKind::Alien), &view, ai.state, grid);
+
+            let next = match decision {
+                Decision::Stay => None,
+                Decision::Wander => wander_step(pos, &blocks, &mut rng),
+                Decision::MoveTo(goal) => first_step(cached_path(&mut data.path_cache, entity, pos, goal, grid, turn)),
+                Decision::Approach(target) => approach_step(&mut data.path_cache, entity, pos, target, grid, turn),
+                Decision::StepAwayFrom(threat) => step_away(pos, threat, &blocks),
+            };
+
+            // whatever's in the way now may not have been when the route was planned
+            let next = next.filter(|next| *blocks.get(next).unwrap_or(&0) == 0);
+
+            if let Some(next) = next {
+                if data.blocks_moves.contains(entity) {
+                    *blocks.get_mut(&pos).expect("Should be an entry at position since this blocks") -= 1;
+                    *blocks.entry(next).or_insert(0) += 1;
+                }
+
+                data.has_position.get_mut(entity).expect("Just checked it has a position").position = next;
+            }
 
-            if data.blocks_moves.get(entity).is_some() {
-                *blocks.get_mut(&pos).expect("Should be an entry at position since this blocks") -= 1;
-                *blocks.entry(pos + choice).or_insert(0) += 1;
+            if let Some(NPC::Alien(ai)) = data.npc.get_mut(entity) {
+                ai.state = state;
             }
+        }
+    }
+}
+
+// Close enough, and nothing in the way; NPCs don't need the lights on to notice someone
+fn can_perceive(pos: TilePos, target: TilePos, occlusions: &Occlusions) -> bool {
+    let range = constants::ai::PERCEPTION_RANGE;
+
+    distance(pos, target) <= range && has_line_of_sight(pos, target, range + 1, |at| occlusions.contains(&at))
+}
+
+// Staying put is as likely as any three steps, so wandering NPCs don't look too restless
+fn wander_step(pos: TilePos, blocks: &HashMap<TilePos, u32>, rng: &mut impl Rng) -> Option<TilePos> {
+    let mut choices = vec![None; 3];
 
-            has_position.position += choice;
+    for &next in direct_neighbors(pos)[1..].iter() {
+        if *blocks.get(&next).unwrap_or(&0) == 0 {
+            choices.push(Some(next));
         }
     }
+
+    choices[rng.gen_range(0, choices.len())]
+}
+
+fn first_step(path: Option<Vec<TilePos>>) -> Option<TilePos> {
+    path.and_then(|steps| steps.first().copied())
 }
 
-fn maybe_note_move_choice(position: TilePos, change: TilePos, choices_vec: &mut Vec<TilePos>, blocks: &HashMap<TilePos, u32>) {
-    let new_pos = position + change;
-    if *blocks.get(&new_pos).unwrap_or(&0) == 0 {
-        choices_vec.push(change);
+// Whatever is being approached is standing on its tile, so plan as though it weren't, and stop
+// short of it
+fn approach_step(cache: &mut PathCache, entity: Entity, pos: TilePos, target: TilePos, grid: &PathGrid, turn: u64) -> Option<TilePos> {
+    let mut grid = grid.clone();
+    grid.set_cost(target, constants::pathing::STEP_COST);
+
+    first_step(cached_path(cache, entity, pos, target, &grid, turn)).filter(|&next| next != target)
+}
+
+fn step_away(pos: TilePos, threat: TilePos, blocks: &HashMap<TilePos, u32>) -> Option<TilePos> {
+    direct_neighbors(pos)[1..]
+        .iter()
+        .copied()
+        .filter(|next| *blocks.get(next).unwrap_or(&0) == 0)
+        .filter(|&next| distance(next, threat) > distance(pos, threat))
+        .max_by_key(|&next| distance(next, threat))
+}
+
+// The airiest tile in reach, preferring closer ones; nowhere, if it's no better than right here
+fn best_air_near(pos: TilePos, air: &HashMap<TilePos, usize>) -> Option<TilePos> {
+    let range = constants::ai::SEEK_AIR_RANGE;
+    let here = air.get(&pos).copied().unwrap_or(0);
+
+    let mut best: Option<(usize, i32, TilePos)> = None;
+
+    for dx in -range..=range {
+        for dy in -range..=range {
+            let candidate = TilePos {
+                x: pos.x + dx,
+                y: pos.y + dy,
+            };
+            let dist = distance(pos, candidate);
+            if dist > range {
+                continue;
+            }
+
+            if let Some(&oxygen) = air.get(&candidate) {
+                let better = match best {
+                    None => true,
+                    Some((best_oxygen, best_dist, _)) => oxygen > best_oxygen || (oxygen == best_oxygen && dist < best_dist),
+                };
+                if better {
+                    best = Some((oxygen, dist, candidate));
+                }
+            }
+        }
     }
+
+    best.filter(|&(oxygen, _, _)| oxygen > here).map(|(_, _, candidate)| candidate)
 }
 
 fn get_all_blocks<'a>(
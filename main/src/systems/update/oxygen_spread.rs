@@ -1,8 +1,11 @@
 use super::*;
 
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{HashMap, HashSet};
 
-use components::{BlocksAirflow, HasPosition, OxygenContainer, Vacuum};
+use specs::Entity;
+
+use components::{BlocksAirflow, Gas, GasMixture, HasPosition, OxygenContainer, Vacuum};
+use diffusion_helpers::{Cell, Grid};
 use resources::NpcMoves;
 
 pub struct OxygenSpreadSystem;
@@ -13,35 +16,11 @@ pub struct OxygenSpreadSystemData<'a> {
     oxygen_cont: WriteStorage<'a, OxygenContainer>,
     vacuums: ReadStorage<'a, Vacuum>,
     blocks_airflow: ReadStorage<'a, BlocksAirflow>,
+    entities: Entities<'a>,
 
     npc_moves: Read<'a, NpcMoves>,
 }
 
-struct OxygenTaker<'b> {
-    container: &'b mut OxygenContainer,
-    has_pos: &'b HasPosition,
-}
-
-impl<'b> Ord for OxygenTaker<'b> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.container.contents.cmp(&other.container.contents).reverse()
-    }
-}
-
-impl<'b> PartialOrd for OxygenTaker<'b> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.container.contents.cmp(&other.container.contents).reverse())
-    }
-}
-
-impl<'b> Eq for OxygenTaker<'b> {}
-
-impl<'b> PartialEq for OxygenTaker<'b> {
-    fn eq(&self, other: &Self) -> bool {
-        self.container.contents == other.container.contents
-    }
-}
-
 impl<'a> System<'a> for OxygenSpreadSystem {
     type SystemData = OxygenSpreadSystemData<'a>;
 
@@ -65,100 +44,162 @@ impl<'a> System<'a> for OxygenSpreadSystem {
                 .collect::<HashSet<TilePos>>()
         });
 
-        let containers = timed!("Computing container locations", {
-            (&data.has_pos, &data.oxygen_cont)
-                .join()
-                .map(|(hp, _)| hp.position)
-                .filter(|pos| !airblocks.contains(pos))
-                .collect::<HashSet<TilePos>>()
-        });
-
         // First, all vacuums must vent their air into space
         for (ox, _, _) in (&mut data.oxygen_cont, &data.vacuums, !&data.blocks_airflow).join() {
-            ox.contents = 0;
+            ox.contents = GasMixture::default();
         }
 
-        // Then; if a position is adjacent to a vacuum, all oxygen "shared" with that position will
-        // just disappear (into the vacuum in theory, although it just goes into space; vacuums never
-        // accumulate oxygen)
-        let pos_should_vent = |pos| is_vacuum_adjacent(pos, &airblocks, &vacuums, &containers);
+        // Every square which can hold air, with the containers in it; if there are several, the
+        // first one holds all the air for the square once the spread is done
+        let mut containers: HashMap<TilePos, Vec<Entity>> = HashMap::new();
+        for (hp, _, ent) in (&data.has_pos, &data.oxygen_cont, &data.entities).join() {
+            if !airblocks.contains(&hp.position) && !vacuums.contains(&hp.position) {
+                containers.entry(hp.position).or_default().push(ent);
+            }
+        }
 
-        // We do several small iterations per timestep, which smooths out the airflow
-        for _ in 0..constants::oxygen::OXYGEN_SYSTEM_ITERATIONS {
-            let mut oxygen_taker_queue = BinaryHeap::new();
-            let mut oxygen_sharing = HashMap::new();
+        if containers.is_empty() {
+            return;
+        }
 
-            // First, each oxygen container (which isn't already a vacuum) "shares" a small portion of
-            // its available oxygen into a pot which anything adjacent can reach
-            for (mut giver_oxygen, has_pos, _) in (&mut data.oxygen_cont, &data.has_pos, !&data.vacuums)
-                .join()
-                .filter(|(_, hp, _)| !airblocks.contains(&hp.position))
-            {
-                if giver_oxygen.contents > 0 {
-                    let reduction = std::cmp::min(giver_oxygen.contents, constants::oxygen::OXYGEN_SYSTEM_SHARE_PER_ITERATION);
-                    giver_oxygen.contents -= reduction;
-
-                    // If it's adjacent to a vacuum that "shared" oxygen just goes away
-                    if !pos_should_vent(has_pos.position) {
-                        *oxygen_sharing.entry(has_pos.position).or_insert(0) += reduction;
-                    }
-                }
+        // Anything outside this box has no air and is open to space. The grid leaks out of its
+        // edges, so the box reaches one square past the air on every side; that way only squares
+        // which really are open to space (not the walls around the station) soak anything up.
+        let x_min = containers.keys().map(|pos| pos.x).min().unwrap() - 1;
+        let y_min = containers.keys().map(|pos| pos.y).min().unwrap() - 1;
+        let x_max = containers.keys().map(|pos| pos.x).max().unwrap() + 1;
+        let y_max = containers.keys().map(|pos| pos.y).max().unwrap() + 1;
 
-                oxygen_taker_queue.push(OxygenTaker {
-                    container: giver_oxygen,
-                    has_pos,
-                });
-            }
+        let width = (x_max - x_min + 1) as usize;
+        let height = (y_max - y_min + 1) as usize;
 
-            // Then, each container (in priority order based on need, that is, low oxygen) "takes" one
-            // unit of oxygen from an adjacent square, if available, then goes back on the queue. A container
-            // pops off the queue when either it's full, or there is nothing adjacent to take.
+        for &gas in Gas::ALL.iter() {
+            let mut grid = Grid::new(width, height);
 
-            // Worst case this is O(SHARING_PER_ITERATION * Nlog(N)), where N is number of oxygen containers,
-            // since we use a min heap for priority queue
-            loop {
-                let taker = oxygen_taker_queue.pop();
-                if taker.is_none() {
-                    break;
+            for &pos in airblocks.iter() {
+                if x_min <= pos.x && pos.x <= x_max && y_min <= pos.y && pos.y <= y_max {
+                    grid.set((pos.x - x_min) as usize, (pos.y - y_min) as usize, Cell::Blocked);
                 }
-                let taker = taker.unwrap();
-
-                for neighbor_pos in full_neighbors(taker.has_pos.position).iter() {
-                    let neighbor_capacity = oxygen_sharing.get_mut(neighbor_pos);
-                    if neighbor_capacity.is_none() {
-                        continue;
-                    }
+            }
 
-                    let neighbor_capacity = neighbor_capacity.unwrap();
-                    *neighbor_capacity -= 1;
-                    taker.container.contents += 1;
+            for (pos, ents) in containers.iter() {
+                let amount = ents.iter().map(|&ent| data.oxygen_cont.get(ent).unwrap().contents.get(gas)).sum();
+                grid.set((pos.x - x_min) as usize, (pos.y - y_min) as usize, Cell::Air(amount));
+            }
 
-                    if *neighbor_capacity == 0 {
-                        oxygen_sharing.remove(&neighbor_pos);
-                    }
+            // We do several small iterations per timestep, which smooths out the airflow
+            for _ in 0..constants::oxygen::OXYGEN_SYSTEM_ITERATIONS {
+                grid.diffuse(constants::oxygen::DIFFUSION_DIVISOR);
+            }
 
-                    if taker.container.contents < taker.container.capacity {
-                        oxygen_taker_queue.push(taker);
-                    }
+            for (pos, ents) in containers.iter() {
+                let amount = match grid.get((pos.x - x_min) as usize, (pos.y - y_min) as usize) {
+                    Cell::Air(amount) => amount,
+                    other => unreachable!("Container square should hold air, but was {:?}", other),
+                };
 
-                    break;
+                for (i, &ent) in ents.iter().enumerate() {
+                    *data.oxygen_cont.get_mut(ent).unwrap().contents.get_mut(gas) = if i == 0 { amount } else { 0 };
                 }
             }
         }
     }
 }
 
-fn is_vacuum_adjacent(pos: TilePos, blocks: &HashSet<TilePos>, known_vacuums: &HashSet<TilePos>, containers: &HashSet<TilePos>) -> bool {
-    for neighbor_pos in full_neighbors(pos).iter() {
-        if blocks.contains(neighbor_pos) {
-            continue;
-        }
-        if known_vacuums.contains(neighbor_pos) {
-            return true;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{Builder, RunNow, World, WorldExt};
+
+    // '#' is a wall, '.' is floor, '+' is floor with a hole in the hull and anything else is open
+    // space; each floor square starts out with a different mix of gases
+    fn station(rows: &[&str]) -> World {
+        let mut world = World::new();
+        System::setup(&mut OxygenSpreadSystem, &mut world);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let position = TilePos { x: x as i32, y: y as i32 };
+                let contents = GasMixture {
+                    oxygen: 10 * x,
+                    carbon_dioxide: 7 * y,
+                    nitrogen: 100 + 3 * x * y,
+                    toxin: if x == 2 && y == 2 { 50 } else { 0 },
+                };
+                let container = OxygenContainer { capacity: 1000, contents };
+
+                let builder = match c {
+                    '#' => world
+                        .create_entity()
+                        .with(OxygenContainer {
+                            capacity: 1000,
+                            contents: GasMixture::default(),
+                        })
+                        .with(BlocksAirflow),
+                    '.' => world.create_entity().with(container),
+                    '+' => world.create_entity().with(container).with(Vacuum),
+                    _ => continue,
+                };
+
+                builder.with(HasPosition { position }).build();
+            }
         }
-        if !containers.contains(neighbor_pos) {
-            return true;
+
+        world
+    }
+
+    fn total(world: &World, gas: Gas) -> usize {
+        world
+            .read_storage::<OxygenContainer>()
+            .join()
+            .map(|container| container.contents.get(gas))
+            .sum()
+    }
+
+    #[test]
+    fn sealed_room_keeps_its_air_test() {
+        let mut world = station(&[
+            "      ", //
+            " #####", //
+            " #...#", //
+            " #...#", //
+            " #####", //
+        ]);
+        let before: Vec<usize> = Gas::ALL.iter().map(|&gas| total(&world, gas)).collect();
+
+        for _ in 0..5 {
+            OxygenSpreadSystem.run_now(&world);
+            world.maintain();
+
+            let after: Vec<usize> = Gas::ALL.iter().map(|&gas| total(&world, gas)).collect();
+            assert_eq!(before, after);
         }
+
+        // and it did actually move around; the toxin started out all in one corner
+        let toxin_at = |x, y| {
+            (&world.read_storage::<HasPosition>(), &world.read_storage::<OxygenContainer>())
+                .join()
+                .find(|(hp, _)| hp.position == TilePos { x, y })
+                .map(|(_, container)| container.contents.toxin)
+                .unwrap()
+        };
+        assert!(toxin_at(3, 2) > 0);
+        assert!(toxin_at(2, 2) < 50);
+    }
+
+    #[test]
+    fn hole_in_the_hull_leaks_test() {
+        let mut world = station(&[
+            "#####", //
+            "#..+#", //
+            "#####", //
+        ]);
+        let before = total(&world, Gas::Nitrogen);
+
+        OxygenSpreadSystem.run_now(&world);
+        world.maintain();
+
+        assert!(total(&world, Gas::Nitrogen) < before);
     }
-    false
 }
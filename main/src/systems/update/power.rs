@@ -0,0 +1,99 @@
+use super::*;
+
+use std::collections::{HashMap, HashSet};
+
+use components::*;
+use resources::NpcMoves;
+
+pub struct PowerSystem;
+
+#[derive(SystemData)]
+pub struct PowerSystemData<'a> {
+    has_pos: ReadStorage<'a, HasPosition>,
+    conduit: ReadStorage<'a, Conduit>,
+    generator: ReadStorage<'a, PowerGenerator>,
+    consumer: WriteStorage<'a, PowerConsumer>,
+    broken: ReadStorage<'a, Broken>,
+    char_render: WriteStorage<'a, CharRender>,
+    entities: Entities<'a>,
+
+    npc_moves: Read<'a, NpcMoves>,
+}
+
+impl<'a> System<'a> for PowerSystem {
+    type SystemData = PowerSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        if !data.npc_moves.move_was_made {
+            return;
+        }
+
+        let conduits: HashSet<TilePos> = (&data.has_pos, &data.conduit).join().map(|(hp, _)| hp.position).collect();
+        let networks = timed!("Computing power networks", { power_helpers::find_networks(&conduits) });
+
+        // First, total up what each network has to give
+        let mut supply: HashMap<usize, usize> = HashMap::new();
+
+        for (generator, hp, entity) in (&data.generator, &data.has_pos, &data.entities).join() {
+            let working = generator.enabled && !data.broken.contains(entity);
+
+            if let Some(cr) = data.char_render.get_mut(entity) {
+                cr.fg_color = generator_color(working);
+            }
+
+            if let (true, Some(&network)) = (working, networks.get(&hp.position)) {
+                *supply.entry(network).or_default() += generator.output;
+            }
+        }
+
+        // Then hand it out to everything hooked up to the same network
+        let mut demands: HashMap<usize, Vec<Entity>> = HashMap::new();
+
+        for (consumer, hp, entity) in (&mut data.consumer, &data.has_pos, &data.entities).join() {
+            consumer.powered = false;
+
+            if consumer.connected {
+                if let Some(&network) = networks.get(&hp.position) {
+                    demands.entry(network).or_default().push(entity);
+                }
+            }
+        }
+
+        for (network, mut entities) in demands {
+            // so ties in priority are broken the same way every time
+            entities.sort_by_key(|entity| entity.id());
+
+            let loads: Vec<(PowerPriority, usize)> = entities
+                .iter()
+                .map(|&entity| {
+                    let consumer = data.consumer.get(entity).unwrap();
+                    (consumer.priority, consumer.load)
+                })
+                .collect();
+
+            let powered = power_helpers::allocate_power(supply.get(&network).copied().unwrap_or(0), &loads);
+
+            for (entity, powered) in entities.into_iter().zip(powered) {
+                data.consumer.get_mut(entity).unwrap().powered = powered;
+            }
+        }
+    }
+}
+
+fn generator_color(working: bool) -> Color {
+    if working {
+        Color {
+            r: 1.0,
+            g: 0.9,
+            b: 0.3,
+            a: 1.0,
+        }
+    } else {
+        Color {
+            r: 0.4,
+            g: 0.4,
+            b: 0.4,
+            a: 1.0,
+        }
+    }
+}
@@ -201,16 +201,46 @@ serde_components! (
         player: Player,
         oc: OxygenContainer,
         breathes: Breathes,
+        temperature: Temperature,
+        insulated: Insulated,
+        heat_source: HeatSource,
+        flammable: Flammable,
+        burning: Burning,
         cs: CanSuffocate,
+        corpse: Corpse,
+        readable: Readable,
         vacuum: Vacuum,
         door: Door,
+        has_keycard: HasKeycard,
+        window: Window,
+        life_support: LifeSupport,
+        network_device: NetworkDevice,
+        terminal: Terminal,
+        security_camera: SecurityCamera,
+        breakable: Breakable,
+        broken: Broken,
+        repairable: Repairable,
+        spare_parts: SpareParts,
+        power_generator: PowerGenerator,
+        conduit: Conduit,
+        power_consumer: PowerConsumer,
+        light_source: LightSource,
         od: OpensDoors,
+        pushable: Pushable,
+        item: Item,
+        keycard: Keycard,
+        inventory: Inventory,
+        equippable: Equippable,
+        equipment: Equipment,
+        oxygen_tank: OxygenTank,
         camera: Camera,
         npc: NPC,
         talkable: Talkable,
     ]
 );
 
+// The codex and the player's skills are left out on purpose: they're the player's progress across
+// loops, and loading is how a loop resets, so they mustn't be rolled back along with the world
 serde_resources! (
     SerializeResourcesM, DeserializeResourcesM,
     resources: [
@@ -263,6 +293,10 @@ pub struct DeserializeSystemData<'a> {
     saves: Write<'a, SavedStates>,
     callbacks: Write<'a, Callbacks>,
     render_stale: Write<'a, RenderStale>,
+    // entities don't survive a reload, so neither can anything the player was in the middle of
+    ongoing_action: Write<'a, OngoingPlayerAction>,
+    // same with any routes planned for them
+    path_cache: Write<'a, PathCache>,
 }
 
 impl<'a> System<'a> for DeserializeSystem {
@@ -299,7 +333,133 @@ impl<'a> System<'a> for DeserializeSystem {
                 // eprintln!("Load requested, but no save game data is present");
             }
 
+            data.ongoing_action.current = None;
+            data.path_cache.paths.clear();
             data.render_stale.0 = true;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{saveload::MarkedBuilder, Builder, RunNow, World, WorldExt};
+
+    use skills::{ExpGain, SkillAddition, SkillTree};
+
+    fn world() -> World {
+        let mut world = World::new();
+        System::setup(&mut SerializeSystem, &mut world);
+        System::setup(&mut DeserializeSystem, &mut world);
+
+        let skills = SkillTree::new()
+            .with_skill(SkillAddition {
+                name: "Hacking".to_string(),
+                parents: Vec::new(),
+            })
+            .unwrap();
+        world.insert(skills);
+
+        world
+    }
+
+    fn gain_exp(world: &mut World, exp: usize) {
+        world
+            .write_resource::<SkillTree>()
+            .gain_experience(ExpGain {
+                exp,
+                skill_name: "Hacking",
+            })
+            .unwrap();
+    }
+
+    fn save(world: &mut World) {
+        world.write_resource::<Callbacks>().push(Callback::SaveGame);
+        SerializeSystem.run_now(world);
+        world.maintain();
+    }
+
+    fn load(world: &mut World) {
+        world.write_resource::<Callbacks>().push(Callback::LoadGame);
+        DeserializeSystem.run_now(world);
+        world.maintain();
+    }
+
+    #[test]
+    fn exp_survives_loading_test() {
+        let mut world = world();
+        gain_exp(&mut world, 10);
+        let saved_at = world.read_resource::<GameClock>().total_seconds();
+        save(&mut world);
+
+        gain_exp(&mut world, 25);
+        world.write_resource::<GameClock>().seconds += 60;
+        load(&mut world);
+
+        // the world goes back to how it was, but the player keeps what they learned
+        assert_eq!(saved_at, world.read_resource::<GameClock>().total_seconds());
+        assert_eq!(Ok(35), world.read_resource::<SkillTree>().check_experience("Hacking"));
+    }
+
+    fn name_of(world: &World, entity: Entity) -> String {
+        world.read_storage::<Item>().get(entity).unwrap().name.clone()
+    }
+
+    #[test]
+    fn inventory_survives_saving_test() {
+        let mut world = world();
+
+        let mut item = |name: &str| {
+            world
+                .create_entity()
+                .marked::<SaveComponent>()
+                .with(Item { name: name.to_string() })
+                .build()
+        };
+        let wrench = item("Wrench");
+        let helmet = item("Helmet");
+
+        world
+            .create_entity()
+            .marked::<SaveComponent>()
+            .with(Player {})
+            .with(Inventory {
+                items: vec![wrench, helmet],
+                capacity: 3,
+            })
+            .with(Equipment {
+                head: Some(helmet),
+                ..Equipment::default()
+            })
+            .build();
+
+        save(&mut world);
+
+        // a fresh world, so every entity has to be matched up again through the save markers
+        let mut loaded = self::world();
+        // and none of them end up with their old ids by coincidence
+        for _ in 0..5 {
+            loaded.create_entity().build();
+        }
+        *loaded.write_resource::<SavedStates>() = (*world.read_resource::<SavedStates>()).clone();
+        load(&mut loaded);
+
+        let (inventory, equipment) = {
+            let player = loaded.read_storage::<Player>();
+            let inventory = loaded.read_storage::<Inventory>();
+            let equipment = loaded.read_storage::<Equipment>();
+            let (_, inventory, equipment) = (&player, &inventory, &equipment)
+                .join()
+                .next()
+                .expect("The player should be loaded");
+            (inventory.clone(), *equipment)
+        };
+
+        assert_eq!(3, inventory.capacity);
+        let names: Vec<String> = inventory.items.iter().map(|&item| name_of(&loaded, item)).collect();
+        assert_eq!(vec!["Wrench", "Helmet"], names);
+        assert_eq!("Helmet", name_of(&loaded, equipment.head.unwrap()));
+        assert_eq!(None, equipment.body);
+    }
+}
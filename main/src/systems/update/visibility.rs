@@ -1,17 +1,15 @@
-//! System for managing the visibility of objects
-//!
-//! Shamelessly taken from http://journal.stuffwithstuff.com/2015/09/07/what-the-hero-sees/
-//! which is a completely wonderful article
+//! System for managing the visibility of objects; the player is just one observer, and any
+//! cameras they've tapped into are others
 
 use super::*;
 
 use std::cmp::max;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
-use components::{BlocksVisibility, HasPosition, Player, Visible};
-use resources::{NpcMoves, RenderStale};
+use components::{BlocksVisibility, Broken, HasPosition, Player, PowerConsumer, SecurityCamera, Visible};
+use resources::{LightMap, NpcMoves, RenderStale};
 
-use numerics::Float;
+use fov_helpers::{compute_fov, Occlusions};
 use world::{TilePos, VisibilityType};
 
 #[derive(SystemData)]
@@ -20,7 +18,12 @@ pub struct VisibilitySystemData<'a> {
     has_position: ReadStorage<'a, HasPosition>,
     blocks_visibility: ReadStorage<'a, BlocksVisibility>,
     visible: WriteStorage<'a, Visible>,
+    security_camera: ReadStorage<'a, SecurityCamera>,
+    power_consumer: ReadStorage<'a, PowerConsumer>,
+    broken: ReadStorage<'a, Broken>,
+    entities: Entities<'a>,
 
+    light_map: Read<'a, LightMap>,
     npc_moves: Read<'a, NpcMoves>,
     render_stale: Read<'a, RenderStale>,
 }
@@ -30,7 +33,6 @@ pub struct VisibilitySystem;
 // We do use a hashmap, but the early stopping rules
 // technically populating the hashmap doesn't seem ideal, but we can cache later (and really should)
 type Visibles<'a> = HashMap<TilePos, Vec<&'a mut Visible>>; // pos -> 1 or more entities at that position
-type Occlusions = HashSet<TilePos>;
 
 impl<'a> System<'a> for VisibilitySystem {
     type SystemData = VisibilitySystemData<'a>;
@@ -63,17 +65,39 @@ impl<'a> System<'a> for VisibilitySystem {
 
         max_range = std::cmp::min(max_range, 30);
 
+        // NB: for the player, this range MUST cover the entire map (up to the cap), or things which are
+        // just too far away would never be marked unseen
         // NB: this range is worst case, but if we hit full shadow, we can stop early
         // the only time we'd actually hit this max range is if we never get to full shadow ("we can see forever")
         // which is not a good state for this algorithm :dusty-stick:
-        refresh_visibility(player_pos, &mut visibles, max_range, &occlusions);
+        let mut observers = vec![(player_pos, max_range, VisibilityType::CurrentlyVisible)];
+
+        // Any camera the player has tapped into sees on their behalf, as long as it's working
+        for (camera, hp, entity) in (&data.security_camera, &data.has_position, &data.entities).join() {
+            if camera.feed_tapped && power_helpers::is_powered(data.power_consumer.get(entity)) && !data.broken.contains(entity) {
+                observers.push((hp.position, camera.range, VisibilityType::SeenThroughFeed));
+            }
+        }
+
+        // Observers can always see themselves, it just helps sanity; anything else has to be lit well enough to make out
+        let light_map = &data.light_map;
+        for (observer_pos, range, visibility) in observers {
+            for pos in compute_fov(observer_pos, range, |pos| occlusions.contains(&pos)) {
+                if pos == observer_pos || light_map.level(pos) >= constants::lighting::MIN_VISIBLE_LIGHT {
+                    mark_visible(&mut visibles, pos, visibility);
+                }
+            }
+        }
     }
 }
 
-fn mark_visible<'a>(visibles: &mut Visibles<'a>, pos: TilePos) {
+// Seeing something directly beats seeing it on a feed, so this never downgrades anything
+fn mark_visible<'a>(visibles: &mut Visibles<'a>, pos: TilePos, visibility: VisibilityType) {
     if let Some(stuff) = visibles.get_mut(&pos) {
         for mut thing in stuff {
-            thing.visibility = VisibilityType::CurrentlyVisible;
+            if thing.visibility != VisibilityType::CurrentlyVisible {
+                thing.visibility = visibility;
+            }
         }
     }
 }
@@ -82,6 +106,7 @@ fn cannot_see(vis: &mut Visible) {
     if vis.memorable {
         vis.visibility = match vis.visibility {
             VisibilityType::CurrentlyVisible => VisibilityType::Remembered,
+            VisibilityType::SeenThroughFeed => VisibilityType::Remembered,
             VisibilityType::Remembered => VisibilityType::Remembered,
             VisibilityType::NotSeen => VisibilityType::NotSeen,
         };
@@ -90,163 +115,112 @@ fn cannot_see(vis: &mut Visible) {
     }
 }
 
-fn refresh_visibility<'a>(observer_pos: TilePos, visibles: &mut Visibles<'a>, vis_range: i32, occlusions: &Occlusions) {
-    // You can always see yourself, it just helps sanity
-    mark_visible(visibles, observer_pos);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for octant in 0..8 {
-        refresh_octant_vis(observer_pos, octant, visibles, vis_range, occlusions);
-    }
-}
-
-// NB: vis_range MUST include the entire map! Or we will never set the edge of vision to be invisible
-//      in the case where you can no longer see something because it's just too far away
-// PRE: all visibles have been marked "can't currently see"; so there is no need to mark things as invisible.
-//      This helps with early stopping.
-fn refresh_octant_vis<'a>(observer_pos: TilePos, octant: usize, visibles: &mut Visibles<'a>, vis_range: i32, occlusions: &Occlusions) {
-    let mut line = ShadowLine::default();
+    use specs::{Builder, RunNow, World, WorldExt};
 
-    for row in 1..vis_range {
-        let mut all_occluded = true;
+    use components::PowerPriority;
 
-        for col in 0..=row {
-            let pos = observer_pos + transform_octant(row, col, octant);
+    // A lit corridor along y=0, with the player at one end and a wall between them and the far end
+    fn corridor() -> World {
+        let mut world = World::new();
+        System::setup(&mut VisibilitySystem, &mut world);
 
-            let projection = project_tile(row, col);
-
-            if line.is_in_shadow(projection) {
-                continue;
+        for x in 0..=20 {
+            let position = TilePos { x, y: 0 };
+            world.write_resource::<LightMap>().levels.insert(position, 1.0);
+            let mut builder = world.create_entity().with(HasPosition { position }).with(Visible {
+                visibility: VisibilityType::NotSeen,
+                memorable: false,
+            });
+            if x == 5 {
+                builder = builder.with(BlocksVisibility);
             }
+            builder.build();
+        }
 
-            all_occluded = false;
-            mark_visible(visibles, pos);
+        world
+            .create_entity()
+            .with(Player {})
+            .with(HasPosition {
+                position: TilePos { x: 0, y: 0 },
+            })
+            .build();
 
-            if occlusions.contains(&pos) {
-                line.add_shadow(projection);
-            }
-        }
+        world
+    }
 
-        if all_occluded {
-            break;
-        }
+    fn camera(world: &mut World, feed_tapped: bool) -> Entity {
+        world
+            .create_entity()
+            .with(SecurityCamera { range: 5, feed_tapped })
+            .with(HasPosition {
+                position: TilePos { x: 15, y: 0 },
+            })
+            .build()
     }
-}
 
-#[derive(Copy, Clone, Debug)]
-struct Shadow {
-    start: Float,
-    end: Float,
-}
+    fn take_turn(world: &mut World) {
+        world.write_resource::<NpcMoves>().move_was_made = true;
+        VisibilitySystem.run_now(world);
+        world.maintain();
+    }
 
-impl Shadow {
-    fn contains(self, other: Shadow) -> bool {
-        self.start <= other.start && self.end >= other.end
+    fn visibility_at(world: &World, x: i32) -> VisibilityType {
+        (&world.read_storage::<HasPosition>(), &world.read_storage::<Visible>())
+            .join()
+            .find(|(hp, _)| hp.position == TilePos { x, y: 0 })
+            .map(|(_, vis)| vis.visibility)
+            .unwrap()
     }
-}
 
-// only refers to a single octant (!)
-#[derive(Default, Debug)]
-struct ShadowLine {
-    shadows: Vec<Shadow>,
-}
+    #[test]
+    fn untapped_camera_shows_nothing_test() {
+        let mut world = corridor();
+        camera(&mut world, false);
 
-impl ShadowLine {
-    fn is_in_shadow(&self, projection: Shadow) -> bool {
-        self.shadows.iter().any(|shadow| shadow.contains(projection))
-    }
+        take_turn(&mut world);
 
-    // precondition: shadow is a nontrivial addition
-    fn add_shadow(&mut self, to_insert: Shadow) {
-        let shadow_len = self.shadows.len();
+        assert_eq!(VisibilityType::CurrentlyVisible, visibility_at(&world, 4));
+        assert_eq!(VisibilityType::NotSeen, visibility_at(&world, 15));
+    }
 
-        let mut index = 0;
+    #[test]
+    fn tapped_camera_shows_its_view_test() {
+        let mut world = corridor();
+        camera(&mut world, true);
 
-        // linear search for the insertion point; list tends to be very short
-        // so that index is the first where to_insert.start <= shadows[index].start
-        while index < shadow_len && self.shadows[index].start < to_insert.start {
-            index += 1;
-        }
+        take_turn(&mut world);
 
-        // NB: because of the iteration order (doing individual squares, and doing closer
-        // rows before later ones), we actually never need to unify more than two shadows
-        // because this square will always fit in the union of two adjacent shadows
-        let overlaps_previous = index > 0 && self.shadows[index - 1].end >= to_insert.start;
-        let overlaps_next = index < shadow_len && self.shadows[index].start <= to_insert.end;
-
-        if overlaps_next {
-            if overlaps_previous {
-                // Then we need to unify prev and next into one shadow
-                self.shadows[index - 1].end = self.shadows[index].end;
-                self.shadows.remove(index);
-            } else {
-                self.shadows[index].start = to_insert.start;
-            }
-        } else {
-            if overlaps_previous {
-                self.shadows[index - 1].end = to_insert.end;
-            } else {
-                self.shadows.insert(index, to_insert);
-            }
+        assert_eq!(VisibilityType::CurrentlyVisible, visibility_at(&world, 4));
+        for x in 11..=19 {
+            assert_eq!(VisibilityType::SeenThroughFeed, visibility_at(&world, x), "at x={}", x);
         }
+        // the wall is in the way, and the rest is out of the camera's range
+        assert_eq!(VisibilityType::NotSeen, visibility_at(&world, 8));
     }
-}
 
-/// Computes a shadow of a given occlusive block at (row, col)
-/// (which is a relative position to the observer). Assumes the
-/// thing has already been transformed by octant, so is "in the
-/// zeroth octant"
-///
-/// Params:
-/// * rel_pos: position relative to the observer's position
-///
-/// Precondition: row >= col (N/NE octant), row >= 1
-fn project_tile(row: i32, col: i32) -> Shadow {
-    // NB: here, row means "up" (so 3 means 3 tiles north)
-    // whereas typically that direction would be negative
-    // don't worry about that
-    let (row, col) = (row as f32, col as f32);
-
-    // slope to the top-left corner of the blocking square
-    let top_left = col / (row + 2.0);
-    // slope to the bottom-right corner of the blocking square
-    let bottom_right = (col + 1.0) / (row + 1.0);
-
-    Shadow {
-        start: top_left.into(),
-        end: bottom_right.into(),
+    #[test]
+    fn unpowered_camera_shows_nothing_test() {
+        let mut world = corridor();
+        let camera = camera(&mut world, true);
+        world
+            .write_storage::<PowerConsumer>()
+            .insert(
+                camera,
+                PowerConsumer {
+                    load: 1,
+                    priority: PowerPriority::Normal,
+                    connected: true,
+                    powered: false,
+                },
+            )
+            .unwrap();
+
+        take_turn(&mut world);
+
+        assert_eq!(VisibilityType::NotSeen, visibility_at(&world, 15));
     }
 }
-
-/// Transforms a (row, col) pair from the given octant into an actual position.
-/// Octants are labeled clockwise, sequentially
-///
-/// Note that (row, col) coordinates are such that the "row" axis extends positively
-/// from the observer, and the "col" axis is seen as horizontal and varies from 0
-/// to row (inclusive)
-fn transform_octant(row: i32, col: i32, octant: usize) -> TilePos {
-    let (x, y) = match octant {
-        // 0 is the N/NE octant, and is a y-reflection of 3 (S/SE)
-        0 => (col, -row),
-        // 1 is the E/NE octant, and is an x/y reflection of 0 (N/NE)
-        1 => (row, -col),
-        // 2 is the E/SE octant, and is an x/y reflection of 3 (S/SE)
-        2 => (row, col),
-        // 3 is the S/SE octant, where the major axis is y (positive is South, as desired) and minor
-        // is x, so line up with row,col perfectly
-        3 => (col, row),
-        // 4 is the S/SW octant, and is an x-reflection of 3 (S/SE)
-        4 => (-col, row),
-        // 5 is the W/SW octant, and is an x/y-reflection of 4 (S/SW)
-        5 => (-row, col),
-        // 6 is the W/NW octant, and is an x/y-reflection of 7 (N/NW)
-        6 => (-row, -col),
-        // 7 is the N/NW octant, and is an x-reflection of 0 (N/NE)
-        7 => (-col, -row),
-        // There are only octants 0..=7. The rusty thing would be to use enums,
-        // but they're annoying for enumeration through, and the octant is only
-        // used in this module, and only privately, so it should be alright
-        _ => panic!("Unsupported octant: {}", octant),
-    };
-
-    TilePos { x, y }
-}
@@ -0,0 +1,249 @@
+use super::*;
+
+use std::collections::HashMap;
+
+use components::*;
+use resources::NpcMoves;
+
+pub struct WindowUpdateSystem;
+
+#[derive(SystemData)]
+pub struct WindowUpdateSystemData<'a> {
+    has_position: ReadStorage<'a, HasPosition>,
+    window: WriteStorage<'a, Window>,
+    oxygen_cont: ReadStorage<'a, OxygenContainer>,
+    temperature: ReadStorage<'a, Temperature>,
+    char_render: WriteStorage<'a, CharRender>,
+
+    blocks_airflow: WriteStorage<'a, BlocksAirflow>,
+    blocks_movement: WriteStorage<'a, BlocksMovement>,
+    broken: WriteStorage<'a, Broken>,
+
+    npc_moves: Read<'a, NpcMoves>,
+    entities: Entities<'a>,
+}
+
+impl<'a> System<'a> for WindowUpdateSystem {
+    type SystemData = WindowUpdateSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        use constants::window::*;
+
+        if !data.npc_moves.move_was_made {
+            return;
+        }
+
+        let mut pressures: HashMap<TilePos, usize> = HashMap::new();
+        for (hp, oc) in (&data.has_position, &data.oxygen_cont).join() {
+            *pressures.entry(hp.position).or_insert(0) += oc.contents.pressure();
+        }
+
+        let mut temperatures: HashMap<TilePos, f32> = HashMap::new();
+        for (hp, temp) in (&data.has_position, &data.temperature).join() {
+            temperatures.insert(hp.position, temp.degrees);
+        }
+
+        for (window, has_pos, entity) in (&mut data.window, &data.has_position, &data.entities).join() {
+            // an open window isn't holding anything back
+            let holding = window.window_state == WindowState::Closed;
+            if holding && pressure_across(has_pos.position, &pressures) > window.integrity * PRESSURE_PER_INTEGRITY {
+                window.take_damage(PRESSURE_DAMAGE);
+            }
+
+            if temperatures.get(&has_pos.position).copied().unwrap_or(0.0) > SAFE_TEMPERATURE {
+                window.take_damage(HEAT_DAMAGE);
+            }
+
+            match window.window_state {
+                WindowState::Closed => {
+                    data.blocks_airflow
+                        .insert(entity, BlocksAirflow)
+                        .expect("The entity should be current");
+                    data.blocks_movement
+                        .insert(entity, BlocksMovement)
+                        .expect("The entity should be current");
+                }
+                WindowState::Open => {
+                    data.blocks_airflow.remove(entity);
+                    data.blocks_movement
+                        .insert(entity, BlocksMovement)
+                        .expect("The entity should be current");
+                }
+                WindowState::Broken => {
+                    data.blocks_airflow.remove(entity);
+                    data.blocks_movement.remove(entity);
+                    data.broken.insert(entity, Broken).expect("The entity should be current");
+                }
+            }
+
+            if let Some(renderable) = data.char_render.get_mut(entity) {
+                let (glyph, fg_color) = window_look(window);
+                renderable.glyph = glyph;
+                renderable.fg_color = fg_color;
+            }
+        }
+    }
+}
+
+// The difference between the air on either side of the pane; anywhere without air is open space
+fn pressure_across(pos: TilePos, pressures: &HashMap<TilePos, usize>) -> usize {
+    let around = direct_neighbors(pos)[1..]
+        .iter()
+        .map(|pos| pressures.get(pos).copied().unwrap_or(0))
+        .collect::<Vec<usize>>();
+
+    let highest = around.iter().max().copied().unwrap_or(0);
+    let lowest = around.iter().min().copied().unwrap_or(0);
+
+    highest - lowest
+}
+
+fn window_look(window: &Window) -> (char, Color) {
+    match window.window_state {
+        WindowState::Broken => ('%', BROKEN_FG),
+        WindowState::Open => (':', WINDOW_FG),
+        WindowState::Closed if window.integrity * 2 < constants::window::MAX_INTEGRITY => ('=', CRACKED_FG),
+        WindowState::Closed => ('=', WINDOW_FG),
+    }
+}
+
+const WINDOW_FG: Color = Color {
+    r: 0.6,
+    g: 0.8,
+    b: 1.0,
+    a: 1.0,
+};
+
+const CRACKED_FG: Color = Color {
+    r: 0.9,
+    g: 0.9,
+    b: 0.9,
+    a: 1.0,
+};
+
+const BROKEN_FG: Color = Color {
+    r: 0.5,
+    g: 0.5,
+    b: 0.5,
+    a: 1.0,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{Builder, RunNow, World, WorldExt};
+
+    use constants::window::*;
+
+    // A room full of air at x = 0, the window at x = 1, and space beyond it
+    fn hull(window: Window) -> (World, Entity) {
+        let mut world = World::new();
+        System::setup(&mut WindowUpdateSystem, &mut world);
+
+        world
+            .create_entity()
+            .with(HasPosition {
+                position: TilePos { x: 0, y: 0 },
+            })
+            .with(OxygenContainer {
+                capacity: constants::oxygen::DEFAULT_CONTAINER_CAPACITY,
+                contents: GasMixture::station_air(),
+            })
+            .build();
+
+        let window = world
+            .create_entity()
+            .with(HasPosition {
+                position: TilePos { x: 1, y: 0 },
+            })
+            .with(window)
+            .build();
+
+        (world, window)
+    }
+
+    fn closed(integrity: usize) -> Window {
+        Window {
+            window_state: WindowState::Closed,
+            integrity,
+        }
+    }
+
+    fn take_turn(world: &mut World) {
+        world.write_resource::<NpcMoves>().move_was_made = true;
+        WindowUpdateSystem.run_now(world);
+        world.maintain();
+    }
+
+    fn window_of(world: &World, entity: Entity) -> Window {
+        *world.read_storage::<Window>().get(entity).unwrap()
+    }
+
+    #[test]
+    fn new_window_holds_the_air_test() {
+        let (mut world, window) = hull(closed(MAX_INTEGRITY));
+
+        for _ in 0..100 {
+            take_turn(&mut world);
+        }
+
+        assert_eq!(closed(MAX_INTEGRITY), window_of(&world, window));
+    }
+
+    #[test]
+    fn damaged_window_gives_way_test() {
+        let (mut world, window) = hull(closed(MAX_INTEGRITY - IMPACT_DAMAGE));
+
+        take_turn(&mut world);
+        assert_eq!(closed(MAX_INTEGRITY - IMPACT_DAMAGE - PRESSURE_DAMAGE), window_of(&world, window));
+
+        // it only gets weaker, so the air pressure finishes it off
+        for _ in 0..MAX_INTEGRITY {
+            take_turn(&mut world);
+        }
+
+        assert_eq!(WindowState::Broken, window_of(&world, window).window_state);
+        assert!(world.read_storage::<Broken>().contains(window));
+        assert!(!world.read_storage::<BlocksAirflow>().contains(window));
+        assert!(!world.read_storage::<BlocksMovement>().contains(window));
+    }
+
+    #[test]
+    fn open_window_holds_nothing_back_test() {
+        let open = Window {
+            window_state: WindowState::Open,
+            integrity: 1,
+        };
+        let (mut world, window) = hull(open);
+
+        take_turn(&mut world);
+
+        assert_eq!(open, window_of(&world, window));
+        assert!(!world.read_storage::<BlocksAirflow>().contains(window));
+        assert!(world.read_storage::<BlocksMovement>().contains(window));
+    }
+
+    #[test]
+    fn heat_cracks_windows_test() {
+        let (mut world, window) = hull(closed(MAX_INTEGRITY));
+        world
+            .write_storage::<Temperature>()
+            .insert(
+                window,
+                Temperature {
+                    degrees: SAFE_TEMPERATURE + 1.0,
+                },
+            )
+            .unwrap();
+
+        take_turn(&mut world);
+        assert_eq!(closed(MAX_INTEGRITY - HEAT_DAMAGE), window_of(&world, window));
+
+        // and once it's weak enough, the pressure joins in
+        for _ in 0..MAX_INTEGRITY {
+            take_turn(&mut world);
+        }
+        assert_eq!(WindowState::Broken, window_of(&world, window).window_state);
+    }
+}
@@ -48,7 +48,7 @@ macro_rules! timed {
 #[cfg(not(feature = "timing"))]
 macro_rules! timed {
     ($name:expr, $to_run:expr) => {
-        $to_run;
+        $to_run
     };
 }
 
@@ -56,7 +56,7 @@ macro_rules! timed {
 // off (but still debug other things)
 macro_rules! not_timed {
     ($name:expr, $to_run:expr) => {
-        $to_run;
+        $to_run
     };
 }
 
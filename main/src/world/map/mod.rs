@@ -1,18 +1,14 @@
 use super::*;
 
+use std::collections::HashSet;
+
 use specs::Builder;
 
-mod params;
-mod rand_gen;
+use worldgen::{GenSquareType, GeneratedEntity, ItemKind};
 
-pub use params::MapGenerationParams;
+use lore::{LoreDatabase, LoreSource};
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub enum GenSquareType {
-    Floor,
-    Wall,
-    Open,
-}
+pub use worldgen::MapGenerationParams;
 
 fn to_real_square(kind: GenSquareType) -> Option<SquareType> {
     match kind {
@@ -33,6 +29,8 @@ pub enum VisibilityType {
     NotSeen,
     Remembered,
     CurrentlyVisible,
+    // visible right now, but only on a camera feed
+    SeenThroughFeed,
 }
 
 #[derive(Clone, Debug)]
@@ -98,6 +96,13 @@ fn get_blocks_airflow(kind: SquareType) -> bool {
     }
 }
 
+fn get_insulated(kind: SquareType) -> bool {
+    match kind {
+        SquareType::Floor => false,
+        SquareType::Wall => true,
+    }
+}
+
 fn get_vacuum(kind: SquareType) -> bool {
     match kind {
         SquareType::Floor => false,
@@ -107,7 +112,7 @@ fn get_vacuum(kind: SquareType) -> bool {
 
 impl Map {
     pub fn make_random(params: &MapGenerationParams, world: &mut World) -> Self {
-        let gen_result = rand_gen::rand_gen(params);
+        let gen_result = worldgen::rand_gen(params);
 
         let row_width = gen_result.width;
         let col_height = gen_result.height;
@@ -130,6 +135,8 @@ impl Map {
             );
         }
 
+        let conduits: HashSet<TilePos> = gen_result.conduits.iter().copied().collect();
+
         for square in gen_result.cells {
             if let Some(kind) = to_real_square(square.square_type) {
                 let mut tile_builder = world
@@ -143,8 +150,11 @@ impl Map {
                         position: TilePos { x, y },
                     })
                     .with(components::OxygenContainer {
-                        capacity: constants::oxygen::DEFAULT_FULL_OXYGEN,
-                        contents: constants::oxygen::DEFAULT_FULL_OXYGEN,
+                        capacity: constants::oxygen::DEFAULT_CONTAINER_CAPACITY,
+                        contents: components::GasMixture::station_air(),
+                    })
+                    .with(components::Temperature {
+                        degrees: constants::temperature::DEFAULT_STATION_TEMPERATURE,
                     })
                     .with(components::CharRender {
                         glyph: make_glyph(kind),
@@ -154,6 +164,9 @@ impl Map {
                         disabled: false,
                     });
 
+                if conduits.contains(&TilePos { x, y }) {
+                    tile_builder = tile_builder.with(components::Conduit);
+                }
                 if get_occludes(kind) {
                     tile_builder = tile_builder.with(components::BlocksVisibility);
                 }
@@ -163,6 +176,9 @@ impl Map {
                 if get_blocks_airflow(kind) {
                     tile_builder = tile_builder.with(components::BlocksAirflow);
                 }
+                if get_insulated(kind) {
+                    tile_builder = tile_builder.with(components::Insulated);
+                }
                 if get_vacuum(kind) {
                     tile_builder = tile_builder.with(components::Vacuum);
                 }
@@ -177,17 +193,20 @@ impl Map {
             }
         }
 
+        // lore is handed out in order, so the same seed puts the same text in the same places
+        let lore: LoreDatabase = (*world.read_resource::<LoreDatabase>()).clone();
+        let mut terminals_seen = 0;
+
         for other in gen_result.others {
             match other {
-                rand_gen::GeneratedEntity::Door(pos) => {
+                GeneratedEntity::Door(pos, network) => {
                     world
                         .create_entity()
                         .marked::<components::SaveComponent>()
                         .with(components::HasPosition { position: pos })
-                        .with(components::Hackable {
-                            name: "Door".to_owned(),
-                            hack_state: components::HackState::Uncompromised,
-                        })
+                        .with(components::Hackable::new("Door", constants::hacking::DOOR_SECURITY))
+                        .with(constants::repair::DOOR)
+                        .with(components::NetworkDevice { network })
                         .with(components::CharRender {
                             glyph: 'd',
                             z_level: components::ZLevel::OnFloor,
@@ -203,20 +222,25 @@ impl Map {
                         .with(components::Door {
                             door_behavior: components::DoorBehavior::FullAuto,
                             door_state: components::DoorState::Closed,
+                            access: components::DoorAccess::Everyone,
                         })
                         .with(components::BlocksAirflow)
                         .with(components::BlocksMovement)
+                        .with(components::Insulated)
+                        .with(components::PowerConsumer::new(
+                            constants::power::DOOR_LOAD,
+                            constants::power::DOOR_PRIORITY,
+                        ))
                         .build();
                 }
-                rand_gen::GeneratedEntity::Airlock(pos) => {
+                GeneratedEntity::Airlock(pos, network) => {
                     world
                         .create_entity()
                         .marked::<components::SaveComponent>()
                         .with(components::HasPosition { position: pos })
-                        .with(components::Hackable {
-                            name: "Airlock".to_owned(),
-                            hack_state: components::HackState::Uncompromised,
-                        })
+                        .with(components::Hackable::new("Airlock", constants::hacking::AIRLOCK_SECURITY))
+                        .with(constants::repair::DOOR)
+                        .with(components::NetworkDevice { network })
                         .with(components::CharRender {
                             glyph: 'A',
                             z_level: components::ZLevel::OnFloor,
@@ -232,19 +256,27 @@ impl Map {
                         .with(components::Door {
                             door_behavior: components::DoorBehavior::StayClosed,
                             door_state: components::DoorState::Closed,
+                            access: components::DoorAccess::Everyone,
                         })
                         .with(components::BlocksAirflow)
                         .with(components::BlocksMovement)
+                        .with(components::Insulated)
+                        .with(components::PowerConsumer::new(
+                            constants::power::DOOR_LOAD,
+                            constants::power::DOOR_PRIORITY,
+                        ))
                         .build();
                 }
-                rand_gen::GeneratedEntity::Alien(pos, color) => {
+                GeneratedEntity::Alien(pos, color) => {
                     world
                         .create_entity()
                         .marked::<components::SaveComponent>()
                         .with(components::Breathes::default())
                         .with(components::CanSuffocate::Death)
-                        .with(components::NPC::Alien(components::AlienAI::Wander))
+                        .with(components::NPC::Alien(components::AlienAI::new(pos)))
+                        .with(components::Inventory::new(constants::inventory::NPC_CAPACITY))
                         .with(components::OpensDoors)
+                        .with(components::Pushable { plugs_breaches: false })
                         .with(components::HasPosition { position: pos })
                         .with(components::CharRender {
                             glyph: 'N',
@@ -261,7 +293,7 @@ impl Map {
                         .with(components::BlocksMovement)
                         .build();
                 }
-                rand_gen::GeneratedEntity::Rubbish(pos) => {
+                GeneratedEntity::Rubbish(pos) => {
                     world
                         .create_entity()
                         .marked::<components::SaveComponent>()
@@ -282,10 +314,221 @@ impl Map {
                             visibility: VisibilityType::NotSeen,
                             memorable: false,
                         })
+                        .with(components::Flammable {
+                            fuel: constants::fire::RUBBISH_FUEL,
+                        })
+                        .with(components::Pushable { plugs_breaches: true })
                         .with(components::BlocksMovement)
                         .build();
                 }
-                rand_gen::GeneratedEntity::Pillar(pos) => {
+                GeneratedEntity::Heater(pos) => {
+                    world
+                        .create_entity()
+                        .marked::<components::SaveComponent>()
+                        .with(components::HasPosition { position: pos })
+                        .with(components::Hackable::new("Heater", constants::hacking::HEATER_SECURITY))
+                        .with(components::HeatSource {
+                            setting: components::HeatSetting::Heat,
+                        })
+                        .with(components::Flammable {
+                            fuel: constants::fire::HEATER_FUEL,
+                        })
+                        .with(components::PowerConsumer::new(
+                            constants::power::HEATER_LOAD,
+                            constants::power::HEATER_PRIORITY,
+                        ))
+                        .with(components::CharRender {
+                            glyph: 'h',
+                            z_level: components::ZLevel::OnFloor,
+                            bg_color: CLEAR,
+                            fg_color: quicksilver::graphics::Color {
+                                r: 1.0,
+                                g: 0.5,
+                                b: 0.2,
+                                a: 1.0,
+                            },
+                            disabled: false,
+                        })
+                        .with(components::Visible {
+                            visibility: VisibilityType::NotSeen,
+                            memorable: true,
+                        })
+                        .build();
+                }
+                GeneratedEntity::Window(pos) => {
+                    world
+                        .create_entity()
+                        .marked::<components::SaveComponent>()
+                        .with(components::HasPosition { position: pos })
+                        .with(components::Hackable::new("Window", constants::hacking::WINDOW_SECURITY))
+                        .with(constants::repair::WINDOW)
+                        .with(components::CharRender {
+                            glyph: '=',
+                            z_level: components::ZLevel::OnFloor,
+                            bg_color: CLEAR,
+                            fg_color: quicksilver::graphics::Color {
+                                r: 0.6,
+                                g: 0.8,
+                                b: 1.0,
+                                a: 1.0,
+                            },
+                            disabled: false,
+                        })
+                        .with(components::Visible {
+                            visibility: VisibilityType::NotSeen,
+                            memorable: true,
+                        })
+                        .with(components::Window {
+                            window_state: components::WindowState::Closed,
+                            integrity: constants::window::MAX_INTEGRITY,
+                        })
+                        .with(components::BlocksAirflow)
+                        .with(components::BlocksMovement)
+                        .build();
+                }
+                GeneratedEntity::OxygenGenerator(pos) => {
+                    spawn_life_support(world, pos, "Oxygen Generator", 'O', components::LifeSupportKind::OxygenGenerator);
+                }
+                GeneratedEntity::Scrubber(pos) => {
+                    spawn_life_support(world, pos, "CO2 Scrubber", 'S', components::LifeSupportKind::Scrubber);
+                }
+                GeneratedEntity::Vent(pos, network) => {
+                    spawn_life_support(world, pos, "Vent", 'v', components::LifeSupportKind::Vent { network });
+                }
+                GeneratedEntity::PowerGenerator(pos) => {
+                    world
+                        .create_entity()
+                        .marked::<components::SaveComponent>()
+                        .with(components::HasPosition { position: pos })
+                        .with(components::Hackable::new("Generator", constants::hacking::GENERATOR_SECURITY))
+                        .with(components::PowerGenerator {
+                            output: constants::power::GENERATOR_OUTPUT,
+                            enabled: true,
+                        })
+                        .with(components::CharRender {
+                            glyph: 'G',
+                            z_level: components::ZLevel::OnFloor,
+                            bg_color: CLEAR,
+                            // the power system colors this in, depending on whether it's running
+                            fg_color: Color::WHITE,
+                            disabled: false,
+                        })
+                        .with(components::Visible {
+                            visibility: VisibilityType::NotSeen,
+                            memorable: true,
+                        })
+                        .build();
+                }
+                GeneratedEntity::CeilingLight(pos) => {
+                    world
+                        .create_entity()
+                        .marked::<components::SaveComponent>()
+                        .with(components::HasPosition { position: pos })
+                        .with(components::Hackable::new("Ceiling Light", constants::hacking::LIGHT_SECURITY))
+                        .with(constants::repair::LIGHT)
+                        .with(components::LightSource {
+                            radius: constants::lighting::CEILING_LIGHT_RADIUS,
+                            enabled: true,
+                        })
+                        .with(components::PowerConsumer::new(
+                            constants::lighting::LIGHT_LOAD,
+                            constants::lighting::LIGHT_PRIORITY,
+                        ))
+                        .with(components::Breakable {
+                            condition: constants::lighting::MAX_CONDITION,
+                            max_condition: constants::lighting::MAX_CONDITION,
+                        })
+                        .with(components::CharRender {
+                            glyph: '*',
+                            z_level: components::ZLevel::OnFloor,
+                            bg_color: CLEAR,
+                            // the lighting system colors this in, depending on whether it's working
+                            fg_color: Color::WHITE,
+                            disabled: false,
+                        })
+                        .with(components::Visible {
+                            visibility: VisibilityType::NotSeen,
+                            memorable: true,
+                        })
+                        .build();
+                }
+                GeneratedEntity::Terminal(pos, network) => {
+                    let mut builder = world
+                        .create_entity()
+                        .marked::<components::SaveComponent>()
+                        .with(components::HasPosition { position: pos })
+                        .with(components::Hackable::new("Terminal", constants::hacking::TERMINAL_SECURITY))
+                        .with(components::Terminal { network })
+                        .with(components::CharRender {
+                            glyph: 't',
+                            z_level: components::ZLevel::OnFloor,
+                            bg_color: CLEAR,
+                            fg_color: quicksilver::graphics::Color {
+                                r: 0.4,
+                                g: 1.0,
+                                b: 0.4,
+                                a: 1.0,
+                            },
+                            disabled: false,
+                        })
+                        .with(components::Visible {
+                            visibility: VisibilityType::NotSeen,
+                            memorable: true,
+                        })
+                        .with(components::BlocksMovement);
+
+                    if let Some(entry) = lore.nth_from(LoreSource::Terminal, terminals_seen) {
+                        builder = builder.with(components::Readable { lore_id: entry.id.clone() });
+                    }
+                    terminals_seen += 1;
+
+                    builder.build();
+                }
+                GeneratedEntity::SecurityCamera(pos, network) => {
+                    world
+                        .create_entity()
+                        .marked::<components::SaveComponent>()
+                        .with(components::HasPosition { position: pos })
+                        .with(components::Hackable::new("Security Camera", constants::hacking::CAMERA_SECURITY))
+                        .with(constants::repair::CAMERA)
+                        .with(components::NetworkDevice { network })
+                        .with(components::SecurityCamera {
+                            range: constants::cameras::CAMERA_RANGE,
+                            feed_tapped: false,
+                        })
+                        .with(components::PowerConsumer::new(
+                            constants::cameras::CAMERA_LOAD,
+                            constants::cameras::CAMERA_PRIORITY,
+                        ))
+                        .with(components::CharRender {
+                            glyph: 'c',
+                            z_level: components::ZLevel::OnFloor,
+                            bg_color: CLEAR,
+                            fg_color: quicksilver::graphics::Color {
+                                r: 0.9,
+                                g: 0.3,
+                                b: 0.3,
+                                a: 1.0,
+                            },
+                            disabled: false,
+                        })
+                        .with(components::Visible {
+                            visibility: VisibilityType::NotSeen,
+                            memorable: true,
+                        })
+                        .build();
+                }
+                GeneratedEntity::Item(pos, kind) => {
+                    spawn_item(world, pos, kind);
+                }
+                GeneratedEntity::Corpse(pos, color, carrying) => spawn_corpse(world, pos, color, carrying),
+                GeneratedEntity::Note(pos, index) => {
+                    // more notes than lore just means some rooms go without
+                    if let Some(entry) = lore.nth_from(LoreSource::Note, index) {
+                        spawn_note(world, pos, entry.id.clone());
+                    }
+                }
+                GeneratedEntity::Pillar(pos) => {
                     world
                         .create_entity()
                         .marked::<components::SaveComponent>()
@@ -313,6 +556,8 @@ impl Map {
             }
         }
 
+        mark_broken(world, &gen_result.broken);
+
         Map {
             x_min,
             x_max,
@@ -323,3 +568,347 @@ impl Map {
         }
     }
 }
+
+// Whatever worldgen decided starts out broken needs repairing before it'll work
+fn mark_broken(world: &mut World, broken: &[TilePos]) {
+    let positions = world.read_storage::<components::HasPosition>();
+    let repairable = world.read_storage::<components::Repairable>();
+    let mut breakable = world.write_storage::<components::Breakable>();
+    let mut broken_storage = world.write_storage::<components::Broken>();
+    let entities = world.entities();
+
+    for (hp, _, entity) in (&positions, &repairable, &entities).join() {
+        if !broken.contains(&hp.position) {
+            continue;
+        }
+
+        if let Some(breakable) = breakable.get_mut(entity) {
+            breakable.condition = 0;
+        }
+        broken_storage
+            .insert(entity, components::Broken)
+            .expect("The entity was just created");
+    }
+}
+
+fn spawn_item(world: &mut World, pos: TilePos, kind: ItemKind) -> Entity {
+    use constants::equipment::*;
+
+    let (name, glyph) = match kind {
+        ItemKind::Keycard => ("Keycard", 'k'),
+        ItemKind::Helmet => ("Helmet", 'm'),
+        ItemKind::Flashlight => ("Flashlight", 'f'),
+        ItemKind::HackingRig => ("Hacking Rig", 'r'),
+        ItemKind::OxygenTank => ("Oxygen Tank", 'o'),
+        ItemKind::SpaceSuit => ("Space Suit", 'u'),
+    };
+
+    let mut builder = world
+        .create_entity()
+        .marked::<components::SaveComponent>()
+        .with(components::HasPosition { position: pos })
+        .with(components::Item { name: name.to_string() })
+        .with(components::CharRender {
+            glyph,
+            z_level: components::ZLevel::OnFloor,
+            bg_color: CLEAR,
+            fg_color: quicksilver::graphics::Color {
+                r: 1.0,
+                g: 0.9,
+                b: 0.3,
+                a: 1.0,
+            },
+            disabled: false,
+        })
+        .with(components::Visible {
+            visibility: VisibilityType::NotSeen,
+            memorable: false,
+        });
+
+    let equippable = |slot, modifiers| components::Equippable { slot, modifiers };
+    let no_modifiers = components::EquipModifiers::default();
+
+    builder = match kind {
+        ItemKind::Keycard => builder.with(components::Keycard),
+        ItemKind::Helmet => builder.with(equippable(
+            components::EquipSlot::Head,
+            components::EquipModifiers {
+                breathed_oxygen: Some(HELMET_BREATHED_OXYGEN),
+                ..no_modifiers
+            },
+        )),
+        ItemKind::Flashlight => builder.with(equippable(
+            components::EquipSlot::Hands,
+            components::EquipModifiers {
+                light_radius: constants::lighting::FLASHLIGHT_RADIUS,
+                ..no_modifiers
+            },
+        )),
+        ItemKind::HackingRig => builder.with(equippable(
+            components::EquipSlot::Back,
+            components::EquipModifiers {
+                hack_speedup_perc: HACKING_RIG_SPEEDUP_PERC,
+                ..no_modifiers
+            },
+        )),
+        ItemKind::OxygenTank => builder
+            .with(equippable(components::EquipSlot::Back, no_modifiers))
+            .with(components::OxygenTank {
+                capacity: TANK_CAPACITY,
+                contents: TANK_CAPACITY,
+            }),
+        ItemKind::SpaceSuit => builder.with(equippable(
+            components::EquipSlot::Body,
+            components::EquipModifiers {
+                sealed: true,
+                ..no_modifiers
+            },
+        )),
+    };
+
+    builder.build()
+}
+
+// Generated bodies look just like the ones left when aliens die in play; they've just been here longer
+fn spawn_corpse(world: &mut World, pos: TilePos, color: Color, carrying: Option<ItemKind>) {
+    let mut inventory = components::Inventory::new(constants::inventory::NPC_CAPACITY);
+
+    if let Some(kind) = carrying {
+        let item = spawn_item(world, pos, kind);
+        world.write_storage::<components::HasPosition>().remove(item);
+        inventory.items.push(item);
+    }
+
+    let mut builder = world
+        .create_entity()
+        .marked::<components::SaveComponent>()
+        .with(components::HasPosition { position: pos })
+        .with(components::Corpse {
+            name: "Alien".to_string(),
+            cause: components::DeathCause::Air,
+            clue: Some(constants::corpses::AIRLOCK_CLUE.to_string()),
+        })
+        .with(inventory)
+        .with(components::Pushable { plugs_breaches: false })
+        .with(components::CharRender {
+            glyph: constants::corpses::CORPSE_GLYPH,
+            z_level: components::ZLevel::OnFloor,
+            bg_color: CLEAR,
+            fg_color: color,
+            disabled: false,
+        })
+        .with(components::Visible {
+            visibility: VisibilityType::NotSeen,
+            memorable: true,
+        });
+
+    if constants::corpses::BLOCKS_MOVEMENT {
+        builder = builder.with(components::BlocksMovement);
+    }
+
+    builder.build();
+}
+
+fn spawn_note(world: &mut World, pos: TilePos, lore_id: String) {
+    world
+        .create_entity()
+        .marked::<components::SaveComponent>()
+        .with(components::HasPosition { position: pos })
+        .with(components::Readable { lore_id })
+        .with(components::CharRender {
+            glyph: '?',
+            z_level: components::ZLevel::OnFloor,
+            bg_color: CLEAR,
+            fg_color: quicksilver::graphics::Color {
+                r: 0.9,
+                g: 0.9,
+                b: 0.8,
+                a: 1.0,
+            },
+            disabled: false,
+        })
+        .with(components::Visible {
+            visibility: VisibilityType::NotSeen,
+            memorable: true,
+        })
+        .build();
+}
+
+fn spawn_life_support(world: &mut World, pos: TilePos, name: &str, glyph: char, kind: components::LifeSupportKind) {
+    let load = match kind {
+        components::LifeSupportKind::Vent { .. } => constants::power::VENT_LOAD,
+        _ => constants::power::LIFE_SUPPORT_LOAD,
+    };
+
+    let mut builder = world
+        .create_entity()
+        .marked::<components::SaveComponent>()
+        .with(components::HasPosition { position: pos })
+        .with(components::Hackable::new(name, constants::hacking::LIFE_SUPPORT_SECURITY))
+        .with(constants::repair::LIFE_SUPPORT)
+        .with(components::LifeSupport { kind, enabled: true })
+        .with(components::PowerConsumer::new(load, constants::power::LIFE_SUPPORT_PRIORITY))
+        .with(components::Breakable {
+            condition: constants::life_support::MAX_CONDITION,
+            max_condition: constants::life_support::MAX_CONDITION,
+        })
+        .with(components::CharRender {
+            glyph,
+            z_level: components::ZLevel::OnFloor,
+            bg_color: CLEAR,
+            // the life support system colors these in, depending on whether they're working
+            fg_color: Color::WHITE,
+            disabled: false,
+        })
+        .with(components::Visible {
+            visibility: VisibilityType::NotSeen,
+            memorable: true,
+        });
+
+    // vents are on the same network as their ducts
+    if let components::LifeSupportKind::Vent { network } = kind {
+        builder = builder.with(components::NetworkDevice { network });
+    }
+
+    builder.build();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::{RunNow, WorldExt};
+
+    use resources::{Callback, Callbacks, DialogueState, InventoryCallback, TakeDecision};
+    use worldgen::{MapDimensions, RoomDimensions};
+
+    fn make_world(seed: u64) -> World {
+        let mut world = World::new();
+        // between them, saving and loading touch every saved component a map can have; the walls'
+        // BlocksAirflow is the only unsaved one
+        System::setup(&mut systems::SerializeSystem, &mut world);
+        System::setup(&mut systems::DeserializeSystem, &mut world);
+        world.register::<components::BlocksAirflow>();
+        world.insert(LoreDatabase::default());
+
+        let params = MapGenerationParams {
+            room_dimensions: RoomDimensions {
+                room_min_width: 8,
+                room_max_width: 12,
+                room_min_height: 8,
+                room_max_height: 12,
+            },
+            map_dimensions: MapDimensions {
+                map_width: 60,
+                map_height: 30,
+            },
+            max_retries: 500,
+            seed,
+        };
+        Map::make_random(&params, &mut world);
+
+        world
+    }
+
+    #[test]
+    fn corpses_by_broken_airlocks_test() {
+        let mut corpses_seen = 0;
+        let mut items_seen = 0;
+
+        for seed in 0..20 {
+            let world = make_world(seed);
+
+            let has_pos = world.read_storage::<components::HasPosition>();
+            let corpse = world.read_storage::<components::Corpse>();
+            let inventory = world.read_storage::<components::Inventory>();
+            let hackable = world.read_storage::<components::Hackable>();
+            let broken = world.read_storage::<components::Broken>();
+            let item = world.read_storage::<components::Item>();
+
+            let broken_airlocks: Vec<TilePos> = (&has_pos, &hackable, &broken)
+                .join()
+                .filter(|(_, hackable, _)| hackable.name == "Airlock")
+                .map(|(hp, _, _)| hp.position)
+                .collect();
+
+            for (hp, corpse, inventory) in (&has_pos, &corpse, &inventory).join() {
+                corpses_seen += 1;
+
+                let pos = hp.position;
+                assert!(
+                    broken_airlocks.iter().any(|&a| (a.x - pos.x).abs() + (a.y - pos.y).abs() == 1),
+                    "seed {}: corpse at {:?} isn't by a broken airlock",
+                    seed,
+                    pos
+                );
+                assert_eq!(Some(constants::corpses::AIRLOCK_CLUE), corpse.clue.as_deref());
+
+                // whatever it's carrying is only found by searching it
+                for &carried in &inventory.items {
+                    items_seen += 1;
+                    assert!(item.contains(carried));
+                    assert!(!has_pos.contains(carried));
+                }
+            }
+        }
+
+        assert!(corpses_seen > 0, "No corpses in any of the maps");
+        assert!(items_seen > 0, "No corpse was carrying anything");
+    }
+
+    // The dialogue the handler just launched, if any
+    fn take_dialogue(world: &World) -> Option<DialogueState> {
+        world
+            .write_resource::<Callbacks>()
+            .take_some(|cb| match cb {
+                Callback::StartDialogue(state) => TakeDecision::Take(state),
+                x => TakeDecision::Leave(x),
+            })
+            .pop()
+    }
+
+    #[test]
+    fn searching_a_corpse_test() {
+        let (mut world, corpse) = (0..20)
+            .map(make_world)
+            .find_map(|world| {
+                let corpse = (
+                    &world.read_storage::<components::Corpse>(),
+                    &world.read_storage::<components::Inventory>(),
+                    &world.entities(),
+                )
+                    .join()
+                    .find(|(_, inventory, _)| !inventory.items.is_empty())
+                    .map(|(_, _, entity)| entity);
+                corpse.map(|corpse| (world, corpse))
+            })
+            .expect("Some corpse should be carrying something");
+
+        System::setup(&mut systems::InventoryCallbackHandlerSystem, &mut world);
+        let player = world
+            .create_entity()
+            .with(components::Player {})
+            .with(components::Inventory::new(constants::inventory::PLAYER_CAPACITY))
+            .build();
+
+        world
+            .write_resource::<Callbacks>()
+            .push(Callback::Inventory(InventoryCallback::Search { corpse }));
+        systems::InventoryCallbackHandlerSystem.run_now(&world);
+
+        let dialogue = take_dialogue(&world).expect("Searching should say what's there");
+        let words = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+        assert!(words(&dialogue.main_text).contains(constants::corpses::AIRLOCK_CLUE));
+
+        let take = dialogue
+            .options
+            .iter()
+            .find(|option| option.selected_text.starts_with("[Take the "))
+            .expect("There should be something to take");
+        world.write_resource::<Callbacks>().extend(take.callbacks.iter().cloned());
+        systems::InventoryCallbackHandlerSystem.run_now(&world);
+
+        assert_eq!(1, world.read_storage::<components::Inventory>().get(player).unwrap().items.len());
+        assert!(world.read_storage::<components::Inventory>().get(corpse).unwrap().items.is_empty());
+    }
+}
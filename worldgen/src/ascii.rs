@@ -0,0 +1,58 @@
+use super::*;
+
+impl MapGenResult {
+    /// Renders the map as plain text, one line per row, using (roughly) the same
+    /// glyphs the game uses. Generated entities are drawn on top of their squares.
+    pub fn to_ascii(&self) -> String {
+        let mut grid: Vec<char> = self
+            .cells
+            .iter()
+            .map(|square| match square.square_type {
+                GenSquareType::Floor => '.',
+                GenSquareType::Wall => '#',
+                GenSquareType::Open => ' ',
+            })
+            .collect();
+
+        for other in &self.others {
+            let (pos, glyph) = entity_glyph(other);
+            if pos.x >= 0 && pos.y >= 0 && self.check_index(pos.x as usize, pos.y as usize) {
+                grid[pos.y as usize * self.width + pos.x as usize] = glyph;
+            }
+        }
+
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in grid.chunks(self.width) {
+            out.extend(row.iter());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn entity_glyph(entity: &GeneratedEntity) -> (TilePos, char) {
+    match *entity {
+        GeneratedEntity::Rubbish(pos) => (pos, '`'),
+        GeneratedEntity::Pillar(pos) => (pos, 'I'),
+        GeneratedEntity::Door(pos, _) => (pos, 'd'),
+        GeneratedEntity::Airlock(pos, _) => (pos, 'A'),
+        GeneratedEntity::Alien(pos, _) => (pos, 'N'),
+        GeneratedEntity::Heater(pos) => (pos, 'h'),
+        GeneratedEntity::Window(pos) => (pos, '='),
+        GeneratedEntity::OxygenGenerator(pos) => (pos, 'O'),
+        GeneratedEntity::Scrubber(pos) => (pos, 'S'),
+        GeneratedEntity::Vent(pos, _) => (pos, 'v'),
+        GeneratedEntity::PowerGenerator(pos) => (pos, 'G'),
+        GeneratedEntity::CeilingLight(pos) => (pos, '*'),
+        GeneratedEntity::Terminal(pos, _) => (pos, 't'),
+        GeneratedEntity::SecurityCamera(pos, _) => (pos, 'c'),
+        GeneratedEntity::Item(pos, ItemKind::Keycard) => (pos, 'k'),
+        GeneratedEntity::Item(pos, ItemKind::Helmet) => (pos, 'm'),
+        GeneratedEntity::Item(pos, ItemKind::Flashlight) => (pos, 'f'),
+        GeneratedEntity::Item(pos, ItemKind::HackingRig) => (pos, 'r'),
+        GeneratedEntity::Item(pos, ItemKind::OxygenTank) => (pos, 'o'),
+        GeneratedEntity::Item(pos, ItemKind::SpaceSuit) => (pos, 'u'),
+        GeneratedEntity::Corpse(pos, _, _) => (pos, '%'),
+        GeneratedEntity::Note(pos, _) => (pos, '?'),
+    }
+}
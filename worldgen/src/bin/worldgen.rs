@@ -0,0 +1,181 @@
+//! Small CLI for poking at the map generator without launching the game.
+//!
+//! Examples:
+//!     cargo run -p worldgen -- --seed 128763297
+//!     cargo run -p worldgen -- --sweep 200 --seed 1
+//!     cargo run -p worldgen -- --params path/to/map_params.ron --no-map
+
+use std::process::exit;
+
+use worldgen::{rand_gen, MapGenerationParams, MapStats};
+
+const DEFAULT_PARAMS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../main/static/config/map_params.ron");
+
+const USAGE: &str = "Usage: worldgen [--params <path>] [--seed <seed>] [--sweep <count>] [--no-map]
+
+    --params <path>   RON file with map generation params (default: the game's map_params.ron)
+    --seed <seed>     override the seed from the params file
+    --sweep <count>   generate <count> maps with consecutive seeds, starting at the seed,
+                      and report stats for each, plus a summary
+    --no-map          don't print the map itself (ignored for sweeps, which never print maps)";
+
+struct Args {
+    params_path: String,
+    seed: Option<u64>,
+    sweep: Option<u64>,
+    print_map: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        params_path: DEFAULT_PARAMS_PATH.to_string(),
+        seed: None,
+        sweep: None,
+        print_map: true,
+    };
+
+    let mut raw = std::env::args().skip(1);
+
+    while let Some(arg) = raw.next() {
+        match arg.as_str() {
+            "--params" => args.params_path = raw.next().ok_or("--params needs a path")?,
+            "--seed" => args.seed = Some(parse_num(raw.next(), "--seed")?),
+            "--sweep" => args.sweep = Some(parse_num(raw.next(), "--sweep")?),
+            "--no-map" => args.print_map = false,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                exit(0);
+            }
+            other => return Err(format!("Unrecognized argument: {}", other)),
+        }
+    }
+
+    Ok(args)
+}
+
+fn parse_num(raw: Option<String>, flag: &str) -> Result<u64, String> {
+    let raw = raw.ok_or_else(|| format!("{} needs a number", flag))?;
+    raw.parse().map_err(|_| format!("{} needs a number, got '{}'", flag, raw))
+}
+
+fn load_params(path: &str) -> Result<MapGenerationParams, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    ron::de::from_bytes(&bytes).map_err(|e| format!("Could not parse {}: {}", path, e))
+}
+
+fn stats_line(seed: u64, stats: &MapStats) -> String {
+    format!(
+        "seed={:<12} rooms={:<4} doors={:<4} airlocks={:<4} aliens={:<4} rubbish={:<4} pillars={:<4} heaters={:<4} windows={:<4} life_support={:<4} generators={:<4} lights={:<4} terminals={:<4} cameras={:<4} items={:<4} corpses={:<4} notes={:<4} broken={:<4} floor={:<6} regions={:<3} largest={:.1}%{}",
+        seed,
+        stats.rooms,
+        stats.doors,
+        stats.airlocks,
+        stats.aliens,
+        stats.rubbish,
+        stats.pillars,
+        stats.heaters,
+        stats.windows,
+        stats.life_support,
+        stats.generators,
+        stats.lights,
+        stats.terminals,
+        stats.cameras,
+        stats.items,
+        stats.corpses,
+        stats.notes,
+        stats.broken,
+        stats.floor_tiles,
+        stats.regions,
+        largest_region_perc(stats),
+        if stats.is_connected() { "" } else { "  (DISCONNECTED)" },
+    )
+}
+
+fn largest_region_perc(stats: &MapStats) -> f32 {
+    if stats.floor_tiles == 0 {
+        0.0
+    } else {
+        100.0 * (stats.largest_region as f32) / (stats.floor_tiles as f32)
+    }
+}
+
+fn run_single(params: &MapGenerationParams, print_map: bool) {
+    let map = rand_gen(params);
+
+    if print_map {
+        print!("{}", map.to_ascii());
+        println!();
+    }
+
+    println!("{}", stats_line(params.seed, &MapStats::from_map(&map)));
+}
+
+fn run_sweep(params: &MapGenerationParams, count: u64) {
+    let mut all_stats = Vec::with_capacity(count as usize);
+
+    for seed in params.seed..params.seed.saturating_add(count) {
+        let params = MapGenerationParams { seed, ..*params };
+        let stats = MapStats::from_map(&rand_gen(&params));
+        println!("{}", stats_line(seed, &stats));
+        all_stats.push(stats);
+    }
+
+    if all_stats.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Summary over {} seeds (min / mean / max):", all_stats.len());
+
+    let summarize = |name: &str, get: &dyn Fn(&MapStats) -> usize| {
+        let values: Vec<usize> = all_stats.iter().map(get).collect();
+        let min = values.iter().min().copied().unwrap_or(0);
+        let max = values.iter().max().copied().unwrap_or(0);
+        let mean = values.iter().sum::<usize>() as f32 / values.len() as f32;
+        println!("    {:<10} {:>6} / {:>8.1} / {:>6}", name, min, mean, max);
+    };
+
+    summarize("rooms", &|s| s.rooms);
+    summarize("doors", &|s| s.doors);
+    summarize("airlocks", &|s| s.airlocks);
+    summarize("aliens", &|s| s.aliens);
+    summarize("rubbish", &|s| s.rubbish);
+    summarize("pillars", &|s| s.pillars);
+    summarize("heaters", &|s| s.heaters);
+    summarize("windows", &|s| s.windows);
+    summarize("life supp.", &|s| s.life_support);
+    summarize("generators", &|s| s.generators);
+    summarize("lights", &|s| s.lights);
+    summarize("terminals", &|s| s.terminals);
+    summarize("cameras", &|s| s.cameras);
+    summarize("items", &|s| s.items);
+    summarize("corpses", &|s| s.corpses);
+    summarize("notes", &|s| s.notes);
+    summarize("broken", &|s| s.broken);
+    summarize("floor", &|s| s.floor_tiles);
+    summarize("regions", &|s| s.regions);
+
+    let disconnected = all_stats.iter().filter(|s| !s.is_connected()).count();
+    println!("    {} of {} maps are disconnected", disconnected, all_stats.len());
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, USAGE);
+        exit(2);
+    });
+
+    let mut params = load_params(&args.params_path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1);
+    });
+
+    if let Some(seed) = args.seed {
+        params.seed = seed;
+    }
+
+    match args.sweep {
+        Some(count) => run_sweep(&params, count),
+        None => run_single(&params, args.print_map),
+    }
+}
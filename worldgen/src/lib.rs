@@ -0,0 +1,53 @@
+//! Random generation of the station map.
+//!
+//! This is kept separate from the game so that it can be run (and inspected) without
+//! a window; see the `worldgen` binary for a small CLI around it.
+
+#![allow(clippy::collapsible_if)]
+#![allow(clippy::needless_range_loop)]
+
+use std::cmp::max;
+use std::ops::{Add, AddAssign};
+
+use quicksilver::graphics::Color;
+use serde::{Deserialize, Serialize};
+
+mod ascii;
+mod params;
+mod rand_gen;
+pub mod rng;
+mod stats;
+
+pub use params::{MapDimensions, MapGenerationParams, RoomDimensions};
+pub use rand_gen::{rand_gen, GenSquareType, GeneratedEntity, ItemKind, MapGenResult, Room, Square};
+pub use stats::MapStats;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize)]
+pub struct TilePos {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl TilePos {
+    pub fn manhattan_distance(self, other: TilePos) -> i32 {
+        max((self.x - other.x).abs(), (self.y - other.y).abs())
+    }
+}
+
+impl Add<TilePos> for TilePos {
+    type Output = TilePos;
+
+    fn add(self, rhs: TilePos) -> TilePos {
+        TilePos {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl AddAssign<TilePos> for TilePos {
+    fn add_assign(&mut self, rhs: TilePos) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
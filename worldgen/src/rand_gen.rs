@@ -0,0 +1,787 @@
+use super::*;
+
+use crate::rng::{make_rng, PalladRng, Rng};
+use std::cmp::{max, min};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum GenSquareType {
+    Floor,
+    Wall,
+    Open,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Room {
+    pub left: usize,
+    pub right: usize,
+    pub top: usize,
+    pub bottom: usize,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+struct Door {
+    x: usize,
+    y: usize,
+}
+
+impl Room {
+    fn bad_touch(&self, other: &Room) -> bool {
+        fn dimension_bad(a_min: usize, a_max: usize, b_min: usize, b_max: usize) -> bool {
+            !((a_min == b_max || a_max == b_min) || (a_max + 1 < b_min || b_max + 1 < a_min))
+        }
+
+        dimension_bad(self.left, self.right, other.left, other.right) && dimension_bad(self.top, self.bottom, other.top, other.bottom)
+    }
+
+    fn try_make_door(&self, other: &Room, rng: &mut PalladRng) -> Option<Door> {
+        // horizontal touching, maybe
+        if self.left == other.right || self.right == other.left {
+            let y_min = max(self.top + 1, other.top + 1);
+            let y_max = min(self.bottom - 1, other.bottom - 1);
+
+            if y_min > y_max {
+                None
+            } else {
+                let x = if self.left == other.right { self.left } else { self.right };
+                let y = rng.gen_range(y_min, y_max + 1);
+                Some(Door { x, y })
+            }
+        } else if self.top == other.bottom || self.bottom == other.top {
+            let x_min = max(self.left + 1, other.left + 1);
+            let x_max = min(self.right - 1, other.right - 1);
+
+            if x_min > x_max {
+                None
+            } else {
+                let x = rng.gen_range(x_min, x_max + 1);
+                let y = if self.top == other.bottom { self.top } else { self.bottom };
+                Some(Door { x, y })
+            }
+        } else {
+            None
+        }
+    }
+}
+
+fn make_random_rooms(params: &MapGenerationParams, rng: &mut PalladRng) -> Vec<Room> {
+    let width = params.map_dimensions.map_width;
+    let height = params.map_dimensions.map_height;
+
+    let min_width = params.room_dimensions.room_min_width;
+    let max_width = params.room_dimensions.room_max_width;
+
+    let min_height = params.room_dimensions.room_min_height;
+    let max_height = params.room_dimensions.room_max_height;
+
+    let mut rooms: Vec<Room> = Vec::new();
+    let mut retries_remaining = params.max_retries;
+
+    'room_loop: while retries_remaining > 0 {
+        let room_x = rng.gen_range(0, width - min_width);
+        let room_width = rng.gen_range(min_width, min(max_width, width - room_x));
+
+        let room_y = rng.gen_range(0, height - min_height);
+        let room_height = rng.gen_range(min_height, min(max_height, height - room_y));
+
+        let room = Room {
+            left: room_x,
+            right: room_x + room_width - 1,
+            top: room_y,
+            bottom: room_y + room_height - 1,
+        };
+
+        for old_room in &rooms {
+            if old_room.bad_touch(&room) {
+                retries_remaining -= 1;
+                continue 'room_loop;
+            }
+        }
+
+        rooms.push(room);
+    }
+
+    rooms
+}
+
+fn make_raw_square(square_type: GenSquareType) -> Square {
+    Square { square_type }
+}
+
+pub struct MapGenResult {
+    pub width: usize,
+    pub height: usize,
+    // the rooms which were carved out, in generation order
+    pub rooms: Vec<Room>,
+    // cells is row-by-row (C-indexed) for cells[x,y] is cells[y * width + x]
+    pub cells: Vec<Square>,
+    // just an array of random stuff that could be generated
+    pub others: Vec<GeneratedEntity>,
+    // positions of equipment (from `others`) which starts out broken
+    pub broken: Vec<TilePos>,
+    // squares with power conduit running through them; see lay_conduits
+    pub conduits: Vec<TilePos>,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Square {
+    pub square_type: GenSquareType,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum GeneratedEntity {
+    Rubbish(TilePos),
+    Pillar(TilePos),
+    // doors, airlocks and vents are devices on their section's network, numbered the same way as
+    // the duct networks; a terminal can reach every device on its network
+    Door(TilePos, usize),
+    Airlock(TilePos, usize),
+    Alien(TilePos, Color),
+    Heater(TilePos),
+    Window(TilePos),
+    OxygenGenerator(TilePos),
+    Scrubber(TilePos),
+    // vents with the same duct network number share their air
+    Vent(TilePos, usize),
+    PowerGenerator(TilePos),
+    CeilingLight(TilePos),
+    Terminal(TilePos, usize),
+    SecurityCamera(TilePos, usize),
+    Item(TilePos, ItemKind),
+    // the remains of an alien, maybe with something still on it
+    Corpse(TilePos, Color, Option<ItemKind>),
+    // a note left lying around; notes are numbered in placement order, which is the order
+    // the lore for them is handed out in
+    Note(TilePos, usize),
+}
+
+// Loose things lying around the station, which can be picked up and carried off
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ItemKind {
+    Keycard,
+    Helmet,
+    Flashlight,
+    HackingRig,
+    OxygenTank,
+    SpaceSuit,
+}
+
+// Things worth wearing, which turn up in some rooms
+const GEAR: [ItemKind; 5] = [
+    ItemKind::Helmet,
+    ItemKind::Flashlight,
+    ItemKind::HackingRig,
+    ItemKind::OxygenTank,
+    ItemKind::SpaceSuit,
+];
+
+impl GeneratedEntity {
+    /// Whether this needs a conduit to it; either it draws power, or it makes it
+    pub fn is_wired(&self) -> bool {
+        match *self {
+            GeneratedEntity::Door(_, _)
+            | GeneratedEntity::Airlock(_, _)
+            | GeneratedEntity::Heater(_)
+            | GeneratedEntity::OxygenGenerator(_)
+            | GeneratedEntity::Scrubber(_)
+            | GeneratedEntity::Vent(_, _)
+            | GeneratedEntity::PowerGenerator(_)
+            | GeneratedEntity::CeilingLight(_)
+            | GeneratedEntity::SecurityCamera(_, _) => true,
+            GeneratedEntity::Rubbish(_)
+            | GeneratedEntity::Pillar(_)
+            | GeneratedEntity::Alien(_, _)
+            | GeneratedEntity::Window(_)
+            | GeneratedEntity::Terminal(_, _)
+            | GeneratedEntity::Item(_, _)
+            | GeneratedEntity::Corpse(_, _, _)
+            | GeneratedEntity::Note(_, _) => false,
+        }
+    }
+
+    pub fn pos(&self) -> TilePos {
+        match *self {
+            GeneratedEntity::Rubbish(pos) => pos,
+            GeneratedEntity::Pillar(pos) => pos,
+            GeneratedEntity::Door(pos, _) => pos,
+            GeneratedEntity::Airlock(pos, _) => pos,
+            GeneratedEntity::Alien(pos, _) => pos,
+            GeneratedEntity::Heater(pos) => pos,
+            GeneratedEntity::Window(pos) => pos,
+            GeneratedEntity::OxygenGenerator(pos) => pos,
+            GeneratedEntity::Scrubber(pos) => pos,
+            GeneratedEntity::Vent(pos, _) => pos,
+            GeneratedEntity::PowerGenerator(pos) => pos,
+            GeneratedEntity::CeilingLight(pos) => pos,
+            GeneratedEntity::Terminal(pos, _) => pos,
+            GeneratedEntity::SecurityCamera(pos, _) => pos,
+            GeneratedEntity::Item(pos, _) => pos,
+            GeneratedEntity::Corpse(pos, _, _) => pos,
+            GeneratedEntity::Note(pos, _) => pos,
+        }
+    }
+}
+
+impl MapGenResult {
+    pub fn check_index(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        if !self.check_index(x, y) {
+            panic!(
+                "MapGen: Invalid x/y: x: {}, y: {}, width: {}, height: {}",
+                x, y, self.width, self.height
+            );
+        }
+
+        x + y * self.width
+    }
+
+    fn set_square(&mut self, x: usize, y: usize, square: Square) {
+        let ind = self.index(x, y);
+        self.cells[ind] = square;
+    }
+
+    pub fn get_square(&self, x: usize, y: usize) -> Option<Square> {
+        if !self.check_index(x, y) {
+            None
+        } else {
+            let ind = self.index(x, y);
+            Some(self.cells[ind])
+        }
+    }
+}
+
+pub fn rand_gen(params: &MapGenerationParams) -> MapGenResult {
+    let open = make_raw_square(GenSquareType::Open);
+    let floor = make_raw_square(GenSquareType::Floor);
+    let wall = make_raw_square(GenSquareType::Wall);
+
+    let width = params.map_dimensions.map_width;
+    let height = params.map_dimensions.map_height;
+    let seed = params.seed;
+
+    let capacity = width * height;
+    let mut cells = Vec::with_capacity(capacity);
+    for _ in 0..capacity {
+        cells.push(open);
+    }
+
+    let mut map = MapGenResult {
+        width,
+        height,
+        rooms: Vec::new(),
+        cells,
+        others: Vec::new(),
+        broken: Vec::new(),
+        conduits: Vec::new(),
+    };
+
+    let mut rng = make_rng(seed);
+
+    let rooms = make_random_rooms(params, &mut rng);
+
+    for room in &rooms {
+        for x in room.left..=room.right {
+            map.set_square(x, room.top, wall);
+            map.set_square(x, room.bottom, wall);
+        }
+
+        for y in room.top..=room.bottom {
+            if (room.left == 0
+                || map.get_square(room.left - 1, y)
+                    == Some(Square {
+                        square_type: GenSquareType::Open,
+                    }))
+                && rng.gen_range(1, 10) == 1
+            {
+                map.set_square(room.left, y, floor);
+                map.others.push(GeneratedEntity::Airlock(
+                    TilePos {
+                        x: room.left as i32,
+                        y: y as i32,
+                    },
+                    room_section(room, width, height),
+                ));
+            } else {
+                map.set_square(room.left, y, wall);
+            }
+            map.set_square(room.right, y, wall);
+        }
+
+        for x in (room.left + 1)..room.right {
+            for y in (room.top + 1)..room.bottom {
+                let next_perc = rng.gen_range(1, 101); // 1 to 100
+                if next_perc <= 3 {
+                    let color: Color = Color {
+                        r: rng.gen_range(0.4, 0.6),
+                        g: rng.gen_range(0.8, 1.0),
+                        b: rng.gen_range(0.2, 0.4),
+                        a: 1.0,
+                    };
+                    map.others.push(GeneratedEntity::Alien(TilePos { x: x as i32, y: y as i32 }, color));
+                } else if next_perc <= 7 {
+                    map.others.push(GeneratedEntity::Rubbish(TilePos { x: x as i32, y: y as i32 }));
+                } else if next_perc <= 12 {
+                    map.others.push(GeneratedEntity::Pillar(TilePos { x: x as i32, y: y as i32 }));
+                };
+
+                map.set_square(x, y, floor);
+            }
+        }
+    }
+
+    let num_rooms = rooms.len();
+
+    for i in 0..num_rooms {
+        let a = rooms[i];
+        for j in i + 1..num_rooms {
+            let b = rooms[j];
+
+            if let Some(door_val) = a.try_make_door(&b, &mut rng) {
+                map.set_square(door_val.x, door_val.y, floor);
+                map.others.push(GeneratedEntity::Door(
+                    TilePos {
+                        x: door_val.x as i32,
+                        y: door_val.y as i32,
+                    },
+                    room_section(&a, width, height),
+                ))
+            }
+        }
+    }
+
+    // Post-generation passes; these don't touch the rng, so they don't change the layout for a given seed
+    place_heaters(&mut map, &rooms);
+    place_windows(&mut map, seed);
+    place_life_support(&mut map, &rooms);
+    place_power_generators(&mut map, &rooms);
+    place_lights(&mut map, &rooms);
+    place_terminals(&mut map, &rooms);
+    place_cameras(&mut map, &rooms);
+    lay_conduits(&mut map, &rooms);
+    place_items(&mut map, &rooms, seed);
+    break_some_equipment(&mut map, seed);
+    place_corpses(&mut map, seed);
+    place_notes(&mut map, &rooms, seed);
+
+    map.rooms = rooms;
+
+    map
+}
+
+// The first interior square of the room (reading order) which doesn't already have something in it
+fn free_spot(map: &MapGenResult, room: &Room) -> Option<TilePos> {
+    ((room.top + 1)..room.bottom)
+        .flat_map(|y| ((room.left + 1)..room.right).map(move |x| TilePos { x: x as i32, y: y as i32 }))
+        .find(|&pos| !map.others.iter().any(|other| other.pos() == pos))
+}
+
+// Every room gets one heater
+fn place_heaters(map: &mut MapGenResult, rooms: &[Room]) {
+    for room in rooms {
+        if let Some(pos) = free_spot(map, room) {
+            map.others.push(GeneratedEntity::Heater(pos));
+        }
+    }
+}
+
+// Which section of the station a room belongs to; the map is split into quadrants, and each one
+// gets its own life support (and its own duct and device networks). A door between two sections
+// goes on the network of the room that was generated first.
+fn room_section(room: &Room, width: usize, height: usize) -> usize {
+    let center_x = (room.left + room.right) / 2;
+    let center_y = (room.top + room.bottom) / 2;
+
+    let horizontal = if center_x * 2 < width { 0 } else { 1 };
+    let vertical = if center_y * 2 < height { 0 } else { 1 };
+
+    horizontal + 2 * vertical
+}
+
+// Every room gets a vent into its section's duct network; the first room (in generation order) in
+// each section also gets an oxygen generator, and the second one gets a CO2 scrubber
+fn place_life_support(map: &mut MapGenResult, rooms: &[Room]) {
+    let mut rooms_seen_per_section: HashMap<usize, usize> = HashMap::new();
+
+    for room in rooms {
+        let section = room_section(room, map.width, map.height);
+
+        if let Some(pos) = free_spot(map, room) {
+            map.others.push(GeneratedEntity::Vent(pos, section));
+        }
+
+        let seen = rooms_seen_per_section.entry(section).or_insert(0);
+        let nth_in_section = *seen;
+        *seen += 1;
+
+        if let Some(pos) = free_spot(map, room) {
+            match nth_in_section {
+                0 => map.others.push(GeneratedEntity::OxygenGenerator(pos)),
+                1 => map.others.push(GeneratedEntity::Scrubber(pos)),
+                _ => {}
+            }
+        }
+    }
+}
+
+// The first room (in generation order) in each section gets a terminal onto that section's network
+fn place_terminals(map: &mut MapGenResult, rooms: &[Room]) {
+    let mut sections_seen: Vec<usize> = Vec::new();
+
+    for room in rooms {
+        let section = room_section(room, map.width, map.height);
+        if sections_seen.contains(&section) {
+            continue;
+        }
+
+        if let Some(pos) = free_spot(map, room) {
+            map.others.push(GeneratedEntity::Terminal(pos, section));
+            sections_seen.push(section);
+        }
+    }
+}
+
+// Every room gets a security camera, up in a corner if there's space for it, on its section's network
+fn place_cameras(map: &mut MapGenResult, rooms: &[Room]) {
+    for room in rooms {
+        let corner = TilePos {
+            x: (room.left + 1) as i32,
+            y: (room.top + 1) as i32,
+        };
+
+        let pos = if map.others.iter().any(|other| other.pos() == corner) {
+            free_spot(map, room)
+        } else {
+            Some(corner)
+        };
+
+        if let Some(pos) = pos {
+            map.others
+                .push(GeneratedEntity::SecurityCamera(pos, room_section(room, map.width, map.height)));
+        }
+    }
+}
+
+// Every room gets a light, in the middle of the room if there's space for it
+fn place_lights(map: &mut MapGenResult, rooms: &[Room]) {
+    for room in rooms {
+        let center = TilePos {
+            x: ((room.left + room.right) / 2) as i32,
+            y: ((room.top + room.bottom) / 2) as i32,
+        };
+
+        let pos = if map.others.iter().any(|other| other.pos() == center) {
+            free_spot(map, room)
+        } else {
+            Some(center)
+        };
+
+        if let Some(pos) = pos {
+            map.others.push(GeneratedEntity::CeilingLight(pos));
+        }
+    }
+}
+
+// Every this-many rooms on a single stretch of station gets another generator
+const ROOMS_PER_GENERATOR: usize = 4;
+
+// Rooms which share a wall share the conduit running round it (see lay_conduits), so each separate
+// piece of station starts out as its own grid; each one gets a generator in its first room, and
+// another for every few rooms after that, so that everything starts out powered
+fn place_power_generators(map: &mut MapGenResult, rooms: &[Room]) {
+    let pieces = station_pieces(map);
+
+    let mut rooms_seen_per_piece: HashMap<usize, usize> = HashMap::new();
+
+    for room in rooms {
+        let piece = pieces[map.index(room.left + 1, room.top + 1)];
+
+        let seen = rooms_seen_per_piece.entry(piece).or_insert(0);
+        let nth_in_piece = *seen;
+        *seen += 1;
+
+        if nth_in_piece % ROOMS_PER_GENERATOR == 0 {
+            if let Some(pos) = free_spot(map, room) {
+                map.others.push(GeneratedEntity::PowerGenerator(pos));
+            }
+        }
+    }
+}
+
+// Conduit runs all the way round the walls of every room (doors and airlocks included), with a
+// straight branch off to each machine inside, along the shortest way to the wall. The rings make the
+// main runs hard to cut, but a branch only reaches one machine, so cutting it cuts that machine off.
+fn lay_conduits(map: &mut MapGenResult, rooms: &[Room]) {
+    let mut conduits: Vec<TilePos> = Vec::new();
+    let mut laid: HashSet<TilePos> = HashSet::new();
+
+    let mut lay = |x: usize, y: usize| {
+        let pos = TilePos { x: x as i32, y: y as i32 };
+        if laid.insert(pos) {
+            conduits.push(pos);
+        }
+    };
+
+    for room in rooms {
+        for x in room.left..=room.right {
+            lay(x, room.top);
+            lay(x, room.bottom);
+        }
+        for y in room.top..=room.bottom {
+            lay(room.left, y);
+            lay(room.right, y);
+        }
+    }
+
+    for other in map.others.iter().filter(|other| other.is_wired()) {
+        let pos = other.pos();
+        let (x, y) = (pos.x as usize, pos.y as usize);
+
+        let room = rooms
+            .iter()
+            .find(|room| room.left < x && x < room.right && room.top < y && y < room.bottom);
+
+        if let Some(room) = room {
+            // ties go left, then right, then up, then down
+            let ways = [x - room.left, room.right - x, y - room.top, room.bottom - y];
+            let shortest = (0..ways.len()).min_by_key(|&way| ways[way]).unwrap();
+
+            for step in 0..ways[shortest] {
+                match shortest {
+                    0 => lay(x - step, y),
+                    1 => lay(x + step, y),
+                    2 => lay(x, y - step),
+                    _ => lay(x, y + step),
+                }
+            }
+        }
+    }
+
+    map.conduits = conduits;
+}
+
+// Labels each (non-open) square with which connected piece of the station it belongs to; open
+// squares get usize::MAX
+fn station_pieces(map: &MapGenResult) -> Vec<usize> {
+    let mut pieces = vec![usize::MAX; map.cells.len()];
+    let mut next_piece = 0;
+    let mut stack = Vec::new();
+
+    for start in 0..map.cells.len() {
+        if pieces[start] != usize::MAX || map.cells[start].square_type == GenSquareType::Open {
+            continue;
+        }
+
+        pieces[start] = next_piece;
+        stack.push(start);
+
+        while let Some(ind) = stack.pop() {
+            let (x, y) = (ind % map.width, ind / map.width);
+            let neighbors = [
+                (x > 0, ind.wrapping_sub(1)),
+                (x + 1 < map.width, ind + 1),
+                (y > 0, ind.wrapping_sub(map.width)),
+                (y + 1 < map.height, ind + map.width),
+            ];
+
+            for &(in_bounds, next) in neighbors.iter() {
+                if in_bounds && pieces[next] == usize::MAX && map.cells[next].square_type != GenSquareType::Open {
+                    pieces[next] = next_piece;
+                    stack.push(next);
+                }
+            }
+        }
+
+        next_piece += 1;
+    }
+
+    pieces
+}
+
+// Salt for the item rng, so scattering items around never changes the layout for a seed
+const ITEM_SEED_SALT: u64 = 0x0049_5445_4d53;
+
+// About one room in this many has a keycard lying around in it
+const KEYCARD_ONE_IN: u32 = 5;
+
+// About one room in this many has some gear lying around in it
+const GEAR_ONE_IN: u32 = 4;
+
+fn place_items(map: &mut MapGenResult, rooms: &[Room], seed: u64) {
+    let mut rng = make_rng(seed ^ ITEM_SEED_SALT);
+
+    for room in rooms {
+        let mut found = Vec::new();
+
+        if rng.gen_range(0, KEYCARD_ONE_IN) == 0 {
+            found.push(ItemKind::Keycard);
+        }
+        if rng.gen_range(0, GEAR_ONE_IN) == 0 {
+            found.push(GEAR[rng.gen_range(0, GEAR.len())]);
+        }
+
+        for kind in found {
+            if let Some(pos) = free_spot(map, room) {
+                map.others.push(GeneratedEntity::Item(pos, kind));
+            }
+        }
+    }
+}
+
+// Salt for the broken equipment rng, so breaking things never changes the layout for a seed
+const BROKEN_SEED_SALT: u64 = 0x4252_4f4b_454e;
+
+// About one in this many pieces of equipment starts out broken
+const BROKEN_ONE_IN: u32 = 10;
+
+// The station has been neglected for a while; some of its equipment needs repairs before it works.
+// Windows are left alone, since a broken window is a hole in the hull.
+fn break_some_equipment(map: &mut MapGenResult, seed: u64) {
+    let mut rng = make_rng(seed ^ BROKEN_SEED_SALT);
+
+    for other in &map.others {
+        let can_break = matches!(
+            other,
+            GeneratedEntity::Door(_, _)
+                | GeneratedEntity::Airlock(_, _)
+                | GeneratedEntity::OxygenGenerator(_)
+                | GeneratedEntity::Scrubber(_)
+                | GeneratedEntity::Vent(_, _)
+                | GeneratedEntity::CeilingLight(_)
+                | GeneratedEntity::SecurityCamera(_, _)
+        );
+
+        if can_break && rng.gen_range(0, BROKEN_ONE_IN) == 0 {
+            map.broken.push(other.pos());
+        }
+    }
+}
+
+// Salt for the corpse rng, so leaving bodies around never changes the layout for a seed
+const CORPSE_SEED_SALT: u64 = 0x434f_5250_5345;
+
+// About one broken airlock in this many has an alien who didn't make it lying next to it
+const CORPSE_ONE_IN: u32 = 2;
+
+// About one corpse in this many still has a keycard on it
+const CORPSE_KEYCARD_ONE_IN: u32 = 3;
+
+// Some of the aliens were caught out when the airlocks broke; their bodies are left just inside,
+// colored just like the living ones
+fn place_corpses(map: &mut MapGenResult, seed: u64) {
+    let mut rng = make_rng(seed ^ CORPSE_SEED_SALT);
+
+    let broken_airlocks: Vec<TilePos> = map
+        .others
+        .iter()
+        .filter_map(|other| match other {
+            GeneratedEntity::Airlock(pos, _) if map.broken.contains(pos) => Some(*pos),
+            _ => None,
+        })
+        .collect();
+
+    for airlock in broken_airlocks {
+        if rng.gen_range(0, CORPSE_ONE_IN) != 0 {
+            continue;
+        }
+
+        let inside = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .map(|(dx, dy)| TilePos {
+                x: airlock.x + dx,
+                y: airlock.y + dy,
+            })
+            .find(|&pos| {
+                pos.x >= 0
+                    && pos.y >= 0
+                    && map.get_square(pos.x as usize, pos.y as usize).map(|sq| sq.square_type) == Some(GenSquareType::Floor)
+                    && !map.others.iter().any(|other| other.pos() == pos)
+            });
+
+        if let Some(pos) = inside {
+            let color = Color {
+                r: rng.gen_range(0.4, 0.6),
+                g: rng.gen_range(0.8, 1.0),
+                b: rng.gen_range(0.2, 0.4),
+                a: 1.0,
+            };
+            let carrying = if rng.gen_range(0, CORPSE_KEYCARD_ONE_IN) == 0 {
+                Some(ItemKind::Keycard)
+            } else {
+                None
+            };
+            map.others.push(GeneratedEntity::Corpse(pos, color, carrying));
+        }
+    }
+}
+
+// Salt for the window rng, so the windows get their own stream of randomness and don't disturb
+// the rest of the map
+const WINDOW_SEED_SALT: u64 = 0x5749_4e44_4f57;
+
+// Roughly one in this many hull walls becomes a window
+const WINDOW_ONE_IN: u32 = 8;
+
+// Some of the walls which face directly into space become windows; only straight stretches of
+// wall are eligible, so windows never end up in corners or right next to an airlock
+fn place_windows(map: &mut MapGenResult, seed: u64) {
+    let mut rng = make_rng(seed ^ WINDOW_SEED_SALT);
+
+    let is_type = |map: &MapGenResult, x: i64, y: i64, square_type: GenSquareType| {
+        if x < 0 || y < 0 || !map.check_index(x as usize, y as usize) {
+            square_type == GenSquareType::Open
+        } else {
+            map.get_square(x as usize, y as usize).map(|sq| sq.square_type) == Some(square_type)
+        }
+    };
+
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let (xi, yi) = (x as i64, y as i64);
+
+            if !is_type(map, xi, yi, GenSquareType::Wall) {
+                continue;
+            }
+
+            let faces_space = |dx: i64, dy: i64| {
+                is_type(map, xi + dx, yi + dy, GenSquareType::Open)
+                    && is_type(map, xi - dx, yi - dy, GenSquareType::Floor)
+                    && is_type(map, xi + dy, yi + dx, GenSquareType::Wall)
+                    && is_type(map, xi - dy, yi - dx, GenSquareType::Wall)
+            };
+
+            let is_hull = faces_space(1, 0) || faces_space(-1, 0) || faces_space(0, 1) || faces_space(0, -1);
+
+            if is_hull && rng.gen_range(0, WINDOW_ONE_IN) == 0 {
+                map.set_square(x, y, make_raw_square(GenSquareType::Floor));
+                map.others.push(GeneratedEntity::Window(TilePos { x: x as i32, y: y as i32 }));
+            }
+        }
+    }
+}
+
+// Salt for the note rng, so leaving notes around never changes the layout for a seed
+const NOTE_SEED_SALT: u64 = 0x004e_4f54_4553;
+
+// About one room in this many has a note left lying in it
+const NOTE_ONE_IN: u32 = 3;
+
+// The crew left notes behind; the same seed always leaves the same notes in the same places,
+// so the player can find them again on the next loop
+fn place_notes(map: &mut MapGenResult, rooms: &[Room], seed: u64) {
+    let mut rng = make_rng(seed ^ NOTE_SEED_SALT);
+    let mut next_note = 0;
+
+    for room in rooms {
+        if rng.gen_range(0, NOTE_ONE_IN) != 0 {
+            continue;
+        }
+
+        if let Some(pos) = free_spot(map, room) {
+            map.others.push(GeneratedEntity::Note(pos, next_note));
+            next_note += 1;
+        }
+    }
+}
@@ -0,0 +1,491 @@
+//! Summary statistics for a generated map, mostly useful for eyeballing the generator
+//! (and noticing when a change to it does something unexpected)
+
+use super::*;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct MapStats {
+    pub rooms: usize,
+    pub doors: usize,
+    pub airlocks: usize,
+    pub aliens: usize,
+    pub rubbish: usize,
+    pub pillars: usize,
+    pub heaters: usize,
+    pub windows: usize,
+    // oxygen generators, scrubbers and vents, all together
+    pub life_support: usize,
+    pub generators: usize,
+    pub lights: usize,
+    pub terminals: usize,
+    pub cameras: usize,
+    pub items: usize,
+    pub corpses: usize,
+    pub notes: usize,
+    // equipment which starts out broken
+    pub broken: usize,
+    pub floor_tiles: usize,
+    // number of separate regions of floor; doors and airlocks sit on floor squares,
+    // so this is 1 exactly when every room can be reached from every other
+    pub regions: usize,
+    // number of floor squares in the biggest region
+    pub largest_region: usize,
+}
+
+impl MapStats {
+    pub fn from_map(map: &MapGenResult) -> MapStats {
+        let mut stats = MapStats {
+            rooms: map.rooms.len(),
+            broken: map.broken.len(),
+            ..Default::default()
+        };
+
+        for other in &map.others {
+            match other {
+                GeneratedEntity::Rubbish(_) => stats.rubbish += 1,
+                GeneratedEntity::Pillar(_) => stats.pillars += 1,
+                GeneratedEntity::Door(_, _) => stats.doors += 1,
+                GeneratedEntity::Airlock(_, _) => stats.airlocks += 1,
+                GeneratedEntity::Alien(_, _) => stats.aliens += 1,
+                GeneratedEntity::Heater(_) => stats.heaters += 1,
+                GeneratedEntity::Window(_) => stats.windows += 1,
+                GeneratedEntity::OxygenGenerator(_) | GeneratedEntity::Scrubber(_) | GeneratedEntity::Vent(_, _) => stats.life_support += 1,
+                GeneratedEntity::PowerGenerator(_) => stats.generators += 1,
+                GeneratedEntity::CeilingLight(_) => stats.lights += 1,
+                GeneratedEntity::Terminal(_, _) => stats.terminals += 1,
+                GeneratedEntity::SecurityCamera(_, _) => stats.cameras += 1,
+                GeneratedEntity::Item(_, _) => stats.items += 1,
+                GeneratedEntity::Corpse(_, _, _) => stats.corpses += 1,
+                GeneratedEntity::Note(_, _) => stats.notes += 1,
+            }
+        }
+
+        let is_floor = |ind: usize| map.cells[ind].square_type == GenSquareType::Floor;
+
+        stats.floor_tiles = (0..map.cells.len()).filter(|&ind| is_floor(ind)).count();
+
+        let mut seen = vec![false; map.cells.len()];
+        let mut stack = Vec::new();
+
+        for start in 0..map.cells.len() {
+            if seen[start] || !is_floor(start) {
+                continue;
+            }
+
+            stats.regions += 1;
+            seen[start] = true;
+            stack.push(start);
+
+            let mut region_size = 0;
+
+            while let Some(ind) = stack.pop() {
+                region_size += 1;
+
+                let (x, y) = (ind % map.width, ind / map.width);
+                let mut visit = |nx: usize, ny: usize| {
+                    let next = nx + ny * map.width;
+                    if !seen[next] && is_floor(next) {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                };
+
+                if x > 0 {
+                    visit(x - 1, y);
+                }
+                if x + 1 < map.width {
+                    visit(x + 1, y);
+                }
+                if y > 0 {
+                    visit(x, y - 1);
+                }
+                if y + 1 < map.height {
+                    visit(x, y + 1);
+                }
+            }
+
+            stats.largest_region = max(stats.largest_region, region_size);
+        }
+
+        stats
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.regions <= 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    fn from_rows(rows: &[&str], others: Vec<GeneratedEntity>) -> MapGenResult {
+        let cells = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| Square {
+                square_type: match c {
+                    '.' => GenSquareType::Floor,
+                    '#' => GenSquareType::Wall,
+                    _ => GenSquareType::Open,
+                },
+            })
+            .collect();
+
+        MapGenResult {
+            width: rows[0].len(),
+            height: rows.len(),
+            rooms: Vec::new(),
+            cells,
+            others,
+            broken: Vec::new(),
+            conduits: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn connected_regions_test() {
+        let map = from_rows(&["#####   ", "#..##.# ", "#..#..# ", "#####   "], vec![]);
+
+        let stats = MapStats::from_map(&map);
+
+        assert_eq!(stats.floor_tiles, 7);
+        assert_eq!(stats.regions, 2);
+        assert_eq!(stats.largest_region, 4);
+        assert!(!stats.is_connected());
+    }
+
+    #[test]
+    fn entity_counts_test() {
+        let pos = TilePos { x: 1, y: 1 };
+        let map = from_rows(
+            &["###", "#.#", "###"],
+            vec![
+                GeneratedEntity::Door(pos, 0),
+                GeneratedEntity::Door(pos, 0),
+                GeneratedEntity::Airlock(pos, 0),
+                GeneratedEntity::Alien(pos, Color::WHITE),
+                GeneratedEntity::Pillar(pos),
+            ],
+        );
+
+        let stats = MapStats::from_map(&map);
+
+        assert_eq!(stats.doors, 2);
+        assert_eq!(stats.airlocks, 1);
+        assert_eq!(stats.aliens, 1);
+        assert_eq!(stats.pillars, 1);
+        assert_eq!(stats.rubbish, 0);
+        assert_eq!(stats.heaters, 0);
+        assert!(stats.is_connected());
+    }
+
+    #[test]
+    fn rand_gen_is_deterministic_test() {
+        let params = MapGenerationParams {
+            room_dimensions: RoomDimensions {
+                room_min_width: 8,
+                room_max_width: 12,
+                room_min_height: 8,
+                room_max_height: 12,
+            },
+            map_dimensions: MapDimensions {
+                map_width: 60,
+                map_height: 30,
+            },
+            max_retries: 500,
+            seed: 1234,
+        };
+
+        let first = rand_gen(&params);
+        let second = rand_gen(&params);
+
+        assert_eq!(first.to_ascii(), second.to_ascii());
+        assert_eq!(MapStats::from_map(&first), MapStats::from_map(&second));
+    }
+    #[test]
+    fn every_network_has_a_terminal_test() {
+        let params = MapGenerationParams {
+            room_dimensions: RoomDimensions {
+                room_min_width: 8,
+                room_max_width: 12,
+                room_min_height: 8,
+                room_max_height: 12,
+            },
+            map_dimensions: MapDimensions {
+                map_width: 60,
+                map_height: 30,
+            },
+            max_retries: 500,
+            seed: 4321,
+        };
+
+        let map = rand_gen(&params);
+
+        let terminals: Vec<usize> = map
+            .others
+            .iter()
+            .filter_map(|other| match *other {
+                GeneratedEntity::Terminal(_, network) => Some(network),
+                _ => None,
+            })
+            .collect();
+
+        for other in &map.others {
+            match *other {
+                GeneratedEntity::Door(_, network)
+                | GeneratedEntity::Airlock(_, network)
+                | GeneratedEntity::Vent(_, network)
+                | GeneratedEntity::SecurityCamera(_, network) => {
+                    assert!(terminals.contains(&network), "{:?} has no terminal on its network", other);
+                }
+                _ => {}
+            }
+        }
+
+        // and only one terminal per network
+        for (i, network) in terminals.iter().enumerate() {
+            assert!(!terminals[i + 1..].contains(network));
+        }
+    }
+
+    #[test]
+    fn devices_are_on_their_sections_network_test() {
+        for seed in 0..10 {
+            let params = MapGenerationParams {
+                room_dimensions: RoomDimensions {
+                    room_min_width: 8,
+                    room_max_width: 12,
+                    room_min_height: 8,
+                    room_max_height: 12,
+                },
+                map_dimensions: MapDimensions {
+                    map_width: 60,
+                    map_height: 30,
+                },
+                max_retries: 500,
+                seed,
+            };
+
+            let map = rand_gen(&params);
+
+            // sections are the quadrants of the map, numbered left to right, then top to bottom
+            let section = |room: &Room| {
+                let right_half = (room.left + room.right) / 2 * 2 >= map.width;
+                let bottom_half = (room.top + room.bottom) / 2 * 2 >= map.height;
+                (right_half as usize) + 2 * (bottom_half as usize)
+            };
+
+            for other in &map.others {
+                let (pos, network) = match *other {
+                    GeneratedEntity::Door(pos, network)
+                    | GeneratedEntity::Airlock(pos, network)
+                    | GeneratedEntity::Vent(pos, network)
+                    | GeneratedEntity::Terminal(pos, network)
+                    | GeneratedEntity::SecurityCamera(pos, network) => (pos, network),
+                    _ => continue,
+                };
+
+                // doors sit in the wall between two rooms, and go on the network of either one
+                let in_section = map.rooms.iter().any(|room| {
+                    let (x, y) = (pos.x as usize, pos.y as usize);
+                    room.left <= x && x <= room.right && room.top <= y && y <= room.bottom && section(room) == network
+                });
+                assert!(in_section, "seed {}: {:?} isn't in a room on its network", seed, other);
+            }
+        }
+    }
+
+    #[test]
+    fn corpses_lie_beside_broken_airlocks_test() {
+        for seed in 0..20 {
+            let params = MapGenerationParams {
+                room_dimensions: RoomDimensions {
+                    room_min_width: 8,
+                    room_max_width: 12,
+                    room_min_height: 8,
+                    room_max_height: 12,
+                },
+                map_dimensions: MapDimensions {
+                    map_width: 60,
+                    map_height: 30,
+                },
+                max_retries: 500,
+                seed,
+            };
+
+            let map = rand_gen(&params);
+
+            for other in &map.others {
+                if let GeneratedEntity::Corpse(pos, _, _) = *other {
+                    let beside_broken_airlock = map.others.iter().any(|airlock| match *airlock {
+                        GeneratedEntity::Airlock(a, _) => map.broken.contains(&a) && (a.x - pos.x).abs() + (a.y - pos.y).abs() == 1,
+                        _ => false,
+                    });
+                    assert!(
+                        beside_broken_airlock,
+                        "seed {}: corpse at {:?} isn't by a broken airlock",
+                        seed, pos
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn notes_are_numbered_in_order_test() {
+        for seed in 0..20 {
+            let params = MapGenerationParams {
+                room_dimensions: RoomDimensions {
+                    room_min_width: 8,
+                    room_max_width: 12,
+                    room_min_height: 8,
+                    room_max_height: 12,
+                },
+                map_dimensions: MapDimensions {
+                    map_width: 60,
+                    map_height: 30,
+                },
+                max_retries: 500,
+                seed,
+            };
+
+            let map = rand_gen(&params);
+
+            let notes: Vec<usize> = map
+                .others
+                .iter()
+                .filter_map(|other| match *other {
+                    GeneratedEntity::Note(_, index) => Some(index),
+                    _ => None,
+                })
+                .collect();
+
+            let expected: Vec<usize> = (0..notes.len()).collect();
+            assert_eq!(expected, notes, "seed {}", seed);
+        }
+    }
+
+    // Flood fills along the conduits from the start, returning every square reached
+    fn conduit_network(conduits: &HashSet<TilePos>, start: TilePos) -> HashSet<TilePos> {
+        let mut reached: HashSet<TilePos> = HashSet::new();
+        let mut to_process = vec![start];
+
+        while let Some(pos) = to_process.pop() {
+            if !conduits.contains(&pos) || !reached.insert(pos) {
+                continue;
+            }
+            for &(dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+                to_process.push(TilePos {
+                    x: pos.x + dx,
+                    y: pos.y + dy,
+                });
+            }
+        }
+
+        reached
+    }
+
+    #[test]
+    fn machines_are_wired_to_generators_test() {
+        for seed in 0..10 {
+            let params = MapGenerationParams {
+                room_dimensions: RoomDimensions {
+                    room_min_width: 8,
+                    room_max_width: 12,
+                    room_min_height: 8,
+                    room_max_height: 12,
+                },
+                map_dimensions: MapDimensions {
+                    map_width: 60,
+                    map_height: 30,
+                },
+                max_retries: 500,
+                seed,
+            };
+
+            let map = rand_gen(&params);
+            let conduits: HashSet<TilePos> = map.conduits.iter().copied().collect();
+
+            // conduits go where they're needed, not everywhere
+            let floor = map.cells.iter().filter(|c| c.square_type == GenSquareType::Floor).count();
+            assert!(conduits.len() < floor, "seed {} has conduit everywhere", seed);
+
+            let generators: Vec<TilePos> = map
+                .others
+                .iter()
+                .filter_map(|other| match *other {
+                    GeneratedEntity::PowerGenerator(pos) => Some(pos),
+                    _ => None,
+                })
+                .collect();
+
+            for other in map.others.iter().filter(|other| other.is_wired()) {
+                let network = conduit_network(&conduits, other.pos());
+                assert!(
+                    generators.iter().any(|generator| network.contains(generator)),
+                    "seed {}: {:?} can't reach a generator",
+                    seed,
+                    other
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cutting_a_branch_cuts_off_the_machine_test() {
+        let params = MapGenerationParams {
+            room_dimensions: RoomDimensions {
+                room_min_width: 8,
+                room_max_width: 12,
+                room_min_height: 8,
+                room_max_height: 12,
+            },
+            map_dimensions: MapDimensions {
+                map_width: 60,
+                map_height: 30,
+            },
+            max_retries: 500,
+            seed: 1234,
+        };
+
+        let map = rand_gen(&params);
+        let mut conduits: HashSet<TilePos> = map.conduits.iter().copied().collect();
+
+        let generators: Vec<TilePos> = map
+            .others
+            .iter()
+            .filter_map(|other| match *other {
+                GeneratedEntity::PowerGenerator(pos) => Some(pos),
+                _ => None,
+            })
+            .collect();
+
+        // lights go in the middle of their rooms, so they're always at the end of a branch
+        let light = map
+            .others
+            .iter()
+            .find_map(|other| match *other {
+                GeneratedEntity::CeilingLight(pos) => Some(pos),
+                _ => None,
+            })
+            .expect("There should be a light somewhere");
+        assert!(generators.iter().any(|g| conduit_network(&conduits, light).contains(g)));
+
+        let branch: Vec<TilePos> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .iter()
+            .map(|&(dx, dy)| TilePos {
+                x: light.x + dx,
+                y: light.y + dy,
+            })
+            .filter(|pos| conduits.contains(pos))
+            .collect();
+        assert_eq!(1, branch.len(), "The light should be at the end of a single branch");
+
+        // one square of conduit taken out, and the light is on its own
+        conduits.remove(&branch[0]);
+        assert!(!generators.iter().any(|g| conduit_network(&conduits, light).contains(g)));
+    }
+}